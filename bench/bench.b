@@ -0,0 +1,7 @@
+The classic benchmark of brainfuck implementations; prints the alphabet
+backwards with hundreds of millions of iterations of nested loops in between
+
+>++[<+++++++++++++>-]<[[>+>+<<-]>[<+>-]++++++++
+[>++++++++<-]>.[-]<<>++++++++++[>++++++++++[>++
+++++++++[>++++++++++[>++++++++++[>++++++++++[>+
++++++++++[-]<-]<-]<-]<-]<-]<-]<-]++++++++++.
@@ -0,0 +1,36 @@
+Factors all numbers from 2 to 255 by trial division and prints one line
+per number with its prime factors like the factor program of coreutils
+
+Mostly division by repeated subtraction
+
+[-]++[>>>>>>[-]++++++++++<<<<<<[->>>>>>>>>>>>>>>>>+<<<<<<+<<<<<<<<<<<]>>>>>>>>>>
+>>>>>>>[-<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>]<<<<<<<<<<<[->>>>>>>>>>>+<<<<<+<<<<
+<<]>>>>>>>>>>>[-<<<<<<<<<<<+>>>>>>>>>>>]<<<<<<[->-[>+>>]>[+[-<+>]>+>>]<<<<<]<<<<
+[-]>[-]>>>>[-]>[-<<<<<+>>>>>]>[-<<<<<<<+>>>>>>>]<<<<<<<[->>>>>>>>>>+<<<<<<+<<<<]
+>>>>>>>>>>[-<<<<<<<<<<+>>>>>>>>>>]<<<<<<<<<<<[->>>>>>>>>>>+<<<<<+<<<<<<]>>>>>>>>
+>>>[-<<<<<<<<<<<+>>>>>>>>>>>]<<<<<<[->-[>+>>]>[+[-<+>]>+>>]<<<<<]<<[-]>[-]>>[-]>
+[-<<<+>>>]>[-<<<<<+>>>>>]<<<<<[->>>+<+<<]>>>[-<<<+>>>]<[<<[->>>>+<+<<<]>>>>[-<<<
+<+>>>>]<++++++++++++++++++++++++++++++++++++++++++++++++.[-]<[-]]<<[->>>+<+<<]>>
+>[-<<<+>>>]<<[->>+<+<]>>[-<<+>>]<[->>+<+<]>>[-<<+>>]<[<<[->>>>+<+<<<]>>>>[-<<<<+
+>>>>]<++++++++++++++++++++++++++++++++++++++++++++++++.[-]<[-]]<<<<[->>>>>+<+<<<
+<]>>>>>[-<<<<<+>>>>>]<++++++++++++++++++++++++++++++++++++++++++++++++.[-]<<<<<<
+[-]>[-]>[-]>[-]>[-]>[-]<<<<<++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++.[-]<<<<<[-]<[->>>>>>+<<<<<+<]>>>>>>[-<<<<<<+>>>>>>]<<<<[-]++>>>>[-]<<<<<[
+->>>>>>+<+<<<<<]>>>>>>[-<<<<<<+>>>>>>]<-[<<<<<[->>>>>>>>>>>>+<<<<<<+<<<<<<]>>>>>
+>>>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>>]<<<<<<<<<<<[->>>>>>>>>>>+<<<<<+<<<<<<]>>>>>>>
+>>>>[-<<<<<<<<<<<+>>>>>>>>>>>]<<<<<<[->-[>+>>]>[+[-<+>]>+>>]<<<<<]<<<<[-]>[-]>>>
+>[-]>[-<<<<<+>>>>>]>[-<<<<<<<+>>>>>>>]<<<<<<[->>>>+<+<<<]>>>>[-<<<<+>>>>][-]+<[<
+<<<<+>>>>>>[-]<[-]]>[>++++++++++++++++++++++++++++++++.[-][-]++++++++++<<<<<<<[-
+>>>>>>>>>>>>>>>>>>+<<<<<<+<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<+>>
+>>>>>>>>>>>>>>>>]<<<<<<<<<<<[->>>>>>>>>>>+<<<<<+<<<<<<]>>>>>>>>>>>[-<<<<<<<<<<<+
+>>>>>>>>>>>]<<<<<<[->-[>+>>]>[+[-<+>]>+>>]<<<<<]<<<<[-]>[-]>>>>[-]>[-<<<<<+>>>>>
+]>[-<<<<<<<+>>>>>>>]<<<<<<<[->>>>>>>>>>+<<<<<<+<<<<]>>>>>>>>>>[-<<<<<<<<<<+>>>>>
+>>>>>]<<<<<<<<<<<[->>>>>>>>>>>+<<<<<+<<<<<<]>>>>>>>>>>>[-<<<<<<<<<<<+>>>>>>>>>>>
+]<<<<<<[->-[>+>>]>[+[-<+>]>+>>]<<<<<]<<[-]>[-]>>[-]>[-<<<+>>>]>[-<<<<<+>>>>>]<<<
+<<[->>>+<+<<]>>>[-<<<+>>>]<[<<[->>>>+<+<<<]>>>>[-<<<<+>>>>]<++++++++++++++++++++
+++++++++++++++++++++++++++++.[-]<[-]]<<[->>>+<+<<]>>>[-<<<+>>>]<<[->>+<+<]>>[-<<
++>>]<[->>+<+<]>>[-<<+>>]<[<<[->>>>+<+<<<]>>>>[-<<<<+>>>>]<++++++++++++++++++++++
+++++++++++++++++++++++++++.[-]<[-]]<<<<[->>>>>+<+<<<<]>>>>>[-<<<<<+>>>>>]<++++++
+++++++++++++++++++++++++++++++++++++++++++.[-]<<<<<<[-]>[-]>[-]>[-]>[-]>[-]<<<<<
+<<<<<<<<[-]>>[-<<+>>]>>>>>[-]]<<<<<[-]>[-]>>[-]<<<<<[->>>>>>+<+<<<<<]>>>>>>[-<<<
+<<<+>>>>>>]<-]++++++++++.[-]<<<<<<+]
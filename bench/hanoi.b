@@ -0,0 +1,320 @@
+Solves the towers of Hanoi for 16 disks and prints all 65535 moves
+
+Every move increments a binary counter; the lowest bit which gets set is the
+disk to move; every disk cycles through the pegs in a fixed direction
+
+Mostly branches and output
+
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]+[<[-]+[->>>+<+<<]>>>[-<<<+>>>]<[<<<<<<<<<<<
+<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<
+<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>][-]+<[<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<[-]]>[<<<<<<<<<<<<<<<<<<
+<<<<<<<<<<<<<<<<<<[-]+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+++++++++++++++++++++
++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.
++++++.++++++++++.--------.------------------------------------------------------
+---------------------.+++++++++++++++++.+++++++++.--------------------------.[-]
+<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>
+>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>]<+++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++.[-]++++++++++++++++++++++++++++++
+++.+++++++++++++.+++++++++++++++++.------------------------------.[-]<<<<<<<<<<<
+<<<<<<<<<<+[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<+<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>
+>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>
+>>>>]<<<<<+++<[->-[>+>>]>[+[-<+>]>+>>]<<<<<]<<[-]>[-]>>[-]>[-<<<+>>>]>[-<<<<<+>>
+>>>]<<<<<<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<+
+>>>>>>>>>>>>>>>>>>>>>>]<[-]<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>+<+<<<<<
+<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>
+>>>>>>]<+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.[-]+++
++++++++.[-]<[-]]<<<<[-]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>
+>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+>>>][-]+<[>[-]<[-]]>[<<<<[-]+>>>>[-]]<<[-]]<<[->>>+<+<<]>>>[-<<<+>>>]<[<<<<<<<<<
+<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<
+<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>][-]+<[<<<<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<[-]]>[<<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<<<<<<[-]+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+++++++++++++++++++++++++++++
++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.+++++.++
+++++++++.--------.--------------------------------------------------------------
+-------------.++++++++++++++++++.++++++++.--------------------------.[-]<<<<<<<<
+<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>
+[-<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>]<+++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++.[-]++++++++++++++++++++++++++++++++.+++++++++++
+++.+++++++++++++++++.------------------------------.[-]<<<<<<<<<<<<<<<<<<<<++[->
+>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<+<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>
+>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<+++<[->-[
+>+>>]>[+[-<+>]>+>>]<<<<<]<<[-]>[-]>>[-]>[-<<<+>>>]>[-<<<<<+>>>>>]<<<<<<<<<<<<<<<
+<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>]
+<[-]<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>
+>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>]<+++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++.[-]++++++++++.[-]<[-]]<<<<[-]<<<<<<
+<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<
+<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>][-]+<[>[-]<[-]]>[<<<<[-]+>>>>[-
+]]<<[-]]<<[->>>+<+<<]>>>[-<<<+>>>]<[<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>
+>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>
+>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+>>>>>>][-]+<[<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+>>>[-]<[-]]>[<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[-]+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+>>>>>>++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++.+++++.++++++++++.--------.---------------------------
+------------------------------------------------.+++++++++++++++++++.+++++++.---
+-----------------------.[-]<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<
+<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>]<++++
++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.[-]+++++++++++++++
++++++++++++++++++.+++++++++++++.+++++++++++++++++.------------------------------
+.[-]<<<<<<<<<<<<<<<<<<<+[->>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<+<<<<<<<<<<<<<<<<<<<
+<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>
+>>>>>>>]<<<<<+++<[->-[>+>>]>[+[-<+>]>+>>]<<<<<]<<[-]>[-]>>[-]>[-<<<+>>>]>[-<<<<<
++>>>>>]<<<<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<+>>>
+>>>>>>>>>>>>>>>>>]<[-]<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<
+<<<<<]>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>]<+++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++.[-]++++++++++.[-]<[-]]<
+<<<[-]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<
+<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<
+<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>][-]+<[>[-]<[-]]>[<<<<[-]+
+>>>>[-]]<<[-]]<<[->>>+<+<<]>>>[-<<<+>>>]<[<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>
+>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>
+>>>>>>][-]+<[<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+>[-]<[-]]>[<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[-]+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+>>++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++.+++++.++++++++++.--------.-------------------------------
+--------------------------------------------.++++++++++++++++++++.++++++.-------
+-------------------.[-]<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<
+<<<]>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>]<++++++++++++++
++++++++++++++++++++++++++++++++++++++++++++++++++++.[-]+++++++++++++++++++++++++
++++++++.+++++++++++++.+++++++++++++++++.------------------------------.[-]<<<<<<
+<<<<<<<<<<<<++[->>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<+<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>
+>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<++
++<[->-[>+>>]>[+[-<+>]>+>>]<<<<<]<<[-]>[-]>>[-]>[-<<<+>>>]>[-<<<<<+>>>>>]<<<<<<<<
+<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>]<
+[-]<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>
+>>>[-<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>]<++++++++++++++++++++++++++++++++++
++++++++++++++++++++++++++++++++.[-]++++++++++.[-]<[-]]<<<<[-]<<<<<<<<<<<<<<<<<<<
+<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>
+>>>>>>>>>>>>>>>>>>>>>>>][-]+<[>[-]<[-]]>[<<<<[-]+>>>>[-]]<<[-]]<<[->>>+<+<<]>>>[
+-<<<+>>>]<[<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<
+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<
+<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>][-]+<[<<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<[-]]>[<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+<<<<[-]+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+++++++++++++++++++++++++++++++++++++++
++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.+++++.++++++++++.-
+-------.------------------------------------------------------------------------
+---.+++++++++++++++++++++.+++++.--------------------------.[-]<<<<<<<<<<<<<<<<<[
+->>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<+>
+>>>>>>>>>>>>>>>>>]<+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++.[-]++++++++++++++++++++++++++++++++.+++++++++++++.+++++++++++++++++.-------
+-----------------------.[-]<<<<<<<<<<<<<<<<<+[->>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<+
+<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>
+>>>>>>>>>>>>>>>>>>]<<<<<+++<[->-[>+>>]>[+[-<+>]>+>>]<<<<<]<<[-]>[-]>>[-]>[-<<<+>
+>>]>[-<<<<<+>>>>>]<<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<
+<+>>>>>>>>>>>>>>>>>>]<[-]<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<
+<<]>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>]<++++++++++++++++++
++++++++++++++++++++++++++++++++++++++++++++++++.[-]++++++++++.[-]<[-]]<<<<[-]<<<
+<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<
+<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
++>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>][-]+<[>[-]<[-]]>[<<<<[-]+>>>>[-]]<<[-]]<<[->>>
++<+<<]>>>[-<<<+>>>]<[<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>
+>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<
+<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>][-]+<[<<<<<<<<<<<<<<<<<<<<
+<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<[-]]>[<<<<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<[-]+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+++++++++++++++++++++++++++++++++++++++
++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.+++++.++++++++++.-
+-------.------------------------------------------------------------------------
+---.++++++++++++++++++++++.++++.--------------------------.[-]<<<<<<<<<<<<<<<<[-
+>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<+>>>>>>
+>>>>>>>>>>>]<+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.[
+-]++++++++++++++++++++++++++++++++.+++++++++++++.+++++++++++++++++.-------------
+-----------------.[-]<<<<<<<<<<<<<<<<++[->>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<+<<<<<<<
+<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>
+>>>>>>>]<<<<<+++<[->-[>+>>]>[+[-<+>]>+>>]<<<<<]<<[-]>[-]>>[-]>[-<<<+>>>]>[-<<<<<
++>>>>>]<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>
+>>>>>]<[-]<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>
+>>[-<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>]<+++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++.[-]++++++++++.[-]<[-]]<<<<[-]<<<<<<<<<<<<<<<<<<<<<<<<
+<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>
+>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>
+>>>>>>][-]+<[>[-]<[-]]>[<<<<[-]+>>>>[-]]<<[-]]<<[->>>+<+<<]>>>[-<<<+>>>]<[<<<<<<
+<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>
+>>>>>>>>>>>>>>>>>>>>][-]+<[<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>
+>>>>>>>>>[-]<[-]]>[<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[-]+>>>>>>>>>>>>>>>>>>>>>>>>>>>
+>>>>++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++.+++++.++++++++++.--------.-----------------------------
+----------------------------------------------.+++++++++++++++++++++++.+++.-----
+---------------------.[-]<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<]>>>
+>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>]<++++++++++++++++++++++++++++++
++++++++++++++++++++++++++++++++++++.[-]++++++++++++++++++++++++++++++++.++++++++
++++++.+++++++++++++++++.------------------------------.[-]<<<<<<<<<<<<<<<+[->>>>
+>>>>>>>>>>>>>>>>>>>+<<<<<<+<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<
+<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>]<<<<<+++<[->-[>+>>]>[+[-<+>]>+>>]<<<<<]<<[
+-]>[-]>>[-]>[-<<<+>>>]>[-<<<<<+>>>>>]<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>[-<<
+<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>]<[-]<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>+<+<<<<<<<<
+<<<<<<<]>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>]<+++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++.[-]++++++++++.[-]<[-]]<<<<[-]<<<<
+<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>
+>>>>>>>>>>>>>>>>>>>>][-]+<[>[-]<[-]]>[<<<<[-]+>>>>[-]]<<[-]]<<[->>>+<+<<]>>>[-<<
+<+>>>]<[<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<
+<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>][-]+<[<<<<<<<<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>
+>>>>>>>>>>>>>>>[-]<[-]]>[<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[-]+>>>>>>>>>>>>>>>>>>>>>>
+>>>>>>>>++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++.+++++.++++++++++.--------.-------------------------
+--------------------------------------------------.++++++++++++++++++++++++.++.-
+-------------------------.[-]<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<]>>
+>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>]<++++++++++++++++++++++++++++++++
++++++++++++++++++++++++++++++++++.[-]++++++++++++++++++++++++++++++++.++++++++++
++++.+++++++++++++++++.------------------------------.[-]<<<<<<<<<<<<<<++[->>>>>>
+>>>>>>>>>>>>>>>>+<<<<<<+<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<
+<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>]<<<<<+++<[->-[>+>>]>[+[-<+>]>+>>]<<<<<]<<[-]>[-]>
+>[-]>[-<<<+>>>]>[-<<<<<+>>>>>]<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>[-<<<<<<<<<<<
+<<<<+>>>>>>>>>>>>>>>]<[-]<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<]>>>>>>
+>>>>>>>>>[-<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>]<++++++++++++++++++++++++++++++++++++
++++++++++++++++++++++++++++++.[-]++++++++++.[-]<[-]]<<<<[-]<<<<<<<<<<<<<<<<<<<<<
+<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>
+>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>][-
+]+<[>[-]<[-]]>[<<<<[-]+>>>>[-]]<<[-]]<<[->>>+<+<<]>>>[-<<<+>>>]<[<<<<<<<<<<<<<<<
+<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>
+>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>]
+[-]+<[<<<<<<<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<[-]]>[<<<<<<
+<<<<<<<<<<<<<<<<<<<<<<[-]+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+++++++++++++++++++++++++
++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.++++
++.++++++++++.--------.----------------------------------------------------------
+-----------------.+++++++++++++++++++++++++.+.--------------------------.[-]<<<<
+<<<<<<<<<[->>>>>>>>>>>>>>+<+<<<<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>
+>>>>>>>]<+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.[-]++
+++++++++++++++++++++++++++++++.+++++++++++++.+++++++++++++++++.-----------------
+-------------.[-]<<<<<<<<<<<<<+[->>>>>>>>>>>>>>>>>>>>>+<<<<<<+<<<<<<<<<<<<<<<]>>
+>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>]<<<<<+++<[->-[>
++>>]>[+[-<+>]>+>>]<<<<<]<<[-]>[-]>>[-]>[-<<<+>>>]>[-<<<<<+>>>>>]<<<<<<<<<<<<<<<<
+<<[-]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<[-]<<<<<<<<<<<<<[->>>>>>>>>>
+>>>>+<+<<<<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<++++++++++++
++++++++++++++++++++++++++++++++++++++++++++++++++++++.[-]++++++++++.[-]<[-]]<<<<
+[-]<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<
+<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>
+>>>>>>>>>>>>>>>][-]+<[>[-]<[-]]>[<<<<[-]+>>>>[-]]<<[-]]<<[->>>+<+<<]>>>[-<<<+>>>
+]<[<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<
+<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>
+>>>>>>>>>>>][-]+<[<<<<<<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<[-
+]]>[<<<<<<<<<<<<<<<<<<<<<<<<<<<[-]+>>>>>>>>>>>>>>>>>>>>>>>>>>>>+++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
++++.+++++.++++++++++.--------.--------------------------------------------------
+-------------------------.+++++++++++++++++.-.++++++++++.-----------------------
+---.[-]<<<<<<<<<<<<[->>>>>>>>>>>>>+<+<<<<<<<<<<<<]>>>>>>>>>>>>>[-<<<<<<<<<<<<<+>
+>>>>>>>>>>>>]<+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.
+[-]++++++++++++++++++++++++++++++++.+++++++++++++.+++++++++++++++++.------------
+------------------.[-]<<<<<<<<<<<<++[->>>>>>>>>>>>>>>>>>>>+<<<<<<+<<<<<<<<<<<<<<
+]>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>]<<<<<+++<[->-[>
++>>]>[+[-<+>]>+>>]<<<<<]<<[-]>[-]>>[-]>[-<<<+>>>]>[-<<<<<+>>>>>]<<<<<<<<<<<<<<<<
+<[-]>>>>>>>>>>>>>[-<<<<<<<<<<<<<+>>>>>>>>>>>>>]<[-]<<<<<<<<<<<<[->>>>>>>>>>>>>+<
++<<<<<<<<<<<<]>>>>>>>>>>>>>[-<<<<<<<<<<<<<+>>>>>>>>>>>>>]<++++++++++++++++++++++
++++++++++++++++++++++++++++++++++++++++++++.[-]++++++++++.[-]<[-]]<<<<[-]<<<<<<<
+<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>
+>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>]
+[-]+<[>[-]<[-]]>[<<<<[-]+>>>>[-]]<<[-]]<<[->>>+<+<<]>>>[-<<<+>>>]<[<<<<<<<<<<<<<
+<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>
+>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>][-]+<[<<<<
+<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<[-]]>[<<<<<<<<<<<<<<<<<<<<
+<<<<<<[-]+>>>>>>>>>>>>>>>>>>>>>>>>>>>+++++++++++++++++++++++++++++++++++++++++++
++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.+++++.++++++++++.-----
+---.---------------------------------------------------------------------------.
++++++++++++++++++..+++++++++.--------------------------.[-]<<<<<<<<<<<[->>>>>>>>
+>>>>+<+<<<<<<<<<<<]>>>>>>>>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>>]<++++++++++++++++++++
++++++++++++++++++++++++++++++++++++++++++++++.[-]+++++++++++++++++++++++++++++++
++.+++++++++++++.+++++++++++++++++.------------------------------.[-]<<<<<<<<<<<+
+[->>>>>>>>>>>>>>>>>>>+<<<<<<+<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<
+<<<+>>>>>>>>>>>>>>>>>>>]<<<<<+++<[->-[>+>>]>[+[-<+>]>+>>]<<<<<]<<[-]>[-]>>[-]>[-
+<<<+>>>]>[-<<<<<+>>>>>]<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>
+>]<[-]<<<<<<<<<<<[->>>>>>>>>>>>+<+<<<<<<<<<<<]>>>>>>>>>>>>[-<<<<<<<<<<<<+>>>>>>>
+>>>>>]<+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.[-]++++
+++++++.[-]<[-]]<<<<[-]<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<
+<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>
+>>>>>>>>>>>>>>>>>>>>>>][-]+<[>[-]<[-]]>[<<<<[-]+>>>>[-]]<<[-]]<<[->>>+<+<<]>>>[-
+<<<+>>>]<[<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<
+<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>
+>>>>>>][-]+<[<<<<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>>[-]<[-]]>[<<<<<
+<<<<<<<<<<<<<<<<<<<<[-]+>>>>>>>>>>>>>>>>>>>>>>>>>>++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.+++++.+++
++++++++.--------.---------------------------------------------------------------
+------------.+++++++++++++++++.+.++++++++.--------------------------.[-]<<<<<<<<
+<<[->>>>>>>>>>>+<+<<<<<<<<<<]>>>>>>>>>>>[-<<<<<<<<<<<+>>>>>>>>>>>]<+++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++.[-]++++++++++++++++++++++++
+++++++++.+++++++++++++.+++++++++++++++++.------------------------------.[-]<<<<<
+<<<<<++[->>>>>>>>>>>>>>>>>>+<<<<<<+<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<
+<<<<<<+>>>>>>>>>>>>>>>>>>]<<<<<+++<[->-[>+>>]>[+[-<+>]>+>>]<<<<<]<<[-]>[-]>>[-]>
+[-<<<+>>>]>[-<<<<<+>>>>>]<<<<<<<<<<<<<<<[-]>>>>>>>>>>>[-<<<<<<<<<<<+>>>>>>>>>>>]
+<[-]<<<<<<<<<<[->>>>>>>>>>>+<+<<<<<<<<<<]>>>>>>>>>>>[-<<<<<<<<<<<+>>>>>>>>>>>]<+
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.[-]++++++++++.[
+-]<[-]]<<<<[-]<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<
+<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>
+>>>>>>>>][-]+<[>[-]<[-]]>[<<<<[-]+>>>>[-]]<<[-]]<<[->>>+<+<<]>>>[-<<<+>>>]<[<<<<
+<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>
+>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>][-]+<[<<<<<<<
+<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>[-]<[-]]>[<<<<<<<<<<<<<<<<<<<<<<<<[-]
++>>>>>>>>>>>>>>>>>>>>>>>>>++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++.+++++.++++++++++.--------.-------
+--------------------------------------------------------------------.+++++++++++
+++++++.++.+++++++.--------------------------.[-]<<<<<<<<<[->>>>>>>>>>+<+<<<<<<<<
+<]>>>>>>>>>>[-<<<<<<<<<<+>>>>>>>>>>]<+++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++.[-]++++++++++++++++++++++++++++++++.+++++++++++++.+++++++
+++++++++++.------------------------------.[-]<<<<<<<<<+[->>>>>>>>>>>>>>>>>+<<<<<
+<+<<<<<<<<<<<]>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>]<<<<<+++<[-
+>-[>+>>]>[+[-<+>]>+>>]<<<<<]<<[-]>[-]>>[-]>[-<<<+>>>]>[-<<<<<+>>>>>]<<<<<<<<<<<<
+<<[-]>>>>>>>>>>[-<<<<<<<<<<+>>>>>>>>>>]<[-]<<<<<<<<<[->>>>>>>>>>+<+<<<<<<<<<]>>>
+>>>>>>>[-<<<<<<<<<<+>>>>>>>>>>]<++++++++++++++++++++++++++++++++++++++++++++++++
++++++++++++++++++.[-]++++++++++.[-]<[-]]<<<<[-]<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>
+>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<
+<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>][-]+<[>[-]<[-]]>[<<<<[-]+>>>>[-]]<<[-]]<<[->
+>>+<+<<]>>>[-<<<+>>>]<[<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<
+<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>
+>>>>>>>][-]+<[<<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>[-]<[-]]>[<<<<<<<<
+<<<<<<<<<<<<<<<[-]+>>>>>>>>>>>>>>>>>>>>>>>>+++++++++++++++++++++++++++++++++++++
++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.+++++.++++++++++
+.--------.----------------------------------------------------------------------
+-----.+++++++++++++++++.+++.++++++.--------------------------.[-]<<<<<<<<[->>>>>
+>>>>+<+<<<<<<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<++++++++++++++++++++++++++++++++
++++++++++++++++++++++++++++++++++.[-]++++++++++++++++++++++++++++++++.++++++++++
++++.+++++++++++++++++.------------------------------.[-]<<<<<<<<++[->>>>>>>>>>>>
+>>>>+<<<<<<+<<<<<<<<<<]>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>]<<<<<
++++<[->-[>+>>]>[+[-<+>]>+>>]<<<<<]<<[-]>[-]>>[-]>[-<<<+>>>]>[-<<<<<+>>>>>]<<<<<<
+<<<<<<<[-]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<[-]<<<<<<<<[->>>>>>>>>+<+<<<<<<<<]>>>>
+>>>>>[-<<<<<<<<<+>>>>>>>>>]<++++++++++++++++++++++++++++++++++++++++++++++++++++
++++++++++++++.[-]++++++++++.[-]<[-]]<<<<[-]<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>
+>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<
+<+>>>>>>>>>>>>>>>>>>>>>>>][-]+<[>[-]<[-]]>[<<<<[-]+>>>>[-]]<<[-]]<<[->>>+<+<<]>>
+>[-<<<+>>>]<[<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<
+<]>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>][-]+<[<<
+<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>[-]<[-]]>[<<<<<<<<<<<<<<<<<<<<<<[-]+
+>>>>>>>>>>>>>>>>>>>>>>>+++++++++++++++++++++++++++++++++++++++++++++++++++++++++
++++++++++++++++++++++++++++++++++++++++++++.+++++.++++++++++.--------.----------
+-----------------------------------------------------------------.++++++++++++++
++++.++++.+++++.--------------------------.[-]<<<<<<<[->>>>>>>>+<+<<<<<<<]>>>>>>>
+>[-<<<<<<<<+>>>>>>>>]<++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
++++++++.[-]++++++++++++++++++++++++++++++++.+++++++++++++.+++++++++++++++++.----
+--------------------------.[-]<<<<<<<+[->>>>>>>>>>>>>>>+<<<<<<+<<<<<<<<<]>>>>>>>
+>>>>>>>>[-<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>]<<<<<+++<[->-[>+>>]>[+[-<+>]>+>>]<<<<<
+]<<[-]>[-]>>[-]>[-<<<+>>>]>[-<<<<<+>>>>>]<<<<<<<<<<<<[-]>>>>>>>>[-<<<<<<<<+>>>>>
+>>>]<[-]<<<<<<<[->>>>>>>>+<+<<<<<<<]>>>>>>>>[-<<<<<<<<+>>>>>>>>]<+++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++.[-]++++++++++.[-]<[-]]<<<<[-]
+<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>
+>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>][-]+<[>[-]<[-]]>[<<<<[-
+]+>>>>[-]]<<[-]]<<[->>>+<+<<]>>>[-<<<+>>>]<[<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>
+>>>>>>+<+<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<+>>>>>
+>>>>>>>>>>>>>>>>][-]+<[<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>[-]<[-]]>[<<<
+<<<<<<<<<<<<<<<<<<[-]+>>>>>>>>>>>>>>>>>>>>>>++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.+++++.+++++++++
++.--------.---------------------------------------------------------------------
+------.+++++++++++++++++.+++++.++++.--------------------------.[-]<<<<<<[->>>>>>
+>+<+<<<<<<]>>>>>>>[-<<<<<<<+>>>>>>>]<+++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++.[-]++++++++++++++++++++++++++++++++.+++++++++++++.+++++++
+++++++++++.------------------------------.[-]<<<<<<++[->>>>>>>>>>>>>>+<<<<<<+<<<
+<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<<<<<+++<[->-[>+>>]>[+[-<+>]
+>+>>]<<<<<]<<[-]>[-]>>[-]>[-<<<+>>>]>[-<<<<<+>>>>>]<<<<<<<<<<<[-]>>>>>>>[-<<<<<<
+<+>>>>>>>]<[-]<<<<<<[->>>>>>>+<+<<<<<<]>>>>>>>[-<<<<<<<+>>>>>>>]<+++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++.[-]++++++++++.[-]<[-]]<<<<[-]
+<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>
+>>>>>[-<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>][-]+<[>[-]<[-]]>[<<<<[-]+>>>>
+[-]]<<[-]]<<[->>>+<+<<]>>>[-<<<+>>>]<[<[-]>[-]]<<[-]>]
@@ -0,0 +1,104 @@
+Renders the Mandelbrot set as 80 by 33 characters in the style of the
+classic mandelbrot program by Erik Bosman
+
+Points are iterated in fixed point with five fractional bits on 8 bit cells
+and printed as the letter A plus the number of iterations it took them to
+escape; points which stay within the set for 26 iterations are printed as a
+space
+
+Mostly arithmetic on a handful of cells using small nested loops
+
+[-]-------------------------------->>[-]+++++++++++++++++++++++++++++++++[<[-]--
+-------------------------------------------------------------->>[-]+++++++++++++
++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++[>[-]>[-]>[-]
+++++++++++++++++++++++++++>[-]>[-]+[<<<<[->>>>>>>>>>>>>>>>>>>>>+<<<<<<+<<<<<<<<<
+<<<<<<]>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>]<<<<<+
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
++++++++++++++++++++++++++++++++++++++++++++++++<[->-[>+>>]>[+[-<+>]>+>>]<<<<<]<<
+<<<<<<<<[-]>>>>>>>>>[-]>>[-]>[-<<<+>>>]>[-<<<<<<<<<<<<<+>>>>>>>>>>>>>]<<<<[-]<<<
+<<<<<[-]<[->>>>>>>>>>+<+<<<<<<<<<]>>>>>>>>>>[-<<<<<<<<<<+>>>>>>>>>>][-]+<[<<<<<<
+<<<<<<<<[->>>>>>>>>>>>>>>>+<<<<<<<<<<-<<<<<<]>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<+
+>>>>>>>>>>>>>>>>]<[-]<[-]]>[<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>+<<<<<<<<<<+<<<<<<]
+>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>]<[-]]<<<<<<<<<<<<<<[->>>>>>>
+>>>>>>>>>>>>>+<<<<<<+<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<+>
+>>>>>>>>>>>>>>>>>>>]<<<<<+++++++++++++++++++++++++++++++++++++++++++++++++++++++
++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++<[->-[>
++>>]>[+[-<+>]>+>>]<<<<<]<<<<<<<<[-]>>>>>>>[-]>>[-]>[-<<<+>>>]>[-<<<<<<<<<<<+>>>>
+>>>>>>>]<<<<[-]<<<<<<[-]<[->>>>>>>>+<+<<<<<<<]>>>>>>>>[-<<<<<<<<+>>>>>>>>][-]+<[
+<<<<<<<<<<<<<[->>>>>>>>>>>>>>>+<<<<<<<<-<<<<<<<]>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<
++>>>>>>>>>>>>>>>]<[-]<[-]]>[<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>+<<<<<<<<+<<<<<<<]>>>
+>>>>>>>>>>>>[-<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>]<[-]]<<<<<<<<<[->>>>>>>>>>>>>>>>+<
+<<<<<+<<<<<<<<<<]>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>]<<<<<++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++<[->-[>+>>]>[+[-<+>]>+
+>>]<<<<<]<<[-]>[-]>>[-]>[-<<<+>>>]>[-<<<<<+>>>>>]<<<<<[-<+>]>[-]<<<<<<<[->>>>>>>
+>>>>>>>+<<<<<<+<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<<<<<+++++
++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++<[->-[>+>>]>[+[-<+>]>
++>>]<<<<<]<<[-]>[-]>>[-]>[-<<<+>>>]>[-<<<<<+>>>>>]<<<<<[-<+>]>[-]<<[->>>>+<+<<<]
+>>>>[-<<<<+>>>>][-]+<[<<<<<<<<<<<<<[-]+>[-]>>>>>>>>>>>>>[-]<[-]]>[<<<<<<<<<<<[->
+>>>>>>>>>>>>>>>>>>>>+<<<<<<+<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<
+<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>]<<<<<++++++++++++++++<[->-[>+>>]>[+[-<+>]>+>>]<<<
+<<]<<<<<<<<<<<<[-]>[-]>>>>>>>>>>>>[-]>[-<<<<<<<<<<<<<+>>>>>>>>>>>>>]>[-<<<<<<<<<
+<<<<<<+>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>+<<<<<<+<<<<<<<<<<<<
+<]>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>]<<<<<++++++++++++
+++++<[->-[>+>>]>[+[-<+>]>+>>]<<<<<]<<<<<<<<<<[-]>[-]>>>>>>>>>>[-]>[-<<<<<<<<<<<+
+>>>>>>>>>>>]>[-<<<<<<<<<<<<<+>>>>>>>>>>>>>]<<<<<<[-]<<<<<<<<<[->>>>>>>>>>>>>>+<+
+<<<<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<[<<<<<<<<<<<<<[->>>
+>>>>>>>>>>>+<<+<<<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<-]<[<
+<<++++++++>>>-]<<<<<<<<<<<<[->>>>>>>>>>>>>>+<+<<<<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<
+<<<<<<<<<<+>>>>>>>>>>>>>>]<[<<<<<<<<<<<<[->>>>>>>>>>>>>+<<<<<+<<<<<<<<]>>>>>>>>>
+>>>>[-<<<<<<<<<<<<<+>>>>>>>>>>>>>]<-]<<<<<<<<<<<<[->>>>>>>>>>>>>>>>+<+<<<<<<<<<<
+<<<<<]>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>]<[<<<<<<<<<<<<<<<[->>>
+>>>>>>>>>>>>>+<<<<+<<<<<<<<<<<<]>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>
+>>>]<-]<<<[->>>>>>>>>+<<<<<<+<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<<<<<+++++++++++
++++++++++++++++++++++<[->-[>+>>]>[+[-<+>]>+>>]<<<<<]<<[-]>[-]>>[-]>[-<<<+>>>]>[-
+<<<<<+>>>>>]<<<<<[-<<<<<+>>>>>]<<[-]>[-]>>[-]<<<<<[-]<<<<<<<<[->>>>>>>>>>>>+<+<<
+<<<<<<<<<]>>>>>>>>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>>]<[<<<<<<<<<<<[->>>>>>>>>>>>+<<
++<<<<<<<<<<]>>>>>>>>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>>]<-]<[<<++++++++>>-]<<<<<<<<<
+<[->>>>>>>>>>>>+<+<<<<<<<<<<<]>>>>>>>>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>>]<[<<<<<<<<
+<<[->>>>>>>>>>>+<<<<+<<<<<<<]>>>>>>>>>>>[-<<<<<<<<<<<+>>>>>>>>>>>]<-]<<<<<<<<<<[
+->>>>>>>>>>>>>>+<+<<<<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<[
+<<<<<<<<<<<<<[->>>>>>>>>>>>>>+<<<<+<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>
+>>>>>>>>>>>]<-]<<<[->>>>>>>>>+<<<<<<+<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<<<<<+++
++++++++++++++++++++++++++++++<[->-[>+>>]>[+[-<+>]>+>>]<<<<<]<<[-]>[-]>>[-]>[-<<<
++>>>]>[-<<<<<+>>>>>]<<<<<[-<<<<+>>>>]<<[-]>[-]>>[-]<<<<<<[->>>+<+<<]>>>[-<<<+>>>
+]<<[->>+<+<]>>[-<<+>>]<[->>>>>>>>>+<<<<<<+<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<<<
+<<------------------------------------------------------------------------------
+-------------------------------------------------<[->-[>+>>]>[+[-<+>]>+>>]<<<<<]
+<<[-]>[-]>>[-]>[-<<<+>>>]>[-<<<<<+>>>>>]<<<<<[->>>+<+<<]>>>[-<<<+>>>][-]+<[<<<<<
+<<<<<<<<<<<<<<<[-]+>[-]>>>>>>>>>>>>>>>>>>>>[-]<[-]]>[>[-]<<<<<<<<<<<<<<<<[->>>>>
+>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<+>>>
+>>>>>>>>>>>>>>>>]<[<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>+<<+<<<<<<<<<<<<<<<]>>>>>>
+>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>]<-]<[<++++++++++++++++>-]<<<<<<
+<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>[-<<<<<
+<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>]<[<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>+<<<+<<<<<
+<<<<<<<<]>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>]<-]<<<<<<<<<<<<<<<<
+<[->>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<
++>>>>>>>>>>>>>>>>>>]<[<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>+<<<+<<<<<<<<<<<<<<]>>>
+>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>]<-]<<<<<<<<<<<<<<<<<[->>>>>>
+>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<
+<<<+>>>>>>>>>>>>>>>>>>>>>]<[<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>+<<<<+<<<<<<<
+<<<<<<<<]>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>]<-]<<<[->>
+>>>>>>>+<<<<<<+<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<<<<<++++++++++++++++<[->-[>+>
+>]>[+[-<+>]>+>>]<<<<<]<<[-]>[-]>>[-]>[-<<<+>>>]>[-<<<<<+>>>>>]<<<<<[-<<<+>>>]<<[
+-]>[-]>>[-]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<
+<+>>>>>>>>>>>>>>>>>>]>[-<<<<<<<<<<<<<<<<<<<->>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<
+<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<<<<<+<<<]>>>>>>>>>>>
+>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]
+<<<<<<<<<<<<<<<<<<<<<<<<<[-]<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<
+<<<<<<<<<<<+<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
++>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>+<+
+<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>
+>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<]>>>
+>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>]<[->>+<+<]>>[-<<+>>
+]<[[-]]<[->>>>>>>>+<<<<<<+<<]>>>>>>>>[-<<<<<<<<+>>>>>>>>]<<<<<++<[->-[>+>>]>[+[-
+<+>]>+>>]<<<<<]<<<<<<<[-]>>>>>>[-]>>[-]>[-<<<+>>>]>[-<<<<<<<<<<+>>>>>>>>>>]<<<<<
+<<<<<[-]>>>>>>[->>+<+<]>>[-<<+>>][-]+<[<<<[-<<<<<<<<<<<<<<<<<<<<<<<<->>>>>>>>>>>
+>>>>>>>>>>>>>]>>>>[-]<[-]]>[<<<<[-<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>
+>>>]>>>>[-]]<<<[-]>[-]<<[-]<<<<<<<<<<<<<<<<<<<<<<<-[->>>>>>>>>>>>>>>>>>>>>>>>+<+
+<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>
+>>>>>>>>>>>>>>>>>>>][-]+<[>[-]<[-]]>[<<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>
+>>>>[-]]<<[-]]<<<<<<[-]>[-]>[-]>[-]>[-]<<<<<[-]]<<<<<<<<<<<<[-]>[-]>[-]>[-]>[-]>
+[-]>[-]>[-]>[-]>[-]>[-]<<<<<<<<<<<]<[->>>+<+<<]>>>[-<<<+>>>][-]+<[>>[-]+++++++++
++++++++++++++++++<<<<<[->>>>>-<<<<<]>>>>>+++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++.[-]<[-]<[-]]>[>++++++++++++++++++++++++++++++++.[-]<[
+-]]<<<<[-]<<<<<+>>-]>>>>>>++++++++++.[-]<<<<<<<<<++>>-]
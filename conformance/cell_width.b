@@ -0,0 +1,9 @@
+Checks whether cells wrap around at 256 and at 65536 by printing Y or N for
+each; 8 bit cells print YY and 16 bit cells NY while 32 bit cells print NN
+
+++++++++++++++++[>++++++++++++++++<-]>  cell 1 is 256
+>>+<<[>>-<<[-]]>>>++++++++[<<++++++++++>>-]<<-->[<+++++++++++>-]<.[-]<<
+++++++++++++++++[>++++++++++++++++[>>++++++++++++++++[<++++++++++++++++>-]<<-]<-]
+>>                                      cell 2 is 65536
+>>+<<[>>-<<[-]]>>>++++++++[<<++++++++++>>-]<<-->[<+++++++++++>-]<.[-]
+++++++++++.
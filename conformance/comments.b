@@ -0,0 +1,5 @@
+Everything but the eight commands is a comment; this includes the commands of
+extensions like pbrain and Brainfork as well as debugging commands; prints Y
+
+(:) Y # ! @ ~ 0
+++++++++[>+++++++++++<-]>+.>++++++++++.
@@ -0,0 +1,6 @@
+Reads from empty input into a cell holding one and prints the cell plus 65;
+this is B if EOF leaves the cell unchanged and A if it sets the cell to zero
+while setting it to minus one gives the character before A
+
++,>++++++++[<++++++++>-]<+.
+>++++++++++.
@@ -0,0 +1,4 @@
+Echoes three bytes of input given two; the last byte shows what EOF does to
+the cell holding the previous byte
+
+,.,.,.
@@ -0,0 +1,3 @@
+Moves the pointer to the right until it leaves the tape which is an error
+
++[>+]
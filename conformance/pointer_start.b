@@ -0,0 +1,5 @@
+The pointer starts at the leftmost cell; prints A and moves the pointer left
+of it which is an error
+
+>++++++++[<++++++++>-]<+.
+<
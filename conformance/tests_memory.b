@@ -0,0 +1,7 @@
+Goes to cell 30000 and reports from there with a hash sign; verifies that the
+tape is big enough
+
+Taken from the tests by Daniel B Cristofani
+
+++++[>++++++<-]>[>+++++>+++++++<<-]>>++++<[[>[[>>+<<-]<]>>>-]>-[>+>+<<-]>]
++++++[>+++++++<<++>-]>.<<.
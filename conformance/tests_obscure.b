@@ -0,0 +1,7 @@
+Tests for several obscure problems like loops which are never entered and
+stray characters; prints H
+
+Taken from the tests by Daniel B Cristofani
+
+[]++++++++++[>>+>+>++++++[<<+<+++>>>-]<<<<-]
+"A*$";?@![#>>+<<]>[>>]<<<<[>++<[-]]>.>.
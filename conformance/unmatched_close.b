@@ -0,0 +1,6 @@
+An unmatched closing bracket is an error reported before running the program
+so nothing is printed
+
+Taken from the tests by Daniel B Cristofani
+
++++++[>+++++++>++<<-]>.>.][
@@ -0,0 +1,9 @@
+Decrementing a cell holding zero wraps around to the largest value of a cell
+and incrementing that value wraps around to zero again; prints Y if it does
+and N otherwise
+
+-+
+>>+<<[>>-<<[-]]                         cell 2 is one if cell 0 is zero
+>>>++++++++[<<++++++++++>>-]<<--        cell 1 is N
+>[<+++++++++++>-]                       which becomes Y if cell 2 is set
+<.[-]++++++++++.
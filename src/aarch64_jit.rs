@@ -0,0 +1,747 @@
+use std::collections::HashMap;
+
+use crate::backend::JitBackend;
+use crate::{Instruction, Program};
+use anyhow::{anyhow, Result};
+
+#[derive(Default)]
+pub struct Aarch64Assembler {
+    code: Vec<u8>,
+}
+
+impl Aarch64Assembler {
+    const X0: u8 = 0;
+    const X1: u8 = 1;
+    const X2: u8 = 2;
+    const X8: u8 = 8;
+    const X12: u8 = 12;
+    const X13: u8 = 13;
+    // Callee saved registers, which survive the calls into the I/O functions.
+    const X19: u8 = 19;
+    const X20: u8 = 20;
+    const X21: u8 = 21;
+    const X22: u8 = 22;
+    const X29: u8 = 29;
+    const X30: u8 = 30;
+    const SP: u8 = 31;
+    const XZR: u8 = 31;
+
+    fn clear(&mut self) {
+        self.code.clear();
+    }
+
+    fn position(&self) -> usize {
+        self.code.len()
+    }
+
+    fn emit(&mut self, instruction: u32) {
+        // Every aarch64 instruction is exactly 4 bytes wide and little endian
+        self.code.extend_from_slice(&instruction.to_le_bytes());
+    }
+
+    fn emit_mov(&mut self, dst: u8, src: u8) {
+        // orr dst, xzr, src
+        self.emit(0xAA000000 | (src as u32) << 16 | (Self::XZR as u32) << 5 | dst as u32);
+    }
+
+    fn emit_mov_immediate(&mut self, dst: u8, value: usize) {
+        // movz dst, #(value & 0xFFFF)
+        self.emit(0xD2800000 | ((value & 0xFFFF) as u32) << 5 | dst as u32);
+        for shift in 1..4 {
+            let part = (value >> (shift * 16)) & 0xFFFF;
+            if part != 0 {
+                // movk dst, #part, lsl #(shift * 16)
+                self.emit(0xF2800000 | (shift as u32) << 21 | (part as u32) << 5 | dst as u32);
+            }
+        }
+    }
+
+    fn emit_load(&mut self, dst: u8, base: u8, offset: u16) {
+        // ldr dst, [base, #offset]
+        self.emit(0xF9400000 | ((offset / 8) as u32) << 10 | (base as u32) << 5 | dst as u32);
+    }
+
+    fn emit_store(&mut self, src: u8, base: u8, offset: u16) {
+        // str src, [base, #offset]
+        self.emit(0xF9000000 | ((offset / 8) as u32) << 10 | (base as u32) << 5 | src as u32);
+    }
+
+    fn emit_load_byte(&mut self, dst: u8, base: u8, offset: u8) {
+        // ldrb wdst, [base, offset]
+        self.emit(0x38606800 | (offset as u32) << 16 | (base as u32) << 5 | dst as u32);
+    }
+
+    fn emit_store_byte(&mut self, src: u8, base: u8, offset: u8) {
+        // strb wsrc, [base, offset]
+        self.emit(0x38206800 | (offset as u32) << 16 | (base as u32) << 5 | src as u32);
+    }
+
+    fn emit_add(&mut self, dst: u8, src: u8, other: u8) {
+        // add dst, src, other
+        self.emit(0x8B000000 | (other as u32) << 16 | (src as u32) << 5 | dst as u32);
+    }
+
+    fn emit_add_shifted(&mut self, dst: u8, src: u8, other: u8, shift: u8) {
+        // add dst, src, other, lsl #shift
+        self.emit(
+            0x8B000000
+                | (other as u32) << 16
+                | (shift as u32) << 10
+                | (src as u32) << 5
+                | dst as u32,
+        );
+    }
+
+    fn emit_sub(&mut self, dst: u8, src: u8, other: u8) {
+        // sub dst, src, other
+        self.emit(0xCB000000 | (other as u32) << 16 | (src as u32) << 5 | dst as u32);
+    }
+
+    fn emit_add_immediate(&mut self, dst: u8, src: u8, value: u16) {
+        // add dst, src, #value
+        self.emit(0x91000000 | ((value & 0xFFF) as u32) << 10 | (src as u32) << 5 | dst as u32);
+    }
+
+    fn emit_sub_immediate(&mut self, dst: u8, src: u8, value: u16) {
+        // sub dst, src, #value
+        self.emit(0xD1000000 | ((value & 0xFFF) as u32) << 10 | (src as u32) << 5 | dst as u32);
+    }
+
+    fn emit_add_immediate_32(&mut self, dst: u8, src: u8, value: u16) {
+        // add wdst, wsrc, #value
+        self.emit(0x11000000 | ((value & 0xFFF) as u32) << 10 | (src as u32) << 5 | dst as u32);
+    }
+
+    fn emit_sub_immediate_32(&mut self, dst: u8, src: u8, value: u16) {
+        // sub wdst, wsrc, #value
+        self.emit(0x51000000 | ((value & 0xFFF) as u32) << 10 | (src as u32) << 5 | dst as u32);
+    }
+
+    fn emit_compare(&mut self, first: u8, second: u8) {
+        // cmp first, second
+        self.emit(0xEB000000 | (second as u32) << 16 | (first as u32) << 5 | Self::XZR as u32);
+    }
+
+    fn emit_skip_next_if_not_equal(&mut self) {
+        // b.ne #8
+        self.emit(0x54000001 | 2 << 5);
+    }
+
+    fn emit_skip_next_if_zero(&mut self, src: u8) {
+        // cbz wsrc, #8
+        self.emit(0x34000000 | 2 << 5 | src as u32);
+    }
+
+    fn emit_skip_next_if_non_zero(&mut self, src: u8) {
+        // cbnz wsrc, #8
+        self.emit(0x35000000 | 2 << 5 | src as u32);
+    }
+
+    fn emit_skip_next_if_non_zero_64(&mut self, src: u8) {
+        // cbnz xsrc, #8
+        self.emit(0xB5000000 | 2 << 5 | src as u32);
+    }
+
+    fn emit_address(&mut self, dst: u8, offset: u32) {
+        // adr dst, #offset
+        self.emit(0x10000000 | (offset & 0x3) << 29 | (offset >> 2) << 5 | dst as u32);
+    }
+
+    fn emit_branch(&mut self, target: usize) -> Result<()> {
+        // The offset is relative to the branch instruction itself and counted
+        // in instructions instead of bytes.
+        let relative_target = (target as i64 - self.position() as i64) / 4;
+        if !(-(1 << 25)..(1 << 25)).contains(&relative_target) {
+            return Err(anyhow!("branch target {target} is out of range"));
+        }
+
+        // b relative_target
+        self.emit(0x14000000 | (relative_target as u32 & 0x03FFFFFF));
+        Ok(())
+    }
+
+    fn patch_branch_target(&mut self, patch_target_pos: usize, new_target: usize) -> Result<()> {
+        let relative_target = (new_target as i64 - patch_target_pos as i64) / 4;
+        if !(-(1 << 25)..(1 << 25)).contains(&relative_target) {
+            return Err(anyhow!("branch target {new_target} is out of range"));
+        }
+
+        let instruction = 0x14000000 | (relative_target as u32 & 0x03FFFFFF);
+        self.code[patch_target_pos..patch_target_pos + 4]
+            .copy_from_slice(&instruction.to_le_bytes());
+        Ok(())
+    }
+
+    fn emit_push_pair(&mut self, first: u8, second: u8) {
+        // stp first, second, [sp, #-16]!
+        self.emit(
+            0xA9800000 | 0x7E << 15 | (second as u32) << 10 | (Self::SP as u32) << 5 | first as u32,
+        );
+    }
+
+    fn emit_pop_pair(&mut self, first: u8, second: u8) {
+        // ldp first, second, [sp], #16
+        self.emit(
+            0xA8C00000 | 0x02 << 15 | (second as u32) << 10 | (Self::SP as u32) << 5 | first as u32,
+        );
+    }
+
+    fn emit_call(&mut self, target: u8) {
+        // blr target
+        self.emit(0xD63F0000 | (target as u32) << 5);
+    }
+
+    fn emit_io_call(&mut self, function_offset: u16) {
+        // Pass the context as first argument
+        self.emit_mov(Self::X0, Self::X22);
+        // Pass the memory location as second argument
+        self.emit_add(Self::X1, Self::X19, Self::X21);
+        // Load the I/O function stored in the context and call it
+        self.emit_load(Self::X8, Self::X22, function_offset);
+        self.emit_call(Self::X8);
+    }
+
+    /// Load the address of the entry for the procedure numbered by the
+    /// current cell into X13.
+    fn emit_procedure_entry(&mut self) {
+        self.emit_load_byte(Self::X12, Self::X19, Self::X21);
+        self.emit_load(Self::X13, Self::X22, 16);
+        self.emit_add_shifted(Self::X13, Self::X13, Self::X12, 3);
+    }
+
+    fn emit_return(&mut self) {
+        // ret
+        self.emit(0xD65F03C0);
+    }
+
+    /// Continue at the `]` stored in the context, if any. Returns the
+    /// positions of the branches to backpatch by the index of their `]`.
+    fn emit_resume_dispatch(&mut self, program: &Program) -> Result<HashMap<usize, usize>> {
+        self.emit_load(Self::X12, Self::X22, 48);
+        self.emit_store(Self::XZR, Self::X22, 48);
+        let mut resume_branches = HashMap::new();
+        for (index, instruction) in program.iter().enumerate() {
+            if let Instruction::JmpBack(_) = instruction {
+                self.emit_mov_immediate(Self::X13, index);
+                self.emit_compare(Self::X12, Self::X13);
+                self.emit_skip_next_if_not_equal();
+                resume_branches.insert(index, self.position());
+                self.emit_branch(self.position())?;
+            }
+        }
+        Ok(resume_branches)
+    }
+
+    /// Count down the loop iterations left. Returns the position of the
+    /// branch taken once there are none, to be patched with the exit of the
+    /// loop.
+    fn emit_loop_budget(&mut self) -> Result<usize> {
+        self.emit_load(Self::X12, Self::X22, 40);
+        self.emit_skip_next_if_non_zero_64(Self::X12);
+        let exit = self.position();
+        self.emit_branch(exit)?;
+        self.emit_sub_immediate(Self::X12, Self::X12, 1);
+        self.emit_store(Self::X12, Self::X22, 40);
+        Ok(exit)
+    }
+
+    fn emit_move_addr(&mut self, value: usize, right: bool) {
+        if value < 0x1000 {
+            if right {
+                self.emit_add_immediate(Self::X21, Self::X21, value as u16);
+            } else {
+                self.emit_sub_immediate(Self::X21, Self::X21, value as u16);
+            }
+        } else {
+            self.emit_mov_immediate(Self::X12, value);
+            if right {
+                self.emit_add(Self::X21, Self::X21, Self::X12);
+            } else {
+                self.emit_sub(Self::X21, Self::X21, Self::X12);
+            }
+        }
+    }
+}
+
+impl JitBackend for Aarch64Assembler {
+    fn architecture(&self) -> &'static str {
+        "aarch64"
+    }
+
+    fn compile(&mut self, program: &Program, snapshots: bool) -> Result<()> {
+        let mut forward_jumps = vec![];
+        let mut procedure_starts = vec![];
+        let mut undefined_procedure_branches = vec![];
+        // Branches leaving the program at the `]` of the given index
+        let mut loop_exits = vec![];
+
+        self.clear();
+        self.emit_push_pair(Self::X29, Self::X30);
+        self.emit_push_pair(Self::X19, Self::X20);
+        self.emit_push_pair(Self::X21, Self::X22);
+        // X19 will be the pointer to the memory array
+        // X20 will be the pointer to the offset into the memory array
+        // X21 will hold the offset itself while the program is running
+        // X22 will be the pointer to the JitContext
+        self.emit_mov(Self::X19, Self::X0);
+        self.emit_mov(Self::X20, Self::X1);
+        self.emit_load(Self::X21, Self::X20, 0);
+        self.emit_mov(Self::X22, Self::X2);
+        // Remember the stack pointer to be able to abort from within
+        // procedures.
+        self.emit_add_immediate(Self::X12, Self::SP, 0);
+        self.emit_store(Self::X12, Self::X22, 24);
+        let mut resume_branches = HashMap::new();
+        if snapshots {
+            resume_branches = self.emit_resume_dispatch(program)?;
+        }
+        for (index, instruction) in program.iter().enumerate() {
+            match *instruction {
+                Instruction::AddrRight(value) => self.emit_move_addr(value, true),
+                Instruction::AddrLeft(value) => self.emit_move_addr(value, false),
+                Instruction::Inc(value) => {
+                    self.emit_load_byte(Self::X12, Self::X19, Self::X21);
+                    self.emit_add_immediate_32(Self::X12, Self::X12, value.into());
+                    self.emit_store_byte(Self::X12, Self::X19, Self::X21);
+                }
+                Instruction::Dec(value) => {
+                    self.emit_load_byte(Self::X12, Self::X19, Self::X21);
+                    self.emit_sub_immediate_32(Self::X12, Self::X12, value.into());
+                    self.emit_store_byte(Self::X12, Self::X19, Self::X21);
+                }
+                Instruction::Output(value) => {
+                    for _ in 0..value {
+                        self.emit_io_call(0);
+                    }
+                }
+                Instruction::Input(value) => {
+                    for _ in 0..value {
+                        self.emit_io_call(8);
+                    }
+                }
+                Instruction::JmpForward(_) => {
+                    self.emit_load_byte(Self::X12, Self::X19, Self::X21);
+                    self.emit_skip_next_if_non_zero(Self::X12);
+                    // Backpatch the branch target once we know it.
+                    forward_jumps.push(self.position());
+                    self.emit_branch(self.position())?;
+                }
+                Instruction::JmpBack(_) => {
+                    if snapshots {
+                        let resume_target = self.position();
+                        if let Some(branch) = resume_branches.remove(&index) {
+                            self.patch_branch_target(branch, resume_target)?;
+                        }
+                        loop_exits.push((self.emit_loop_budget()?, index));
+                    }
+                    self.emit_load_byte(Self::X12, Self::X19, Self::X21);
+                    self.emit_skip_next_if_zero(Self::X12);
+                    let target = forward_jumps.pop().expect("expected forward jump target");
+                    self.emit_branch(target + 4)?;
+
+                    // Backpatch the forward branch target
+                    let patch_target = self.position();
+                    self.patch_branch_target(target, patch_target)?;
+                }
+                Instruction::ProcedureStart(_) if snapshots => {
+                    return Err(anyhow!(
+                        "Snapshots of programs with procedures are not supported by the JIT"
+                    ));
+                }
+                Instruction::ProcedureStart(_) => {
+                    // Register the body following this definition, which
+                    // starts behind the store and branch, and branch over it.
+                    self.emit_procedure_entry();
+                    self.emit_address(Self::X12, 12);
+                    self.emit_store(Self::X12, Self::X13, 0);
+                    procedure_starts.push(self.position());
+                    self.emit_branch(self.position())?;
+                    // Nested calls overwrite the link register
+                    self.emit_push_pair(Self::X30, Self::XZR);
+                }
+                Instruction::ProcedureEnd(_) => {
+                    self.emit_pop_pair(Self::X30, Self::XZR);
+                    self.emit_return();
+                    let target = procedure_starts.pop().expect("expected procedure start");
+                    let patch_target = self.position();
+                    self.patch_branch_target(target, patch_target)?;
+                }
+                Instruction::Call => {
+                    self.emit_procedure_entry();
+                    self.emit_load(Self::X12, Self::X13, 0);
+                    self.emit_skip_next_if_non_zero_64(Self::X12);
+                    undefined_procedure_branches.push(self.position());
+                    self.emit_branch(self.position())?;
+                    self.emit_call(Self::X12);
+                }
+                Instruction::Fork => {
+                    return Err(anyhow!("Forking threads is not supported by the JIT"));
+                }
+            }
+        }
+        let epilogue = self.position();
+        self.emit_store(Self::X21, Self::X20, 0);
+        self.emit_pop_pair(Self::X21, Self::X22);
+        self.emit_pop_pair(Self::X19, Self::X20);
+        self.emit_pop_pair(Self::X29, Self::X30);
+        self.emit_return();
+
+        if !undefined_procedure_branches.is_empty() {
+            let undefined_procedure = self.position();
+            for branch in undefined_procedure_branches {
+                self.patch_branch_target(branch, undefined_procedure)?;
+            }
+            self.emit_io_call(32);
+            // Unwind all procedure calls at once
+            self.emit_load(Self::X12, Self::X22, 24);
+            self.emit_add_immediate(Self::SP, Self::X12, 0);
+            self.emit_branch(epilogue)?;
+        }
+
+        for (exit, index) in loop_exits {
+            let patch_target = self.position();
+            self.patch_branch_target(exit, patch_target)?;
+            self.emit_mov_immediate(Self::X12, index);
+            self.emit_store(Self::X12, Self::X22, 48);
+            self.emit_branch(epilogue)?;
+        }
+
+        Ok(())
+    }
+
+    fn code(&self) -> &[u8] {
+        &self.code
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Instructions emitted by the given function, decoded from the code.
+    fn emitted(emit: impl FnOnce(&mut Aarch64Assembler)) -> Vec<u32> {
+        let mut assembler = Aarch64Assembler::default();
+        emit(&mut assembler);
+        assembler
+            .code
+            .chunks_exact(4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect()
+    }
+
+    type Case = (&'static str, fn(&mut Aarch64Assembler), &'static [u32]);
+
+    /// Reference encodings as produced by `llvm-mc -triple=aarch64 -show-encoding`.
+    const CASES: &[Case] = &[
+        (
+            "movz x0, #0x1234",
+            |a| a.emit_mov_immediate(Aarch64Assembler::X0, 0x1234),
+            &[0xD2824680],
+        ),
+        (
+            "movz x13, #0x1234; movk x13, #0x5678, lsl #16",
+            |a| a.emit_mov_immediate(Aarch64Assembler::X13, 0x5678_1234),
+            &[0xD282468D, 0xF2AACF0D],
+        ),
+        (
+            "movz x12, #0; movk x12, #0xbeef, lsl #32; movk x12, #0xdead, lsl #48",
+            |a| a.emit_mov_immediate(Aarch64Assembler::X12, 0xDEAD_BEEF_0000_0000),
+            &[0xD280000C, 0xF2D7DDEC, 0xF2FBD5AC],
+        ),
+        (
+            "ldrb w12, [x19, x21]",
+            |a| {
+                a.emit_load_byte(
+                    Aarch64Assembler::X12,
+                    Aarch64Assembler::X19,
+                    Aarch64Assembler::X21,
+                )
+            },
+            &[0x38756A6C],
+        ),
+        (
+            "strb w12, [x19, x21]",
+            |a| {
+                a.emit_store_byte(
+                    Aarch64Assembler::X12,
+                    Aarch64Assembler::X19,
+                    Aarch64Assembler::X21,
+                )
+            },
+            &[0x38356A6C],
+        ),
+        (
+            "stp x29, x30, [sp, #-16]!",
+            |a| a.emit_push_pair(Aarch64Assembler::X29, Aarch64Assembler::X30),
+            &[0xA9BF7BFD],
+        ),
+        (
+            "stp x21, x22, [sp, #-16]!",
+            |a| a.emit_push_pair(Aarch64Assembler::X21, Aarch64Assembler::X22),
+            &[0xA9BF5BF5],
+        ),
+        (
+            "ldp x29, x30, [sp], #16",
+            |a| a.emit_pop_pair(Aarch64Assembler::X29, Aarch64Assembler::X30),
+            &[0xA8C17BFD],
+        ),
+        (
+            "ldp x19, x20, [sp], #16",
+            |a| a.emit_pop_pair(Aarch64Assembler::X19, Aarch64Assembler::X20),
+            &[0xA8C153F3],
+        ),
+        (
+            "cbz w12, .+8",
+            |a| a.emit_skip_next_if_zero(Aarch64Assembler::X12),
+            &[0x3400004C],
+        ),
+        (
+            "cbnz w12, .+8",
+            |a| a.emit_skip_next_if_non_zero(Aarch64Assembler::X12),
+            &[0x3500004C],
+        ),
+        (
+            "cbnz x12, .+8",
+            |a| a.emit_skip_next_if_non_zero_64(Aarch64Assembler::X12),
+            &[0xB500004C],
+        ),
+        ("b .+8", |a| a.emit_branch(8).unwrap(), &[0x14000002]),
+        (
+            "b .; b .-4",
+            |a| {
+                a.emit_branch(0).unwrap();
+                a.emit_branch(0).unwrap();
+            },
+            &[0x14000000, 0x17FFFFFF],
+        ),
+        (
+            "ldr x8, [x22, #8]",
+            |a| a.emit_load(Aarch64Assembler::X8, Aarch64Assembler::X22, 8),
+            &[0xF94006C8],
+        ),
+        (
+            "ldr x21, [x20]",
+            |a| a.emit_load(Aarch64Assembler::X21, Aarch64Assembler::X20, 0),
+            &[0xF9400295],
+        ),
+        (
+            "str x12, [x22, #48]",
+            |a| a.emit_store(Aarch64Assembler::X12, Aarch64Assembler::X22, 48),
+            &[0xF9001ACC],
+        ),
+        (
+            "str xzr, [x22, #48]",
+            |a| a.emit_store(Aarch64Assembler::XZR, Aarch64Assembler::X22, 48),
+            &[0xF9001ADF],
+        ),
+        (
+            "add x21, x21, #4095",
+            |a| a.emit_add_immediate(Aarch64Assembler::X21, Aarch64Assembler::X21, 4095),
+            &[0x913FFEB5],
+        ),
+        (
+            "mov x12, sp",
+            |a| a.emit_add_immediate(Aarch64Assembler::X12, Aarch64Assembler::SP, 0),
+            &[0x910003EC],
+        ),
+        (
+            "mov sp, x12",
+            |a| a.emit_add_immediate(Aarch64Assembler::SP, Aarch64Assembler::X12, 0),
+            &[0x9100019F],
+        ),
+        (
+            "sub x21, x21, #1",
+            |a| a.emit_sub_immediate(Aarch64Assembler::X21, Aarch64Assembler::X21, 1),
+            &[0xD10006B5],
+        ),
+        (
+            "add w12, w12, #255",
+            |a| a.emit_add_immediate_32(Aarch64Assembler::X12, Aarch64Assembler::X12, 255),
+            &[0x1103FD8C],
+        ),
+        (
+            "sub w12, w12, #1",
+            |a| a.emit_sub_immediate_32(Aarch64Assembler::X12, Aarch64Assembler::X12, 1),
+            &[0x5100058C],
+        ),
+        (
+            "add x1, x19, x21",
+            |a| {
+                a.emit_add(
+                    Aarch64Assembler::X1,
+                    Aarch64Assembler::X19,
+                    Aarch64Assembler::X21,
+                )
+            },
+            &[0x8B150261],
+        ),
+        (
+            "sub x21, x21, x12",
+            |a| {
+                a.emit_sub(
+                    Aarch64Assembler::X21,
+                    Aarch64Assembler::X21,
+                    Aarch64Assembler::X12,
+                )
+            },
+            &[0xCB0C02B5],
+        ),
+        (
+            "add x13, x13, x12, lsl #3",
+            |a| {
+                a.emit_add_shifted(
+                    Aarch64Assembler::X13,
+                    Aarch64Assembler::X13,
+                    Aarch64Assembler::X12,
+                    3,
+                )
+            },
+            &[0x8B0C0DAD],
+        ),
+        (
+            "adr x12, .+12",
+            |a| a.emit_address(Aarch64Assembler::X12, 12),
+            &[0x1000006C],
+        ),
+        (
+            "adr x12, .+13",
+            |a| a.emit_address(Aarch64Assembler::X12, 13),
+            &[0x3000006C],
+        ),
+        (
+            "cmp x12, x13",
+            |a| a.emit_compare(Aarch64Assembler::X12, Aarch64Assembler::X13),
+            &[0xEB0D019F],
+        ),
+        (
+            "b.ne .+8",
+            |a| a.emit_skip_next_if_not_equal(),
+            &[0x54000041],
+        ),
+        (
+            "blr x8",
+            |a| a.emit_call(Aarch64Assembler::X8),
+            &[0xD63F0100],
+        ),
+        ("ret", |a| a.emit_return(), &[0xD65F03C0]),
+        (
+            "mov x19, x0",
+            |a| a.emit_mov(Aarch64Assembler::X19, Aarch64Assembler::X0),
+            &[0xAA0003F3],
+        ),
+    ];
+
+    #[test]
+    fn encodings_match_reference() {
+        for (assembly, emit, expected) in CASES {
+            let actual = emitted(emit);
+            assert_eq!(
+                actual, *expected,
+                "{assembly}: emitted {actual:08X?}, expected {expected:08X?}"
+            );
+        }
+    }
+
+    #[test]
+    fn patched_branches_match_reference() {
+        let actual = emitted(|a| {
+            a.emit_branch(0).unwrap();
+            a.emit_return();
+            a.emit_return();
+            a.patch_branch_target(0, 12).unwrap();
+        });
+        // b .+12; ret; ret
+        assert_eq!(actual, [0x14000003, 0xD65F03C0, 0xD65F03C0]);
+    }
+
+    #[test]
+    fn branches_out_of_range_are_rejected() {
+        let mut assembler = Aarch64Assembler::default();
+        assert!(assembler.emit_branch(1 << 28).is_err());
+    }
+
+    /// Saving the callee saved registers and loading the arguments.
+    const PROLOGUE: [u32; 9] = [
+        0xA9BF7BFD, // stp x29, x30, [sp, #-16]!
+        0xA9BF53F3, // stp x19, x20, [sp, #-16]!
+        0xA9BF5BF5, // stp x21, x22, [sp, #-16]!
+        0xAA0003F3, // mov x19, x0
+        0xAA0103F4, // mov x20, x1
+        0xF9400295, // ldr x21, [x20]
+        0xAA0203F6, // mov x22, x2
+        0x910003EC, // mov x12, sp
+        0xF9000ECC, // str x12, [x22, #24]
+    ];
+
+    /// Storing the pointer and restoring the callee saved registers.
+    const EPILOGUE: [u32; 5] = [
+        0xF9000295, // str x21, [x20]
+        0xA8C15BF5, // ldp x21, x22, [sp], #16
+        0xA8C153F3, // ldp x19, x20, [sp], #16
+        0xA8C17BFD, // ldp x29, x30, [sp], #16
+        0xD65F03C0, // ret
+    ];
+
+    fn compiled(program: &Program) -> Vec<u32> {
+        emitted(|a| a.compile(program, false).unwrap())
+    }
+
+    #[test]
+    fn loops_match_reference() {
+        let body = [
+            0x38756A6C, // ldrb w12, [x19, x21]
+            0x3500004C, // cbnz w12, .+8
+            0x14000007, // b .+28
+            0x38756A6C, // ldrb w12, [x19, x21]
+            0x5100058C, // sub w12, w12, #1
+            0x38356A6C, // strb w12, [x19, x21]
+            0x38756A6C, // ldrb w12, [x19, x21]
+            0x3400004C, // cbz w12, .+8
+            0x17FFFFFB, // b .-20
+        ];
+        let program = vec![
+            Instruction::JmpForward(3),
+            Instruction::Dec(1),
+            Instruction::JmpBack(1),
+        ];
+        assert_eq!(
+            compiled(&program),
+            [&PROLOGUE[..], &body, &EPILOGUE].concat()
+        );
+    }
+
+    #[test]
+    fn io_calls_match_reference() {
+        let body = [
+            0xAA1603E0, // mov x0, x22
+            0x8B150261, // add x1, x19, x21
+            0xF94002C8, // ldr x8, [x22]
+            0xD63F0100, // blr x8
+        ];
+        let program = vec![Instruction::Output(1)];
+        assert_eq!(
+            compiled(&program),
+            [&PROLOGUE[..], &body, &EPILOGUE].concat()
+        );
+    }
+
+    #[test]
+    fn compiles_every_instruction() {
+        let program = vec![
+            Instruction::AddrRight(5000),
+            Instruction::Inc(3),
+            Instruction::JmpForward(6),
+            Instruction::Dec(1),
+            Instruction::Output(1),
+            Instruction::Input(1),
+            Instruction::JmpBack(3),
+            Instruction::AddrLeft(2),
+        ];
+        let mut assembler = Aarch64Assembler::default();
+        assembler.compile(&program, true).unwrap();
+        let code = assembler.code();
+        assert_eq!(code.len() % 4, 0);
+        // The prologue saves the frame pointer and link register first
+        assert_eq!(code[..4], 0xA9BF7BFDu32.to_le_bytes());
+    }
+}
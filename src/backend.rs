@@ -0,0 +1,365 @@
+use std::env;
+use std::io::{ErrorKind, Read, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::snapshot::MachineState;
+use crate::{Intepreter, Program};
+use anyhow::{anyhow, Context, Result};
+
+/// Width of a single cell on the tape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellWidth {
+    U8,
+    U16,
+    U32,
+}
+
+impl CellWidth {
+    pub fn mask(&self) -> u32 {
+        match self {
+            CellWidth::U8 => 0xFF,
+            CellWidth::U16 => 0xFFFF,
+            CellWidth::U32 => 0xFFFF_FFFF,
+        }
+    }
+
+    pub fn bits(&self) -> u32 {
+        match self {
+            CellWidth::U8 => 8,
+            CellWidth::U16 => 16,
+            CellWidth::U32 => 32,
+        }
+    }
+}
+
+impl FromStr for CellWidth {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "8" => Ok(CellWidth::U8),
+            "16" => Ok(CellWidth::U16),
+            "32" => Ok(CellWidth::U32),
+            _ => Err(anyhow!("Invalid cell width {s}, expected one of 8, 16, 32")),
+        }
+    }
+}
+
+/// What happens to the current cell if `,` is executed after the input has
+/// been exhausted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EofBehavior {
+    /// Leave the cell untouched
+    Unchanged,
+    /// Set the cell to 0
+    Zero,
+    /// Set the cell to -1 (all bits set for the configured cell width)
+    MinusOne,
+}
+
+impl EofBehavior {
+    /// Apply the behavior to the given cell value, returning the new value.
+    pub fn apply(&self, cell: u32, cell_width: CellWidth) -> u32 {
+        match self {
+            EofBehavior::Unchanged => cell,
+            EofBehavior::Zero => 0,
+            EofBehavior::MinusOne => cell_width.mask(),
+        }
+    }
+}
+
+impl FromStr for EofBehavior {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "unchanged" => Ok(EofBehavior::Unchanged),
+            "zero" => Ok(EofBehavior::Zero),
+            "minus-one" => Ok(EofBehavior::MinusOne),
+            _ => Err(anyhow!(
+                "Invalid EOF behavior {s}, expected one of unchanged, zero, minus-one"
+            )),
+        }
+    }
+}
+
+/// Configuration shared by all execution backends.
+#[derive(Debug, Clone)]
+pub struct ExecutionConfig {
+    pub tape_size: usize,
+    pub cell_width: CellWidth,
+    /// Whether the cell width has been chosen explicitly instead of being the
+    /// default, so it must not be replaced by the one of bytecode.
+    pub cell_width_given: bool,
+    pub eof: EofBehavior,
+    /// Directory to persist compiled artifacts in, for backends which
+    /// support it.
+    pub cache_dir: Option<PathBuf>,
+    /// Run the threads of forking programs on threads of the operating system
+    /// instead of scheduling them deterministically.
+    pub os_threads: bool,
+    /// Prepare the program to be paused at loop boundaries, so snapshots of
+    /// its state can be taken and restored.
+    pub snapshots: bool,
+}
+
+/// Number of cells on the tape unless configured otherwise.
+pub const DEFAULT_TAPE_SIZE: usize = 640000;
+
+impl Default for ExecutionConfig {
+    fn default() -> Self {
+        Self {
+            tape_size: DEFAULT_TAPE_SIZE,
+            cell_width: CellWidth::U8,
+            cell_width_given: false,
+            eof: EofBehavior::Unchanged,
+            cache_dir: None,
+            os_threads: false,
+            snapshots: false,
+        }
+    }
+}
+
+/// `$XDG_CACHE_HOME/rust_brain` or `$HOME/.cache/rust_brain` if the former is
+/// not set.
+pub fn default_cache_dir() -> Option<PathBuf> {
+    if let Some(cache_home) = env::var_os("XDG_CACHE_HOME").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(cache_home).join("rust_brain"));
+    }
+    env::var_os("HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(|home| PathBuf::from(home).join(".cache").join("rust_brain"))
+}
+
+/// Read a single byte from the given input, returning `None` on EOF.
+pub fn read_byte(input: &mut dyn Read) -> Result<Option<u8>> {
+    let mut buf: [u8; 1] = [0; 1];
+    loop {
+        match input.read(&mut buf) {
+            Ok(0) => return Ok(None),
+            Ok(_) => return Ok(Some(buf[0])),
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e).context("reading data from input"),
+        }
+    }
+}
+
+/// Whether a program stopped by its end or was paused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunState {
+    Finished,
+    /// Paused at a loop boundary, after running out of loop iterations
+    Paused,
+}
+
+/// A program prepared for execution by a backend.
+pub trait CompiledProgram {
+    fn run(&mut self, input: &mut dyn Read, output: &mut dyn Write) -> Result<()>;
+
+    /// Run the program until it ends or is about to start another iteration
+    /// of a loop after the given number of them. Only available if the
+    /// program has been compiled with snapshots enabled.
+    fn run_loops(
+        &mut self,
+        _iterations: u64,
+        _input: &mut dyn Read,
+        _output: &mut dyn Write,
+    ) -> Result<RunState> {
+        Err(anyhow!("Snapshots are not supported by this backend"))
+    }
+
+    /// State of the paused program.
+    fn state(&self) -> Result<MachineState> {
+        Err(anyhow!("Snapshots are not supported by this backend"))
+    }
+
+    /// Continue from the given state once the program is run.
+    fn restore(&mut self, _state: MachineState) -> Result<()> {
+        Err(anyhow!("Snapshots are not supported by this backend"))
+    }
+}
+
+/// A way of executing a parsed brainfuck program.
+pub trait ExecutionBackend {
+    /// Name used to select the backend on the command line.
+    fn name(&self) -> &'static str;
+
+    /// Check whether the backend is able to run the program with the given
+    /// configuration on this host, returning the reason if it is not.
+    fn check(&self, program: &Program, config: &ExecutionConfig) -> Result<()>;
+
+    /// Prepare the program for execution, e.g. by translating it into machine
+    /// code. Kept apart from running it, so both can be timed separately.
+    fn compile(
+        &self,
+        program: Program,
+        config: &ExecutionConfig,
+    ) -> Result<Box<dyn CompiledProgram>>;
+
+    fn execute(
+        &self,
+        program: Program,
+        config: &ExecutionConfig,
+        input: &mut dyn Read,
+        output: &mut dyn Write,
+    ) -> Result<()> {
+        self.compile(program, config)?.run(input, output)
+    }
+}
+
+/// An architecture specific code generator, which is able to translate a
+/// brainfuck program into native machine code.
+///
+/// The generated code must adhere to the C calling convention of the platform
+/// and have the signature
+/// `fn(memory: *mut u8, addr: *mut usize, context: *mut JitContext)`.
+///
+/// I/O is performed by calling the function pointers stored at offset 0
+/// (output) and offset 8 (input) of the context, passing the context as first
+/// and a pointer to the current cell as second argument.
+///
+/// Procedures of pbrain are registered in the table pointed to at offset 16,
+/// which holds the address of every procedure by its number. Calls of
+/// undefined procedures are reported through the function pointer at offset
+/// 32, before execution is aborted by restoring the stack pointer the
+/// generated code stored at offset 24 on entry and returning.
+///
+/// Code generated with snapshots enabled counts the iterations of loops down
+/// from offset 40. Before an iteration beyond them, the index of the `]`
+/// instruction is stored at offset 48 and the code returns. If offset 48 is
+/// not zero on entry, execution continues at the given `]` instead of the
+/// start, after clearing it.
+pub trait JitBackend {
+    /// Name of the architecture the generated code is meant for.
+    fn architecture(&self) -> &'static str;
+
+    /// Translate the given program into machine code, replacing any
+    /// previously generated code. Programs compiled with snapshots enabled
+    /// must not define procedures.
+    fn compile(&mut self, program: &Program, snapshots: bool) -> Result<()>;
+
+    /// The machine code generated by the last call to `compile`.
+    fn code(&self) -> &[u8];
+}
+
+pub struct InterpreterBackend;
+
+impl ExecutionBackend for InterpreterBackend {
+    fn name(&self) -> &'static str {
+        "interp"
+    }
+
+    fn check(&self, _program: &Program, _config: &ExecutionConfig) -> Result<()> {
+        Ok(())
+    }
+
+    fn compile(
+        &self,
+        program: Program,
+        config: &ExecutionConfig,
+    ) -> Result<Box<dyn CompiledProgram>> {
+        Ok(Box::new(Intepreter::new(program, config)))
+    }
+}
+
+impl CompiledProgram for Intepreter {
+    fn run(&mut self, input: &mut dyn Read, output: &mut dyn Write) -> Result<()> {
+        Intepreter::run(self, input, output)
+    }
+
+    fn run_loops(
+        &mut self,
+        iterations: u64,
+        input: &mut dyn Read,
+        output: &mut dyn Write,
+    ) -> Result<RunState> {
+        Intepreter::run_loops(self, iterations, input, output)
+    }
+
+    fn state(&self) -> Result<MachineState> {
+        Ok(Intepreter::state(self))
+    }
+
+    fn restore(&mut self, state: MachineState) -> Result<()> {
+        Intepreter::restore(self, state);
+        Ok(())
+    }
+}
+
+/// All backends compiled into this binary, ordered by preference.
+pub fn backends() -> Vec<Box<dyn ExecutionBackend>> {
+    vec![
+        #[cfg(all(
+            any(target_arch = "x86_64", target_arch = "aarch64"),
+            target_os = "linux"
+        ))]
+        Box::new(crate::jit::NativeJitBackend),
+        Box::new(crate::vm::VmBackend),
+        Box::new(InterpreterBackend),
+    ]
+}
+
+/// Find the backend with the given name.
+fn position(backends: &[Box<dyn ExecutionBackend>], name: &str) -> Result<usize> {
+    backends
+        .iter()
+        .position(|backend| backend.name() == name)
+        .ok_or_else(|| {
+            let available = backends
+                .iter()
+                .map(|backend| backend.name())
+                .collect::<Vec<_>>()
+                .join(", ");
+            anyhow!("Unknown backend {name}, available backends are: {available}")
+        })
+}
+
+/// The backends with the given names in the given order, or all of them if
+/// no names are given.
+pub fn backends_named(names: &[String]) -> Result<Vec<Box<dyn ExecutionBackend>>> {
+    if names.is_empty() {
+        return Ok(backends());
+    }
+    names
+        .iter()
+        .map(|name| {
+            let mut backends = backends();
+            let index = position(&backends, name)?;
+            Ok(backends.remove(index))
+        })
+        .collect()
+}
+
+/// Select the backend with the given name, or the most preferred one if no
+/// name is given.
+///
+/// If the selected backend can not handle the program or the configuration,
+/// the most preferred backend which can is used instead.
+pub fn select_backend(
+    name: Option<&str>,
+    program: &Program,
+    config: &ExecutionConfig,
+) -> Result<Box<dyn ExecutionBackend>> {
+    let mut backends = backends();
+    let index = match name {
+        Some(name) => position(&backends, name)?,
+        None => 0,
+    };
+
+    let backend = backends.remove(index);
+    if let Err(reason) = backend.check(program, config) {
+        let fallback = backends
+            .into_iter()
+            .find(|fallback| fallback.check(program, config).is_ok())
+            .unwrap_or_else(|| Box::new(InterpreterBackend));
+        eprintln!(
+            "Backend {} is not available ({reason}), falling back to {}",
+            backend.name(),
+            fallback.name()
+        );
+        return Ok(fallback);
+    }
+
+    Ok(backend)
+}
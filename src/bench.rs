@@ -0,0 +1,242 @@
+use std::fmt::Write as _;
+use std::io;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::backend::{backends_named, ExecutionBackend, ExecutionConfig};
+use crate::cli::{BenchOptions, ProgramSource};
+use crate::dialect::Dialect;
+use crate::json::JsonValue;
+use crate::{optimizer, read_source, Lexer, Parser};
+use anyhow::{anyhow, Context, Result};
+
+/// Classic workloads bundled with rust_brain, timed if no sources are given.
+const BUNDLED: [(&str, &str); 4] = [
+    ("bench", include_str!("../bench/bench.b")),
+    ("mandelbrot", include_str!("../bench/mandelbrot.b")),
+    ("hanoi", include_str!("../bench/hanoi.b")),
+    ("factor", include_str!("../bench/factor.b")),
+];
+
+/// A program to time, which is run without input.
+struct Workload {
+    name: String,
+    code: Vec<u8>,
+    dialect: Rc<Dialect>,
+}
+
+impl Workload {
+    fn load(source: &ProgramSource, dialect: &Rc<Dialect>) -> Result<Self> {
+        let name = match source {
+            ProgramSource::File(path) => path.clone(),
+            ProgramSource::Stdin => "-".to_string(),
+            ProgramSource::Inline(_) => "-e".to_string(),
+        };
+        Ok(Self {
+            name,
+            code: read_source(source)?,
+            dialect: Rc::clone(dialect),
+        })
+    }
+}
+
+/// Time spent in every phase of running a program.
+#[derive(Debug, Clone, Copy, Default)]
+struct Phases {
+    parse: Duration,
+    optimize: Duration,
+    compile: Duration,
+    run: Duration,
+}
+
+impl Phases {
+    fn total(&self) -> Duration {
+        self.parse + self.optimize + self.compile + self.run
+    }
+}
+
+struct Measurement {
+    workload: String,
+    backend: &'static str,
+    /// Phases of the fastest run
+    phases: Phases,
+}
+
+/// Run the workload once from source to completion, returning the time spent
+/// in every phase together with the output.
+fn measure(
+    workload: &Workload,
+    backend: &dyn ExecutionBackend,
+    config: &ExecutionConfig,
+) -> Result<(Phases, Vec<u8>)> {
+    let start = Instant::now();
+    let mut parser = Parser::default();
+    let program = parser.parse_unoptimized(&mut Lexer::new(
+        &workload.code[..],
+        Rc::clone(&workload.dialect),
+    ))?;
+    let parsed = Instant::now();
+    let (program, _) = optimizer::optimize(&program, &parser.spans);
+    let optimized = Instant::now();
+    let mut compiled = backend.compile(program, config)?;
+    let compiled_at = Instant::now();
+    let mut output = vec![];
+    compiled.run(&mut io::empty(), &mut output)?;
+    let finished = Instant::now();
+
+    let phases = Phases {
+        parse: parsed - start,
+        optimize: optimized - parsed,
+        compile: compiled_at - optimized,
+        run: finished - compiled_at,
+    };
+    Ok((phases, output))
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+}
+
+fn to_table(measurements: &[Measurement]) -> String {
+    let width = measurements
+        .iter()
+        .map(|measurement| measurement.workload.len())
+        .chain(["workload".len()])
+        .max()
+        .unwrap_or_default();
+    // Writing to a String never fails
+    let mut table = String::new();
+    let _ = writeln!(
+        table,
+        "{:<width$}  {:<7} {:>11} {:>11} {:>11} {:>11} {:>11}",
+        "workload", "backend", "parse", "optimize", "compile", "run", "total"
+    );
+    for measurement in measurements {
+        let phases = &measurement.phases;
+        let _ = writeln!(
+            table,
+            "{:<width$}  {:<7} {:>11} {:>11} {:>11} {:>11} {:>11}",
+            measurement.workload,
+            measurement.backend,
+            format_duration(phases.parse),
+            format_duration(phases.optimize),
+            format_duration(phases.compile),
+            format_duration(phases.run),
+            format_duration(phases.total()),
+        );
+    }
+    table
+}
+
+fn to_json(measurements: &[Measurement], runs: usize) -> JsonValue {
+    let nanos = |duration: Duration| JsonValue::from(duration.as_nanos() as usize);
+    let results = measurements
+        .iter()
+        .map(|measurement| {
+            let phases = &measurement.phases;
+            JsonValue::object([
+                ("workload", measurement.workload.as_str().into()),
+                ("backend", measurement.backend.into()),
+                ("parse_ns", nanos(phases.parse)),
+                ("optimize_ns", nanos(phases.optimize)),
+                ("compile_ns", nanos(phases.compile)),
+                ("run_ns", nanos(phases.run)),
+                ("total_ns", nanos(phases.total())),
+            ])
+        })
+        .collect();
+    JsonValue::object([
+        ("runs", runs.into()),
+        ("results", JsonValue::Array(results)),
+    ])
+}
+
+/// Time the workloads on every selected backend and print the results.
+///
+/// Every workload is run the given number of times per backend, of which the
+/// fastest run is reported. The output of all backends has to be the same, so
+/// a broken backend does not go unnoticed just because it is fast.
+pub fn run(options: &BenchOptions, dialect: &Rc<Dialect>, verbose: bool) -> Result<()> {
+    let workloads = if options.sources.is_empty() {
+        let brainfuck = Rc::new(Dialect::brainfuck());
+        BUNDLED
+            .iter()
+            .map(|(name, code)| Workload {
+                name: name.to_string(),
+                code: code.as_bytes().to_vec(),
+                dialect: Rc::clone(&brainfuck),
+            })
+            .collect()
+    } else {
+        options
+            .sources
+            .iter()
+            .map(|source| Workload::load(source, dialect))
+            .collect::<Result<Vec<_>>>()?
+    };
+    let backends = backends_named(&options.backends)?;
+
+    let mut measurements = vec![];
+    for workload in &workloads {
+        // Output of the first backend, which all others have to match
+        let mut expected: Option<(&str, Vec<u8>)> = None;
+        for backend in &backends {
+            let program = Parser::default()
+                .parse_program(&mut Lexer::new(
+                    &workload.code[..],
+                    Rc::clone(&workload.dialect),
+                ))
+                .with_context(|| format!("parse workload {}", workload.name))?;
+            if let Err(reason) = backend.check(&program, &options.config) {
+                eprintln!(
+                    "Skipping {} on {} ({reason})",
+                    workload.name,
+                    backend.name()
+                );
+                continue;
+            }
+
+            let mut fastest: Option<Phases> = None;
+            for run in 1..=options.runs {
+                if verbose {
+                    eprintln!(
+                        "Running {} on {} ({run}/{})",
+                        workload.name,
+                        backend.name(),
+                        options.runs
+                    );
+                }
+                let (phases, output) = measure(workload, backend.as_ref(), &options.config)
+                    .with_context(|| {
+                        format!("run workload {} on {}", workload.name, backend.name())
+                    })?;
+                match &expected {
+                    None => expected = Some((backend.name(), output)),
+                    Some((reference, expected)) if *expected != output => {
+                        return Err(anyhow!(
+                            "Output of {} on {} differs from the one on {reference}",
+                            workload.name,
+                            backend.name()
+                        ));
+                    }
+                    Some(_) => {}
+                }
+                if fastest.is_none_or(|fastest| phases.total() < fastest.total()) {
+                    fastest = Some(phases);
+                }
+            }
+            measurements.push(Measurement {
+                workload: workload.name.clone(),
+                backend: backend.name(),
+                phases: fastest.expect("at least a single run"),
+            });
+        }
+    }
+
+    if options.json {
+        println!("{}", to_json(&measurements, options.runs));
+    } else {
+        print!("{}", to_table(&measurements));
+    }
+    Ok(())
+}
@@ -0,0 +1,363 @@
+use std::io::{Read, Write};
+
+use crate::backend::CellWidth;
+use crate::{Instruction, Program};
+use anyhow::{anyhow, Context, Result};
+
+/// Magic bytes every bytecode file starts with.
+pub const MAGIC: &[u8; 4] = b"RBBC";
+/// Version of the bytecode format. Needs to be increased on every
+/// incompatible change.
+pub const VERSION: u8 = 1;
+
+const OP_ADDR_RIGHT: u8 = 0x01;
+const OP_ADDR_LEFT: u8 = 0x02;
+const OP_INC: u8 = 0x03;
+const OP_DEC: u8 = 0x04;
+const OP_OUTPUT: u8 = 0x05;
+const OP_INPUT: u8 = 0x06;
+const OP_JMP_FORWARD: u8 = 0x07;
+const OP_JMP_BACK: u8 = 0x08;
+const OP_PROCEDURE_START: u8 = 0x09;
+const OP_PROCEDURE_END: u8 = 0x0A;
+const OP_CALL: u8 = 0x0B;
+const OP_FORK: u8 = 0x0C;
+
+/// Metadata stored in front of the serialized program.
+///
+/// Layout (all integers little endian):
+///
+/// ```text
+/// magic "RBBC" | version: u8 | cell width in bits: u8 | optimization level: u8 | reserved: u8 | instruction count: u32
+/// ```
+///
+/// Every instruction is stored as a single opcode byte followed by its operand
+/// as unsigned LEB128. `Inc` and `Dec` store their operand as a plain byte.
+///
+/// The optimization level is informational only. The stored program is
+/// already optimized and runs unchanged, and every level produces programs
+/// of the same instructions with the same meaning, so bytecode built by
+/// another level is still safe to run.
+#[derive(Debug, Clone, Copy)]
+pub struct BytecodeHeader {
+    pub cell_width: CellWidth,
+    pub optimization_level: u8,
+}
+
+pub fn is_bytecode(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+pub fn write_varint<W: Write>(writer: &mut W, mut value: usize) -> Result<()> {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            writer.write_all(&[byte])?;
+            return Ok(());
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+pub fn read_u8<R: Read>(reader: &mut R) -> Result<u8> {
+    let mut buf: [u8; 1] = [0; 1];
+    reader
+        .read_exact(&mut buf)
+        .context("unexpected end of data")?;
+    Ok(buf[0])
+}
+
+pub fn read_varint<R: Read>(reader: &mut R) -> Result<usize> {
+    let mut value: usize = 0;
+    let mut shift = 0;
+    loop {
+        let byte = read_u8(reader)?;
+        if shift >= usize::BITS {
+            return Err(anyhow!("varint is too large"));
+        }
+        value |= ((byte & 0x7F) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+/// Read the given number of bytes. Memory is only allocated for the data
+/// actually present, so a corrupt length can not exhaust it.
+pub fn read_bytes<R: Read>(reader: &mut R, length: usize) -> Result<Vec<u8>> {
+    let mut data = vec![];
+    reader.by_ref().take(length as u64).read_to_end(&mut data)?;
+    if data.len() != length {
+        return Err(anyhow!("unexpected end of data"));
+    }
+    Ok(data)
+}
+
+/// 64 bit FNV-1a hash, which is stable across rust versions and platforms.
+pub fn fnv1a(data: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in data {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+pub fn write_program<W: Write>(
+    writer: &mut W,
+    header: &BytecodeHeader,
+    program: &Program,
+) -> Result<()> {
+    let cell_width: u8 = match header.cell_width {
+        CellWidth::U8 => 8,
+        CellWidth::U16 => 16,
+        CellWidth::U32 => 32,
+    };
+    let count: u32 = program
+        .len()
+        .try_into()
+        .context("program is too large for the bytecode format")?;
+
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[VERSION, cell_width, header.optimization_level, 0])?;
+    writer.write_all(&count.to_le_bytes())?;
+    for instruction in program {
+        match *instruction {
+            Instruction::AddrRight(value) => {
+                writer.write_all(&[OP_ADDR_RIGHT])?;
+                write_varint(writer, value)?;
+            }
+            Instruction::AddrLeft(value) => {
+                writer.write_all(&[OP_ADDR_LEFT])?;
+                write_varint(writer, value)?;
+            }
+            Instruction::Inc(value) => writer.write_all(&[OP_INC, value])?,
+            Instruction::Dec(value) => writer.write_all(&[OP_DEC, value])?,
+            Instruction::Output(value) => {
+                writer.write_all(&[OP_OUTPUT])?;
+                write_varint(writer, value)?;
+            }
+            Instruction::Input(value) => {
+                writer.write_all(&[OP_INPUT])?;
+                write_varint(writer, value)?;
+            }
+            Instruction::JmpForward(target) => {
+                writer.write_all(&[OP_JMP_FORWARD])?;
+                write_varint(writer, target)?;
+            }
+            Instruction::JmpBack(target) => {
+                writer.write_all(&[OP_JMP_BACK])?;
+                write_varint(writer, target)?;
+            }
+            Instruction::ProcedureStart(target) => {
+                writer.write_all(&[OP_PROCEDURE_START])?;
+                write_varint(writer, target)?;
+            }
+            Instruction::ProcedureEnd(target) => {
+                writer.write_all(&[OP_PROCEDURE_END])?;
+                write_varint(writer, target)?;
+            }
+            Instruction::Call => writer.write_all(&[OP_CALL])?,
+            Instruction::Fork => writer.write_all(&[OP_FORK])?,
+        }
+    }
+    writer.flush().context("flush bytecode")?;
+
+    Ok(())
+}
+
+pub fn read_program<R: Read>(reader: &mut R) -> Result<(BytecodeHeader, Program)> {
+    let mut magic: [u8; 4] = [0; 4];
+    reader
+        .read_exact(&mut magic)
+        .context("reading bytecode magic")?;
+    if &magic != MAGIC {
+        return Err(anyhow!("not a rust_brain bytecode file"));
+    }
+
+    let version = read_u8(reader)?;
+    if version != VERSION {
+        return Err(anyhow!(
+            "unsupported bytecode version {version}, expected {VERSION}"
+        ));
+    }
+    let cell_width = match read_u8(reader)? {
+        8 => CellWidth::U8,
+        16 => CellWidth::U16,
+        32 => CellWidth::U32,
+        other => return Err(anyhow!("invalid cell width {other} in bytecode header")),
+    };
+    let optimization_level = read_u8(reader)?;
+    let _reserved = read_u8(reader)?;
+    let mut count: [u8; 4] = [0; 4];
+    reader
+        .read_exact(&mut count)
+        .context("reading bytecode instruction count")?;
+    let count = u32::from_le_bytes(count) as usize;
+
+    let mut program = Program::new();
+    for _ in 0..count {
+        let instruction = match read_u8(reader)? {
+            OP_ADDR_RIGHT => Instruction::AddrRight(read_varint(reader)?),
+            OP_ADDR_LEFT => Instruction::AddrLeft(read_varint(reader)?),
+            OP_INC => Instruction::Inc(read_u8(reader)?),
+            OP_DEC => Instruction::Dec(read_u8(reader)?),
+            OP_OUTPUT => Instruction::Output(read_varint(reader)?),
+            OP_INPUT => Instruction::Input(read_varint(reader)?),
+            OP_JMP_FORWARD => Instruction::JmpForward(read_varint(reader)?),
+            OP_JMP_BACK => Instruction::JmpBack(read_varint(reader)?),
+            OP_PROCEDURE_START => Instruction::ProcedureStart(read_varint(reader)?),
+            OP_PROCEDURE_END => Instruction::ProcedureEnd(read_varint(reader)?),
+            OP_CALL => Instruction::Call,
+            OP_FORK => Instruction::Fork,
+            other => {
+                return Err(anyhow!(
+                    "invalid opcode {other:#04x} at instruction {}",
+                    program.len()
+                ))
+            }
+        };
+        program.push(instruction);
+    }
+
+    let mut trailing: [u8; 1] = [0; 1];
+    if reader.read(&mut trailing)? != 0 {
+        return Err(anyhow!("trailing data after bytecode program"));
+    }
+
+    validate_jumps(&program)?;
+
+    Ok((
+        BytecodeHeader {
+            cell_width,
+            optimization_level,
+        },
+        program,
+    ))
+}
+
+/// Ensure all loops and procedures are balanced and their jumps point behind
+/// their corresponding counterpart, exactly as the `Parser` would have
+/// generated them.
+fn validate_jumps(program: &Program) -> Result<()> {
+    let mut open_blocks = vec![];
+    for (position, instruction) in program.iter().enumerate() {
+        let target = match *instruction {
+            Instruction::JmpForward(_) | Instruction::ProcedureStart(_) => {
+                open_blocks.push(position);
+                continue;
+            }
+            Instruction::JmpBack(target) | Instruction::ProcedureEnd(target) => target,
+            _ => continue,
+        };
+
+        let start = open_blocks
+            .pop()
+            .ok_or_else(|| anyhow!("unmatched block end at instruction {position}"))?;
+        let start_target = match (&program[start], instruction) {
+            (Instruction::JmpForward(target), Instruction::JmpBack(_))
+            | (Instruction::ProcedureStart(target), Instruction::ProcedureEnd(_)) => *target,
+            _ => {
+                return Err(anyhow!(
+                    "mismatched block at instructions {start}..{position}"
+                ))
+            }
+        };
+        if target != start + 1 || start_target != position + 1 {
+            return Err(anyhow!(
+                "invalid jump targets for block at instructions {start}..{position}"
+            ));
+        }
+    }
+
+    if let Some(position) = open_blocks.pop() {
+        return Err(anyhow!("unmatched block start at instruction {position}"));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A program using every instruction.
+    fn program() -> Program {
+        vec![
+            Instruction::ProcedureStart(6),
+            Instruction::AddrRight(300),
+            Instruction::AddrLeft(1),
+            Instruction::Inc(255),
+            Instruction::Dec(7),
+            Instruction::ProcedureEnd(1),
+            Instruction::Output(2),
+            Instruction::JmpForward(12),
+            Instruction::Input(130),
+            Instruction::Call,
+            Instruction::Fork,
+            Instruction::JmpBack(8),
+        ]
+    }
+
+    fn write(program: &Program) -> Vec<u8> {
+        let header = BytecodeHeader {
+            cell_width: CellWidth::U16,
+            optimization_level: 3,
+        };
+        let mut data = vec![];
+        write_program(&mut data, &header, program).unwrap();
+        data
+    }
+
+    #[test]
+    fn programs_are_read_as_written() {
+        let (header, read) = read_program(&mut &write(&program())[..]).unwrap();
+        assert_eq!(read, program());
+        assert_eq!(header.cell_width, CellWidth::U16);
+        assert_eq!(header.optimization_level, 3);
+    }
+
+    #[test]
+    fn broken_files_are_rejected() {
+        let data = write(&program());
+        for length in 0..data.len() {
+            assert!(read_program(&mut &data[..length]).is_err(), "{length}");
+        }
+
+        let mut magic = data.clone();
+        magic[0] = b'X';
+        assert!(read_program(&mut &magic[..]).is_err());
+        let mut version = data.clone();
+        version[4] = VERSION + 1;
+        assert!(read_program(&mut &version[..]).is_err());
+        let mut cell_width = data.clone();
+        cell_width[5] = 12;
+        assert!(read_program(&mut &cell_width[..]).is_err());
+        let mut opcode = data.clone();
+        opcode[12] = 0xFF;
+        assert!(read_program(&mut &opcode[..]).is_err());
+        let mut trailing = data;
+        trailing.push(OP_CALL);
+        assert!(read_program(&mut &trailing[..]).is_err());
+    }
+
+    #[test]
+    fn invalid_jumps_are_rejected() {
+        let invalid = |position: usize, instruction: Instruction| {
+            let mut program = program();
+            program[position] = instruction;
+            read_program(&mut &write(&program)[..]).is_err()
+        };
+        assert!(invalid(7, Instruction::JmpForward(13)));
+        assert!(invalid(7, Instruction::JmpForward(usize::MAX)));
+        assert!(invalid(11, Instruction::JmpBack(7)));
+        assert!(invalid(0, Instruction::ProcedureStart(5)));
+        assert!(invalid(5, Instruction::ProcedureEnd(0)));
+        // Mismatched and unmatched blocks
+        assert!(invalid(5, Instruction::JmpBack(1)));
+        assert!(invalid(7, Instruction::Call));
+        assert!(invalid(11, Instruction::Call));
+    }
+}
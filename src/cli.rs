@@ -0,0 +1,885 @@
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::rc::Rc;
+
+use crate::backend::{default_cache_dir, ExecutionConfig};
+use crate::debugger;
+use crate::dialect::Dialect;
+use crate::formatter::FormatOptions;
+use crate::trace::TraceFormat;
+use crate::transpiler::Target;
+use anyhow::{anyhow, Context, Result};
+
+/// Where the brainfuck program is read from.
+pub enum ProgramSource {
+    File(String),
+    Stdin,
+    Inline(String),
+}
+
+impl ProgramSource {
+    pub fn read(&self) -> Result<Vec<u8>> {
+        match self {
+            ProgramSource::File(path) => {
+                fs::read(path).with_context(|| format!("read file {path}"))
+            }
+            ProgramSource::Stdin => {
+                let mut source = vec![];
+                std::io::stdin()
+                    .read_to_end(&mut source)
+                    .context("read program from stdin")?;
+                Ok(source)
+            }
+            ProgramSource::Inline(program) => Ok(program.as_bytes().to_vec()),
+        }
+    }
+}
+
+impl std::fmt::Display for ProgramSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProgramSource::File(path) => write!(f, "brainfuck file {path}"),
+            ProgramSource::Stdin => write!(f, "brainfuck program from stdin"),
+            ProgramSource::Inline(_) => write!(f, "inline brainfuck program"),
+        }
+    }
+}
+
+/// Where the input of the running brainfuck program is read from.
+pub enum InputSource {
+    Stdin,
+    File(String),
+    Text(String),
+}
+
+impl InputSource {
+    pub fn open(&self) -> Result<Box<dyn Read>> {
+        match self {
+            InputSource::Stdin => Ok(Box::new(std::io::stdin().lock())),
+            InputSource::File(path) => Ok(Box::new(BufReader::new(
+                File::open(path).with_context(|| format!("open input file {path}"))?,
+            ))),
+            InputSource::Text(text) => {
+                Ok(Box::new(std::io::Cursor::new(text.clone().into_bytes())))
+            }
+        }
+    }
+}
+
+/// When to write snapshots of the state of a running program.
+pub struct SnapshotOptions {
+    pub path: String,
+    /// Write a snapshot every given number of loop iterations
+    pub every: Option<u64>,
+    /// Write a snapshot after the given number of loop iterations and stop
+    pub after: Option<u64>,
+}
+
+pub struct RunOptions {
+    pub source: ProgramSource,
+    pub input: InputSource,
+    pub backend: Option<String>,
+    pub config: ExecutionConfig,
+    pub snapshot: Option<SnapshotOptions>,
+    /// Snapshot file to continue the program from
+    pub resume: Option<String>,
+    /// File to record the trace of the execution to
+    pub trace: Option<String>,
+    pub trace_format: TraceFormat,
+    /// Show the execution in the terminal
+    pub visualize: bool,
+}
+
+pub struct DebugOptions {
+    pub source: ProgramSource,
+    /// Input of the program, which is empty if not given
+    pub input: Option<InputSource>,
+    pub config: ExecutionConfig,
+    /// Number of executed instructions which can be undone
+    pub history_limit: usize,
+}
+
+/// What to look up in a trace.
+pub enum ReplayQuery {
+    /// Print every record of the trace as JSON lines
+    Print,
+    /// Find the first step in which the cell became the value
+    CellValue { cell: usize, value: u32 },
+    /// Find the step which produced the output byte at the given index
+    OutputByte(usize),
+    /// Find the first step in which the trace differs from the given one
+    Diff(String),
+}
+
+pub struct ReplayOptions {
+    pub trace: String,
+    pub query: ReplayQuery,
+}
+
+pub struct CoverageOptions {
+    pub source: ProgramSource,
+    /// Inputs of the runs, a single run reading stdin if empty
+    pub inputs: Vec<InputSource>,
+    /// File to write the lcov report to
+    pub lcov: Option<String>,
+    pub config: ExecutionConfig,
+}
+
+pub struct BuildOptions {
+    pub source: ProgramSource,
+    pub output: String,
+    pub config: ExecutionConfig,
+}
+
+pub struct TranspileCliOptions {
+    pub source: ProgramSource,
+    /// File to write the Rust code to, stdout if not given
+    pub output: Option<String>,
+    pub library: bool,
+    pub target: Target,
+    pub config: ExecutionConfig,
+}
+
+pub struct BenchOptions {
+    /// Programs to time, the bundled workloads if empty
+    pub sources: Vec<ProgramSource>,
+    /// Backends to time, all if empty
+    pub backends: Vec<String>,
+    pub runs: usize,
+    pub json: bool,
+    pub config: ExecutionConfig,
+}
+
+pub struct FmtOptions {
+    pub source: ProgramSource,
+    pub minify: bool,
+    pub format: FormatOptions,
+    /// Dialect to write the program in, the one it is read in if not given
+    pub to: Option<Rc<Dialect>>,
+}
+
+pub struct CheckOptions {
+    pub source: ProgramSource,
+    pub json: bool,
+}
+
+pub enum Command {
+    Run(RunOptions),
+    Build(BuildOptions),
+    Transpile(TranspileCliOptions),
+    Check(CheckOptions),
+    Fmt(FmtOptions),
+    Stats(ProgramSource),
+    Gen(InputSource),
+    Bench(BenchOptions),
+    /// Run the conformance suite on the given backends, all if empty
+    Conformance(Vec<String>),
+    Repl(ExecutionConfig),
+    Debug(DebugOptions),
+    Replay(ReplayOptions),
+    Coverage(CoverageOptions),
+    Lsp,
+    /// Print the usage, which has either been asked for or is shown because
+    /// no command has been given
+    Help {
+        requested: bool,
+    },
+}
+
+pub struct Cli {
+    pub verbose: bool,
+    pub dialect: Rc<Dialect>,
+    pub command: Command,
+}
+
+pub fn usage(command: &str) -> String {
+    format!(
+        "Usage:
+  {command} [-v] run [options] <source>    execute a program
+  {command} [-v] build [options] <source>  compile a program to bytecode
+  {command} [-v] transpile [options] <source>
+                                         translate a program to Rust or LLVM IR
+  {command} [-v] check [options] <source>  check a program for errors and common mistakes
+  {command} [-v] fmt [options] <source>    print a program in canonical formatting
+  {command} [-v] stats <source>            print statistics about a program
+  {command} [-v] gen <text>                generate a program printing the given text
+  {command} [-v] bench [options] [<source>...]
+                                         time programs on every backend, the bundled
+                                         workloads if no source is given
+  {command} [-v] conformance [options]     check which implementation-defined behaviors
+                                         every backend and configuration matches
+  {command} [-v] repl [options]            run snippets interactively on a persistent tape
+  {command} [-v] debug [options] <source>  step through a program, forwards and backwards
+  {command} [-v] replay [options] <trace>  query a trace recorded by run --trace
+  {command} [-v] coverage [options] <source>
+                                         report which commands and loop branches
+                                         executed over runs with different inputs
+  {command} [-v] lsp                       serve the language server protocol over stdio
+  {command} [-v] <source>                  shorthand for run
+
+Sources:
+  <file>                  read the program from the given file
+  -                       read the program from stdin
+  -e <program>            use the given program text
+
+Options:
+  -v, --verbose           print diagnostics to stderr
+  --dialect <dialect>     surface syntax of the source (brainfuck, pbrain,
+                          brainfork, ook, blub, spoon or a TOML file with a
+                          keyword table)
+  --backend <name>        execution backend to use (jit, vm, interp; may be
+                          repeated for bench and conformance)
+  --tape-size <cells>     number of cells on the tape
+  --cell-width <bits>     width of a cell (8, 16, 32)
+  --eof <behavior>        cell value on EOF (unchanged, zero, minus-one)
+  --cache                 cache compiled code in the default cache directory
+  --cache-dir <dir>       cache compiled code in the given directory
+  --os-threads            run forked threads in parallel instead of in turns
+  --input <file>          read program input from the given file (or the text
+                          to print for gen, - to read it from stdin)
+  --input-string <text>   use the given text as program input (both may be
+                          repeated for coverage, one run per input)
+  --snapshot <file>       file to write snapshots of the program state to (run
+                          only, the input is read completely in advance)
+  --snapshot-every <n>    write a snapshot every n loop iterations
+  --snapshot-after <n>    write a snapshot after n loop iterations and stop
+  --resume <file>         continue the program from the given snapshot (run
+                          only)
+  --trace <file>          record every executed instruction to the given file,
+                          running the program in the interpreter (run only)
+  --trace-format <format> format of the trace (binary, jsonl; default: binary)
+  --visualize             show the tape and the executed source line in the
+                          terminal while running in the interpreter (run only)
+  --cell <n>              find the first step in which cell n became the value
+                          given by --value (replay only)
+  --value <value>         value of the cell to find (replay only)
+  --output-byte <n>       find the step producing output byte n, counting from
+                          0 (replay only)
+  --diff <trace>          find the first step in which the traces differ
+                          (replay only)
+  --history <count>       number of executed instructions which can be undone
+                          (debug only, default: 1048576)
+  --lcov <file>           write an lcov report to the given file (coverage
+                          only)
+  -o, --output <file>     file to write the bytecode or translated code to
+                          (build, transpile)
+  --target <language>     language to translate to (rust, llvm; transpile
+                          only, default: rust); llvm emits opaque pointers,
+                          which require LLVM 15 or later
+  --lib                   emit a module exposing run instead of a main function
+                          (transpile only)
+  --json                  report warnings or timings as JSON (check, bench)
+  --runs <count>          number of runs per backend, of which the fastest
+                          is reported (bench only, default: 3)
+  --width <columns>       maximal line width (fmt only, default: 80)
+  --strip-comments        remove comments while formatting (fmt only)
+  --minify                strip everything but commands (fmt only)
+  --to <dialect>          dialect to write the program in (fmt only, default:
+                          the dialect of the source)"
+    )
+}
+
+/// Iterator over the command line arguments, which knows how to fetch the
+/// value belonging to an option.
+///
+/// The verbose flag, the dialect and requests for help are accepted at any
+/// position and therefore handled here.
+struct Args {
+    args: std::vec::IntoIter<String>,
+    verbose: bool,
+    dialect: Option<String>,
+    /// Whether help was requested, which ends the arguments
+    help: bool,
+}
+
+impl Args {
+    fn next(&mut self) -> Option<String> {
+        loop {
+            let arg = self.args.next()?;
+            match arg.as_str() {
+                "-v" | "--verbose" => self.verbose = true,
+                // A missing value is reported once all arguments are parsed
+                "--dialect" => self.dialect = Some(self.args.next().unwrap_or_default()),
+                "-h" | "--help" => {
+                    self.help = true;
+                    return None;
+                }
+                _ => return Some(arg),
+            }
+        }
+    }
+
+    fn value(&mut self, option: &str) -> Result<String> {
+        self.args
+            .next()
+            .ok_or_else(|| anyhow!("Missing value for {option}"))
+    }
+}
+
+/// Try to parse the given argument as a program source. Returns `false` if it
+/// is something else.
+fn parse_source(arg: &str, args: &mut Args, source: &mut Option<ProgramSource>) -> Result<bool> {
+    let parsed = match arg {
+        "-e" => ProgramSource::Inline(args.value(arg)?),
+        "-" => ProgramSource::Stdin,
+        _ if arg.starts_with('-') => return Ok(false),
+        _ => ProgramSource::File(arg.to_string()),
+    };
+    if source.is_some() {
+        return Err(anyhow!("Only a single program source may be given"));
+    }
+    *source = Some(parsed);
+    Ok(true)
+}
+
+fn parse_tape_size(arg: &str, args: &mut Args) -> Result<usize> {
+    let value = args.value(arg)?;
+    let tape_size = value
+        .parse()
+        .with_context(|| format!("parse tape size {value}"))?;
+    if tape_size == 0 {
+        return Err(anyhow!("The tape needs at least a single cell"));
+    }
+    Ok(tape_size)
+}
+
+/// Try to parse the given argument as an option of the execution
+/// configuration. Returns `false` if it is something else.
+fn parse_config_option(arg: &str, args: &mut Args, config: &mut ExecutionConfig) -> Result<bool> {
+    match arg {
+        "--tape-size" => config.tape_size = parse_tape_size(arg, args)?,
+        "--cell-width" => {
+            config.cell_width = args.value(arg)?.parse()?;
+            config.cell_width_given = true;
+        }
+        "--eof" => config.eof = args.value(arg)?.parse()?,
+        "--cache" => {
+            config.cache_dir = Some(default_cache_dir().ok_or_else(|| {
+                anyhow!("Could not determine cache directory, use --cache-dir instead")
+            })?)
+        }
+        "--cache-dir" => config.cache_dir = Some(args.value(arg)?.into()),
+        "--os-threads" => config.os_threads = true,
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+fn require_source(source: Option<ProgramSource>) -> Result<ProgramSource> {
+    source.ok_or_else(|| anyhow!("No program source given"))
+}
+
+/// Parse the number of loop iterations given for a snapshot option.
+fn parse_iterations(arg: &str, args: &mut Args) -> Result<u64> {
+    let value = args.value(arg)?;
+    let iterations = value
+        .parse()
+        .with_context(|| format!("parse number of loop iterations {value}"))?;
+    if iterations == 0 && arg == "--snapshot-every" {
+        return Err(anyhow!("Number of loop iterations must be at least 1"));
+    }
+    Ok(iterations)
+}
+
+fn parse_run(args: &mut Args, first: Option<String>) -> Result<Command> {
+    let mut source = None;
+    let mut input = InputSource::Stdin;
+    let mut backend = None;
+    let mut config = ExecutionConfig::default();
+    let mut snapshot_path = None;
+    let mut every = None;
+    let mut after = None;
+    let mut resume = None;
+    let mut trace = None;
+    let mut trace_format = TraceFormat::Binary;
+    let mut visualize = false;
+    let mut next = first.or_else(|| args.next());
+    while let Some(arg) = next {
+        if !parse_source(&arg, args, &mut source)? && !parse_config_option(&arg, args, &mut config)?
+        {
+            match arg.as_str() {
+                "--backend" => backend = Some(args.value(&arg)?),
+                "--input" => input = InputSource::File(args.value(&arg)?),
+                "--input-string" => input = InputSource::Text(args.value(&arg)?),
+                "--snapshot" => snapshot_path = Some(args.value(&arg)?),
+                "--snapshot-every" => every = Some(parse_iterations(&arg, args)?),
+                "--snapshot-after" => after = Some(parse_iterations(&arg, args)?),
+                "--resume" => resume = Some(args.value(&arg)?),
+                "--trace" => trace = Some(args.value(&arg)?),
+                "--trace-format" => trace_format = args.value(&arg)?.parse()?,
+                "--visualize" => visualize = true,
+                _ => return Err(anyhow!("Unknown option {arg} for run")),
+            }
+        }
+        next = args.next();
+    }
+
+    let snapshot = match (snapshot_path, every.is_some() || after.is_some()) {
+        (Some(path), true) => Some(SnapshotOptions { path, every, after }),
+        (Some(_), false) => {
+            return Err(anyhow!(
+                "--snapshot requires --snapshot-every or --snapshot-after"
+            ))
+        }
+        (None, true) => return Err(anyhow!("No snapshot file given, use --snapshot <file>")),
+        (None, false) => None,
+    };
+    if config.os_threads && (snapshot.is_some() || resume.is_some()) {
+        return Err(anyhow!("Snapshots are not supported with --os-threads"));
+    }
+    if trace.is_some() {
+        if config.os_threads {
+            return Err(anyhow!("Traces can not be recorded with --os-threads"));
+        }
+        if snapshot.is_some() || resume.is_some() {
+            return Err(anyhow!(
+                "Traces can not be recorded together with snapshots"
+            ));
+        }
+    }
+    if visualize {
+        if config.os_threads {
+            return Err(anyhow!("Visualizing is not supported with --os-threads"));
+        }
+        if snapshot.is_some() || resume.is_some() || trace.is_some() {
+            return Err(anyhow!(
+                "Visualizing can not be combined with snapshots or traces"
+            ));
+        }
+    }
+    Ok(Command::Run(RunOptions {
+        source: require_source(source)?,
+        input,
+        backend,
+        config,
+        snapshot,
+        resume,
+        trace,
+        trace_format,
+        visualize,
+    }))
+}
+
+fn parse_build(args: &mut Args) -> Result<Command> {
+    let mut config = ExecutionConfig::default();
+    let mut source = None;
+    let mut output = None;
+    while let Some(arg) = args.next() {
+        if !parse_source(&arg, args, &mut source)? {
+            match arg.as_str() {
+                "-o" | "--output" => output = Some(args.value(&arg)?),
+                "--cell-width" => {
+                    config.cell_width = args.value(&arg)?.parse()?;
+                    config.cell_width_given = true;
+                }
+                _ => return Err(anyhow!("Unknown option {arg} for build")),
+            }
+        }
+    }
+    Ok(Command::Build(BuildOptions {
+        source: require_source(source)?,
+        output: output.ok_or_else(|| anyhow!("No output file given, use -o <file>"))?,
+        config,
+    }))
+}
+
+fn parse_transpile(args: &mut Args) -> Result<Command> {
+    let mut config = ExecutionConfig::default();
+    let mut source = None;
+    let mut output = None;
+    let mut library = false;
+    let mut target = Target::Rust;
+    while let Some(arg) = args.next() {
+        if !parse_source(&arg, args, &mut source)? && !parse_config_option(&arg, args, &mut config)?
+        {
+            match arg.as_str() {
+                "-o" | "--output" => output = Some(args.value(&arg)?),
+                "--lib" => library = true,
+                "--target" => target = args.value(&arg)?.parse()?,
+                _ => return Err(anyhow!("Unknown option {arg} for transpile")),
+            }
+        }
+    }
+    Ok(Command::Transpile(TranspileCliOptions {
+        source: require_source(source)?,
+        output,
+        library,
+        target,
+        config,
+    }))
+}
+
+fn parse_check(args: &mut Args) -> Result<Command> {
+    let mut source = None;
+    let mut json = false;
+    while let Some(arg) = args.next() {
+        if !parse_source(&arg, args, &mut source)? {
+            match arg.as_str() {
+                "--json" => json = true,
+                _ => return Err(anyhow!("Unknown option {arg} for check")),
+            }
+        }
+    }
+    Ok(Command::Check(CheckOptions {
+        source: require_source(source)?,
+        json,
+    }))
+}
+
+fn parse_fmt(args: &mut Args) -> Result<Command> {
+    let mut source = None;
+    let mut minify = false;
+    let mut format = FormatOptions::default();
+    let mut to = None;
+    while let Some(arg) = args.next() {
+        if !parse_source(&arg, args, &mut source)? {
+            match arg.as_str() {
+                "--minify" => minify = true,
+                "--to" => to = Some(Rc::new(Dialect::load(&args.value(&arg)?)?)),
+                "--strip-comments" => format.comments = false,
+                "--width" => {
+                    let value = args.value(&arg)?;
+                    format.width = value
+                        .parse()
+                        .with_context(|| format!("parse width {value}"))?;
+                }
+                _ => return Err(anyhow!("Unknown option {arg} for fmt")),
+            }
+        }
+    }
+    Ok(Command::Fmt(FmtOptions {
+        source: require_source(source)?,
+        minify,
+        format,
+        to,
+    }))
+}
+
+fn parse_gen(args: &mut Args) -> Result<Command> {
+    let mut text = None;
+    while let Some(arg) = args.next() {
+        let parsed = match arg.as_str() {
+            "--input" => match args.value(&arg)?.as_str() {
+                "-" => InputSource::Stdin,
+                path => InputSource::File(path.to_string()),
+            },
+            _ if arg.starts_with('-') => return Err(anyhow!("Unknown option {arg} for gen")),
+            _ => InputSource::Text(arg),
+        };
+        if text.is_some() {
+            return Err(anyhow!("Only a single text may be given"));
+        }
+        text = Some(parsed);
+    }
+    Ok(Command::Gen(text.ok_or_else(|| {
+        anyhow!("No text given, use gen <text> or gen --input <file>")
+    })?))
+}
+
+fn parse_bench(args: &mut Args) -> Result<Command> {
+    let mut options = BenchOptions {
+        sources: vec![],
+        backends: vec![],
+        runs: 3,
+        json: false,
+        config: ExecutionConfig::default(),
+    };
+    while let Some(arg) = args.next() {
+        let mut source = None;
+        if parse_source(&arg, args, &mut source)? {
+            options.sources.extend(source);
+        } else if !parse_config_option(&arg, args, &mut options.config)? {
+            match arg.as_str() {
+                "--backend" => options.backends.push(args.value(&arg)?),
+                "--json" => options.json = true,
+                "--runs" => {
+                    let value = args.value(&arg)?;
+                    options.runs = value
+                        .parse()
+                        .with_context(|| format!("parse number of runs {value}"))?;
+                    if options.runs == 0 {
+                        return Err(anyhow!("Number of runs must be at least 1"));
+                    }
+                }
+                _ => return Err(anyhow!("Unknown option {arg} for bench")),
+            }
+        }
+    }
+    Ok(Command::Bench(options))
+}
+
+fn parse_conformance(args: &mut Args) -> Result<Command> {
+    let mut backends = vec![];
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--backend" => backends.push(args.value(&arg)?),
+            _ => return Err(anyhow!("Unknown option {arg} for conformance")),
+        }
+    }
+    Ok(Command::Conformance(backends))
+}
+
+fn parse_repl(args: &mut Args) -> Result<Command> {
+    let mut config = ExecutionConfig::default();
+    while let Some(arg) = args.next() {
+        if !parse_config_option(&arg, args, &mut config)? {
+            return Err(anyhow!("Unknown option {arg} for repl"));
+        }
+    }
+    Ok(Command::Repl(config))
+}
+
+fn parse_debug(args: &mut Args) -> Result<Command> {
+    let mut source = None;
+    let mut input = None;
+    let mut config = ExecutionConfig::default();
+    let mut history_limit = debugger::DEFAULT_HISTORY_LIMIT;
+    while let Some(arg) = args.next() {
+        if !parse_source(&arg, args, &mut source)? && !parse_config_option(&arg, args, &mut config)?
+        {
+            match arg.as_str() {
+                "--input" => input = Some(InputSource::File(args.value(&arg)?)),
+                "--input-string" => input = Some(InputSource::Text(args.value(&arg)?)),
+                "--history" => {
+                    let value = args.value(&arg)?;
+                    history_limit = value
+                        .parse()
+                        .with_context(|| format!("parse history size {value}"))?;
+                    if history_limit == 0 {
+                        return Err(anyhow!("History size must be at least 1"));
+                    }
+                }
+                _ => return Err(anyhow!("Unknown option {arg} for debug")),
+            }
+        }
+    }
+    Ok(Command::Debug(DebugOptions {
+        source: require_source(source)?,
+        input,
+        config,
+        history_limit,
+    }))
+}
+
+fn parse_replay(args: &mut Args) -> Result<Command> {
+    let mut trace = None;
+    let mut cell = None;
+    let mut value = None;
+    let mut output_byte = None;
+    let mut diff = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--cell" => {
+                let text = args.value(&arg)?;
+                cell = Some(text.parse().with_context(|| format!("parse cell {text}"))?);
+            }
+            "--value" => {
+                let text = args.value(&arg)?;
+                value = Some(
+                    text.parse()
+                        .with_context(|| format!("parse value {text}"))?,
+                );
+            }
+            "--output-byte" => {
+                let text = args.value(&arg)?;
+                output_byte = Some(
+                    text.parse()
+                        .with_context(|| format!("parse output byte {text}"))?,
+                );
+            }
+            "--diff" => diff = Some(args.value(&arg)?),
+            _ if arg.starts_with('-') => return Err(anyhow!("Unknown option {arg} for replay")),
+            _ if trace.is_some() => return Err(anyhow!("Only a single trace may be given")),
+            _ => trace = Some(arg),
+        }
+    }
+
+    let mut queries = vec![];
+    match (cell, value) {
+        (Some(cell), Some(value)) => queries.push(ReplayQuery::CellValue { cell, value }),
+        (None, None) => {}
+        _ => return Err(anyhow!("--cell and --value have to be given together")),
+    }
+    queries.extend(output_byte.map(ReplayQuery::OutputByte));
+    queries.extend(diff.map(ReplayQuery::Diff));
+    if queries.len() > 1 {
+        return Err(anyhow!("Only a single query may be given"));
+    }
+    Ok(Command::Replay(ReplayOptions {
+        trace: trace.ok_or_else(|| anyhow!("No trace given"))?,
+        query: queries.pop().unwrap_or(ReplayQuery::Print),
+    }))
+}
+
+fn parse_coverage(args: &mut Args) -> Result<Command> {
+    let mut source = None;
+    let mut inputs = vec![];
+    let mut lcov = None;
+    let mut config = ExecutionConfig::default();
+    while let Some(arg) = args.next() {
+        if !parse_source(&arg, args, &mut source)? && !parse_config_option(&arg, args, &mut config)?
+        {
+            match arg.as_str() {
+                "--input" => inputs.push(InputSource::File(args.value(&arg)?)),
+                "--input-string" => inputs.push(InputSource::Text(args.value(&arg)?)),
+                "--lcov" => lcov = Some(args.value(&arg)?),
+                _ => return Err(anyhow!("Unknown option {arg} for coverage")),
+            }
+        }
+    }
+    if config.os_threads {
+        return Err(anyhow!("Coverage is not supported with --os-threads"));
+    }
+    Ok(Command::Coverage(CoverageOptions {
+        source: require_source(source)?,
+        inputs,
+        lcov,
+        config,
+    }))
+}
+
+fn parse_source_only(args: &mut Args, name: &str) -> Result<ProgramSource> {
+    let mut source = None;
+    while let Some(arg) = args.next() {
+        if !parse_source(&arg, args, &mut source)? {
+            return Err(anyhow!("Unknown option {arg} for {name}"));
+        }
+    }
+    require_source(source)
+}
+
+pub fn parse(args: Vec<String>) -> Result<Cli> {
+    let mut args = Args {
+        args: args.into_iter(),
+        verbose: false,
+        dialect: None,
+        help: false,
+    };
+
+    let command = match args.next() {
+        None => Ok(Command::Help {
+            requested: args.help,
+        }),
+        Some(arg) => match arg.as_str() {
+            "help" => Ok(Command::Help { requested: true }),
+            "run" => parse_run(&mut args, None),
+            "build" => parse_build(&mut args),
+            "transpile" => parse_transpile(&mut args),
+            "check" => parse_check(&mut args),
+            "fmt" => parse_fmt(&mut args),
+            "stats" => parse_source_only(&mut args, "stats").map(Command::Stats),
+            "gen" => parse_gen(&mut args),
+            "bench" => parse_bench(&mut args),
+            "conformance" => parse_conformance(&mut args),
+            "repl" => parse_repl(&mut args),
+            "debug" => parse_debug(&mut args),
+            "replay" => parse_replay(&mut args),
+            "coverage" => parse_coverage(&mut args),
+            "lsp" => match args.next() {
+                Some(arg) => Err(anyhow!("Unknown option {arg} for lsp")),
+                None => Ok(Command::Lsp),
+            },
+            // Anything else is the start of the options for an implicit run
+            _ => parse_run(&mut args, Some(arg)),
+        },
+    };
+    // Help is shown instead of reporting missing arguments of the command
+    let command = match command {
+        _ if args.help => Command::Help { requested: true },
+        command => command?,
+    };
+
+    let dialect = match args.dialect.as_deref() {
+        None => Dialect::default(),
+        Some("") => return Err(anyhow!("Missing value for --dialect")),
+        Some(dialect) => Dialect::load(dialect)?,
+    };
+
+    Ok(Cli {
+        verbose: args.verbose,
+        dialect: Rc::new(dialect),
+        command,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{CellWidth, EofBehavior};
+
+    fn parse_args(args: &[&str]) -> Result<Cli> {
+        parse(args.iter().map(|arg| arg.to_string()).collect())
+    }
+
+    #[test]
+    fn tape_size_is_at_least_one() {
+        for command in ["run", "transpile"] {
+            assert!(parse_args(&[command, "--tape-size", "0", "-e", "+"]).is_err());
+            assert!(parse_args(&[command, "--tape-size", "-1", "-e", "+"]).is_err());
+        }
+        let cli = parse_args(&["run", "--tape-size", "1", "-e", "+"]).unwrap();
+        let Command::Run(options) = cli.command else {
+            panic!("expected a run command");
+        };
+        assert_eq!(options.config.tape_size, 1);
+    }
+
+    #[test]
+    fn every_command_accepts_help() {
+        let commands = [
+            "run",
+            "build",
+            "transpile",
+            "check",
+            "fmt",
+            "stats",
+            "gen",
+            "bench",
+            "conformance",
+            "repl",
+            "debug",
+            "replay",
+            "coverage",
+            "lsp",
+        ];
+        for command in commands {
+            for help in ["-h", "--help"] {
+                let cli = parse_args(&[command, help]).unwrap();
+                assert!(
+                    matches!(cli.command, Command::Help { requested: true }),
+                    "{command} {help}"
+                );
+            }
+        }
+        for args in [&["-h"][..], &["help"], &["-e", "+", "-h"]] {
+            let cli = parse_args(args).unwrap();
+            assert!(matches!(cli.command, Command::Help { requested: true }));
+        }
+        // Values of options are never taken for help
+        let cli = parse_args(&["run", "-e", "-h"]).unwrap();
+        assert!(matches!(cli.command, Command::Run(_)));
+    }
+
+    #[test]
+    fn transpile_accepts_config_options() {
+        let cli = parse_args(&[
+            "transpile",
+            "--cell-width",
+            "16",
+            "--eof",
+            "zero",
+            "--tape-size",
+            "10",
+            "-e",
+            "+",
+        ])
+        .unwrap();
+        let Command::Transpile(options) = cli.command else {
+            panic!("expected a transpile command");
+        };
+        assert_eq!(options.config.cell_width, CellWidth::U16);
+        assert!(options.config.cell_width_given);
+        assert_eq!(options.config.eof, EofBehavior::Zero);
+        assert_eq!(options.config.tape_size, 10);
+    }
+}
@@ -0,0 +1,214 @@
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process;
+
+use crate::backend::CellWidth;
+use crate::bytecode::{self, fnv1a, read_u8, read_varint, write_varint, BytecodeHeader};
+use crate::optimizer::OPTIMIZATION_LEVEL;
+use crate::Program;
+use anyhow::{anyhow, Context, Result};
+
+/// Magic bytes every artifact starts with.
+const MAGIC: &[u8; 4] = b"RBJC";
+/// Version of the artifact format. Needs to be increased on every
+/// incompatible change.
+const VERSION: u8 = 1;
+
+/// On-disk storage for machine code generated by the JIT.
+///
+/// Artifacts are stored in a subdirectory per architecture, named after a
+/// hash of their key. As the code is executed once loaded, every artifact
+/// carries its complete key together with a checksum of the code, and is only
+/// used if both match:
+///
+/// ```text
+/// magic "RBJC" | version: u8 | key length | key | code length | checksum: u64 | code
+/// ```
+///
+/// Lengths are unsigned LEB128 like in bytecode, the checksum is the FNV-1a
+/// hash of the code in little endian.
+pub struct CodeCache {
+    directory: PathBuf,
+}
+
+impl CodeCache {
+    pub fn new(directory: PathBuf) -> Self {
+        Self { directory }
+    }
+
+    /// Everything influencing the generated code: the program, the compiler
+    /// version and all code generation options.
+    pub fn key(
+        program: &Program,
+        architecture: &str,
+        cell_width: CellWidth,
+        snapshots: bool,
+    ) -> Result<Vec<u8>> {
+        // The bytecode representation is a stable serialization of the
+        // program including the options it has been created with.
+        let mut key = vec![];
+        bytecode::write_program(
+            &mut key,
+            &BytecodeHeader {
+                cell_width,
+                optimization_level: OPTIMIZATION_LEVEL,
+            },
+            program,
+        )?;
+        key.extend_from_slice(env!("CARGO_PKG_VERSION").as_bytes());
+        key.extend_from_slice(architecture.as_bytes());
+        if snapshots {
+            key.extend_from_slice(b"snapshots");
+        }
+        Ok(key)
+    }
+
+    fn path(&self, key: &[u8], architecture: &str) -> PathBuf {
+        self.directory
+            .join(architecture)
+            .join(format!("{:016x}.bin", fnv1a(key)))
+    }
+
+    /// The code stored for the given key, if it has been stored before and
+    /// the artifact is intact. Truncated or foreign files, as well as
+    /// artifacts of other keys sharing the hash, are ignored.
+    pub fn load(&self, key: &[u8], architecture: &str) -> Option<Vec<u8>> {
+        let data = fs::read(self.path(key, architecture)).ok()?;
+        read_artifact(&mut &data[..], key).ok()
+    }
+
+    pub fn store(&self, key: &[u8], architecture: &str, code: &[u8]) -> Result<()> {
+        let path = self.path(key, architecture);
+        let directory = path.parent().expect("artifact path to have a parent");
+        fs::create_dir_all(directory)
+            .with_context(|| format!("create cache directory {}", directory.display()))?;
+
+        // Write to a temporary file first and move it into place afterwards,
+        // so concurrent runs never observe a partially written artifact.
+        let temporary = directory.join(format!("{:016x}.{}.tmp", fnv1a(key), process::id()));
+        let mut file = File::create(&temporary)
+            .with_context(|| format!("create cache file {}", temporary.display()))?;
+        write_artifact(&mut file, key, code)
+            .with_context(|| format!("write cache file {}", temporary.display()))?;
+        drop(file);
+        fs::rename(&temporary, &path)
+            .with_context(|| format!("move cache file into place at {}", path.display()))?;
+
+        Ok(())
+    }
+}
+
+fn write_artifact<W: Write>(writer: &mut W, key: &[u8], code: &[u8]) -> Result<()> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[VERSION])?;
+    write_varint(writer, key.len())?;
+    writer.write_all(key)?;
+    write_varint(writer, code.len())?;
+    writer.write_all(&fnv1a(code).to_le_bytes())?;
+    writer.write_all(code)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Read the code of an artifact, ensuring it has been stored for the given
+/// key and is complete.
+fn read_artifact<R: Read>(reader: &mut R, key: &[u8]) -> Result<Vec<u8>> {
+    let mut magic: [u8; 4] = [0; 4];
+    reader
+        .read_exact(&mut magic)
+        .context("reading artifact magic")?;
+    if &magic != MAGIC {
+        return Err(anyhow!("not a rust_brain code artifact"));
+    }
+    let version = read_u8(reader)?;
+    if version != VERSION {
+        return Err(anyhow!(
+            "unsupported artifact version {version}, expected {VERSION}"
+        ));
+    }
+
+    if read_varint(reader)? != key.len() {
+        return Err(anyhow!("artifact has been stored for a different key"));
+    }
+    let mut stored_key = vec![0; key.len()];
+    reader
+        .read_exact(&mut stored_key)
+        .context("reading artifact key")?;
+    if stored_key != key {
+        return Err(anyhow!("artifact has been stored for a different key"));
+    }
+
+    let length = read_varint(reader)?;
+    let mut checksum: [u8; 8] = [0; 8];
+    reader
+        .read_exact(&mut checksum)
+        .context("reading artifact checksum")?;
+    let mut code = vec![];
+    reader
+        .take(length as u64 + 1)
+        .read_to_end(&mut code)
+        .context("reading artifact code")?;
+    if code.len() != length {
+        return Err(anyhow!(
+            "artifact holds {} bytes of code, expected {length}",
+            code.len()
+        ));
+    }
+    if code.is_empty() || fnv1a(&code) != u64::from_le_bytes(checksum) {
+        return Err(anyhow!("checksum of artifact code does not match"));
+    }
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn artifact(key: &[u8], code: &[u8]) -> Vec<u8> {
+        let mut data = vec![];
+        write_artifact(&mut data, key, code).unwrap();
+        data
+    }
+
+    #[test]
+    fn intact_artifacts_are_read() {
+        let data = artifact(b"key", &[0xC3, 0x90]);
+        assert_eq!(read_artifact(&mut &data[..], b"key").unwrap(), [0xC3, 0x90]);
+    }
+
+    #[test]
+    fn truncated_artifacts_are_rejected() {
+        let data = artifact(b"key", &[0xC3, 0x90, 0x90, 0x90]);
+        for length in 0..data.len() {
+            assert!(read_artifact(&mut &data[..length], b"key").is_err());
+        }
+    }
+
+    #[test]
+    fn trailing_data_is_rejected() {
+        let mut data = artifact(b"key", &[0xC3]);
+        data.push(0x90);
+        assert!(read_artifact(&mut &data[..], b"key").is_err());
+    }
+
+    #[test]
+    fn artifacts_of_other_keys_are_rejected() {
+        let data = artifact(b"key", &[0xC3]);
+        assert!(read_artifact(&mut &data[..], b"kez").is_err());
+        assert!(read_artifact(&mut &data[..], b"key2").is_err());
+    }
+
+    #[test]
+    fn corrupted_code_is_rejected() {
+        let mut data = artifact(b"key", &[0xC3, 0x90]);
+        *data.last_mut().unwrap() ^= 1;
+        assert!(read_artifact(&mut &data[..], b"key").is_err());
+    }
+
+    #[test]
+    fn foreign_files_are_rejected() {
+        assert!(read_artifact(&mut &b"\xC3\x90\x90\x90\x90\x90\x90"[..], b"key").is_err());
+        assert!(read_artifact(&mut &b""[..], b"key").is_err());
+    }
+}
@@ -0,0 +1,373 @@
+use std::env;
+use std::io::{Read, Write};
+use std::process::{self, Stdio};
+use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::backend::{backends_named, CellWidth, EofBehavior, ExecutionConfig};
+use crate::dialect::Dialect;
+use crate::{Lexer, Parser};
+use anyhow::{Context, Result};
+
+/// Time a single case may take before it is considered to hang.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+const CELL_WIDTHS: [(&str, CellWidth); 3] = [
+    ("8", CellWidth::U8),
+    ("16", CellWidth::U16),
+    ("32", CellWidth::U32),
+];
+
+const EOF_BEHAVIORS: [(&str, EofBehavior); 3] = [
+    ("unchanged", EofBehavior::Unchanged),
+    ("zero", EofBehavior::Zero),
+    ("minus-one", EofBehavior::MinusOne),
+];
+
+/// What a conforming implementation does when running a case.
+enum Expected {
+    /// Print the same output for every configuration
+    Output(&'static [u8]),
+    /// Print the output given for 8, 16 and 32 bit cells
+    ByCellWidth([&'static [u8]; 3]),
+    /// Print the output given for EOF leaving the cell unchanged, setting it
+    /// to zero and setting it to minus one
+    ByEof([&'static [u8]; 3]),
+    /// Stop with an error after printing the given output
+    Error(&'static [u8]),
+}
+
+impl Expected {
+    /// The output expected for the configuration given by the indices into
+    /// `CELL_WIDTHS` and `EOF_BEHAVIORS`, and whether it ends with an error.
+    fn output(&self, cell_width_index: usize, eof_index: usize) -> (&'static [u8], bool) {
+        match self {
+            Expected::Output(output) => (output, false),
+            Expected::ByCellWidth(outputs) => (outputs[cell_width_index], false),
+            Expected::ByEof(outputs) => (outputs[eof_index], false),
+            Expected::Error(output) => (output, true),
+        }
+    }
+}
+
+/// A program of the conformance suite, checking a single behavior.
+struct Case {
+    name: &'static str,
+    program: &'static str,
+    input: &'static [u8],
+    expected: Expected,
+}
+
+const CASES: [Case; 13] = [
+    Case {
+        name: "wraparound",
+        program: include_str!("../conformance/wraparound.b"),
+        input: b"",
+        expected: Expected::Output(b"Y\n"),
+    },
+    Case {
+        name: "cell_width",
+        program: include_str!("../conformance/cell_width.b"),
+        input: b"",
+        expected: Expected::ByCellWidth([b"YY\n", b"NY\n", b"NN\n"]),
+    },
+    Case {
+        name: "long_runs",
+        program: include_str!("../conformance/long_runs.b"),
+        input: b"",
+        expected: Expected::Output(b"CA\n"),
+    },
+    Case {
+        name: "eof",
+        program: include_str!("../conformance/eof.b"),
+        input: b"",
+        expected: Expected::ByEof([b"B\n", b"A\n", b"@\n"]),
+    },
+    Case {
+        name: "input",
+        program: include_str!("../conformance/input.b"),
+        input: b"ab",
+        expected: Expected::ByEof([b"abb", b"ab\0", b"ab\xff"]),
+    },
+    Case {
+        name: "pointer_start",
+        program: include_str!("../conformance/pointer_start.b"),
+        input: b"",
+        expected: Expected::Error(b"A"),
+    },
+    Case {
+        name: "pointer_end",
+        program: include_str!("../conformance/pointer_end.b"),
+        input: b"",
+        expected: Expected::Error(b""),
+    },
+    Case {
+        name: "deep_nesting",
+        program: include_str!("../conformance/deep_nesting.b"),
+        input: b"",
+        expected: Expected::Output(b"Y\n"),
+    },
+    Case {
+        name: "tests_memory",
+        program: include_str!("../conformance/tests_memory.b"),
+        input: b"",
+        expected: Expected::Output(b"#\n"),
+    },
+    Case {
+        name: "tests_obscure",
+        program: include_str!("../conformance/tests_obscure.b"),
+        input: b"",
+        expected: Expected::Output(b"H\n"),
+    },
+    Case {
+        name: "unmatched_open",
+        program: include_str!("../conformance/unmatched_open.b"),
+        input: b"",
+        expected: Expected::Error(b""),
+    },
+    Case {
+        name: "unmatched_close",
+        program: include_str!("../conformance/unmatched_close.b"),
+        input: b"",
+        expected: Expected::Error(b""),
+    },
+    Case {
+        name: "comments",
+        program: include_str!("../conformance/comments.b"),
+        input: b"",
+        expected: Expected::Output(b"Y\n"),
+    },
+];
+
+/// How running a case ended.
+enum Outcome {
+    Output(Vec<u8>),
+    /// Stopped with the given error after printing the output
+    Error(Vec<u8>, String),
+    /// Terminated abnormally, e.g. by a signal, after printing the output
+    Crashed(Vec<u8>, String),
+    TimedOut,
+}
+
+impl Outcome {
+    fn matches(&self, expected: &[u8], error: bool) -> bool {
+        match self {
+            Outcome::Output(output) => !error && output == expected,
+            Outcome::Error(output, _) => error && output == expected,
+            Outcome::Crashed(..) | Outcome::TimedOut => false,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Outcome::Output(output) => format!("printed {}", escape(output)),
+            Outcome::Error(output, error) => {
+                format!("printed {} and stopped with {error:?}", escape(output))
+            }
+            Outcome::Crashed(output, status) => {
+                format!("printed {} and crashed ({status})", escape(output))
+            }
+            Outcome::TimedOut => format!("timed out after {}s", TIMEOUT.as_secs()),
+        }
+    }
+}
+
+fn escape(output: &[u8]) -> String {
+    format!("\"{}\"", output.escape_ascii())
+}
+
+/// Run the program of the case in a child process, so a backend corrupting
+/// memory or hanging does not take down the runner.
+fn run_case(case: &Case, backend: &str, cell_width: &str, eof: &str) -> Result<Outcome> {
+    let executable = env::current_exe().context("locate rust_brain executable")?;
+    let mut child = process::Command::new(executable)
+        .args(["run", "--backend", backend, "--cell-width", cell_width])
+        .args(["--eof", eof, "-e", case.program])
+        .env("RUST_BACKTRACE", "0")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("start rust_brain to run a case")?;
+
+    // The program may stop before reading all of its input
+    let _ = child
+        .stdin
+        .take()
+        .expect("stdin to be piped")
+        .write_all(case.input);
+    let mut stdout = child.stdout.take().expect("stdout to be piped");
+    let mut stderr = child.stderr.take().expect("stderr to be piped");
+    let stdout = thread::spawn(move || {
+        let mut output = vec![];
+        let _ = stdout.read_to_end(&mut output);
+        output
+    });
+    let stderr = thread::spawn(move || {
+        let mut output = String::new();
+        let _ = stderr.read_to_string(&mut output);
+        output
+    });
+
+    let deadline = Instant::now() + TIMEOUT;
+    let status = loop {
+        if let Some(status) = child.try_wait().context("wait for case to finish")? {
+            break Some(status);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            break None;
+        }
+        thread::sleep(Duration::from_millis(1));
+    };
+    let output = stdout.join().expect("stdout reader not to panic");
+    let errors = stderr.join().expect("stderr reader not to panic");
+
+    Ok(match status {
+        None => Outcome::TimedOut,
+        Some(status) if status.success() => Outcome::Output(output),
+        // Errors are reported by main with exit code 1
+        Some(status) if status.code() == Some(1) => {
+            let error = errors.lines().next().unwrap_or_default();
+            let error = error.strip_prefix("Error: ").unwrap_or(error);
+            Outcome::Error(output, error.to_string())
+        }
+        Some(status) => Outcome::Crashed(output, status.to_string()),
+    })
+}
+
+/// Run every case of the conformance suite on every given backend (all if
+/// none are given) with every cell width and EOF behavior, and report which
+/// behaviors each of them matches.
+pub fn run(backends: &[String], verbose: bool) -> Result<()> {
+    let backends = backends_named(backends)?;
+    let brainfuck = Rc::new(Dialect::brainfuck());
+
+    println!(
+        "{:<7} {:>5}  {:<10} {:>7}  mismatches",
+        "backend", "cells", "eof", "matched"
+    );
+    for backend in &backends {
+        for (cell_width_index, (cell_width_name, cell_width)) in CELL_WIDTHS.iter().enumerate() {
+            for (eof_index, (eof_name, eof)) in EOF_BEHAVIORS.iter().enumerate() {
+                let config = ExecutionConfig {
+                    cell_width: *cell_width,
+                    eof: *eof,
+                    ..ExecutionConfig::default()
+                };
+                let row = format!("{:<7} {cell_width_name:>5}  {eof_name:<10}", backend.name());
+
+                let mut matched = 0;
+                let mut mismatches = vec![];
+                let mut unsupported = None;
+                for case in &CASES {
+                    // Programs which do not parse are rejected by every
+                    // backend alike
+                    let parsed = Parser::default().parse_program(&mut Lexer::new(
+                        case.program.as_bytes(),
+                        Rc::clone(&brainfuck),
+                    ));
+                    if let Ok(program) = parsed {
+                        if let Err(reason) = backend.check(&program, &config) {
+                            unsupported = Some(reason);
+                            break;
+                        }
+                    }
+
+                    let (expected, error) = case.expected.output(cell_width_index, eof_index);
+                    let outcome = run_case(case, backend.name(), cell_width_name, eof_name)?;
+                    if outcome.matches(expected, error) {
+                        matched += 1;
+                        continue;
+                    }
+                    mismatches.push(case.name);
+                    if verbose {
+                        eprintln!(
+                            "{} with {cell_width_name} bit cells and EOF {eof_name}: {} {}, but {}",
+                            backend.name(),
+                            case.name,
+                            if error {
+                                format!("should print {} and stop with an error", escape(expected))
+                            } else {
+                                format!("should print {}", escape(expected))
+                            },
+                            outcome.describe()
+                        );
+                    }
+                }
+
+                match unsupported {
+                    Some(reason) => println!("{row} {:>7}  unsupported ({reason})", "-"),
+                    None => {
+                        let line = format!(
+                            "{row} {:>7}  {}",
+                            format!("{matched}/{}", CASES.len()),
+                            mismatches.join(", ")
+                        );
+                        println!("{}", line.trim_end());
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::ExecutionBackend;
+
+    /// Run the case in this process, which is only safe for backends checking
+    /// every access and not prone to hang.
+    fn run_in_process(
+        case: &Case,
+        backend: &dyn ExecutionBackend,
+        config: &ExecutionConfig,
+    ) -> Outcome {
+        let mut lexer = Lexer::new(case.program.as_bytes(), Rc::new(Dialect::brainfuck()));
+        let program = match Parser::default().parse_program(&mut lexer) {
+            Ok(program) => program,
+            Err(e) => return Outcome::Error(vec![], e.to_string()),
+        };
+        let mut output = vec![];
+        match backend.execute(program, config, &mut &case.input[..], &mut output) {
+            Ok(()) => Outcome::Output(output),
+            Err(e) => Outcome::Error(output, e.to_string()),
+        }
+    }
+
+    #[test]
+    fn safe_backends_conform() {
+        let mut mismatches = vec![];
+        for backend in backends_named(&["interp".to_string(), "vm".to_string()]).unwrap() {
+            for (cell_width_index, (cell_width_name, cell_width)) in CELL_WIDTHS.iter().enumerate()
+            {
+                for (eof_index, (eof_name, eof)) in EOF_BEHAVIORS.iter().enumerate() {
+                    let config = ExecutionConfig {
+                        cell_width: *cell_width,
+                        eof: *eof,
+                        ..ExecutionConfig::default()
+                    };
+                    for case in &CASES {
+                        let (expected, error) = case.expected.output(cell_width_index, eof_index);
+                        let outcome = run_in_process(case, backend.as_ref(), &config);
+                        if !outcome.matches(expected, error) {
+                            mismatches.push(format!(
+                                "{} with {cell_width_name} bit cells and EOF {eof_name}: {} should print {}{}, but {}",
+                                backend.name(),
+                                case.name,
+                                escape(expected),
+                                if error { " and stop with an error" } else { "" },
+                                outcome.describe()
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        assert!(mismatches.is_empty(), "{}", mismatches.join("\n"));
+    }
+}
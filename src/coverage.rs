@@ -0,0 +1,361 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use crate::cli::{CoverageOptions, InputSource, ProgramSource};
+use crate::dialect::Dialect;
+use crate::{optimizer, read_source, Instruction, Intepreter, Lexer, Location, Parser, Span};
+use anyhow::{Context, Result};
+
+/// Which end of a loop a command is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bracket {
+    /// `[`, whose branches enter and skip the loop
+    Open,
+    /// `]`, whose branches repeat and exit the loop
+    Close,
+}
+
+/// How often a single command of the source has been executed over all runs.
+struct CommandCoverage {
+    span: Span,
+    hits: u64,
+    /// For loop brackets, how often each of the two branches has been taken
+    branches: Option<(Bracket, [u64; 2])>,
+}
+
+impl CommandCoverage {
+    fn line(&self) -> usize {
+        self.span.start.line
+    }
+}
+
+/// Execution counts of the optimized program, accumulated over all runs.
+struct Counts {
+    /// Number of times every instruction has been executed
+    hits: Vec<u64>,
+    /// Number of times every loop bracket has jumped
+    jumps: Vec<u64>,
+}
+
+impl Counts {
+    /// Run the program once with the given input, counting the executed
+    /// instructions even if it fails. Returns the number of executed
+    /// instructions.
+    fn run(&mut self, intepreter: &Intepreter, input: &InputSource) -> (u64, Result<()>) {
+        let mut steps: u64 = 0;
+        let result = input.open().and_then(|mut input| {
+            intepreter.run_observed(&mut input, &mut io::sink(), |_, instruction_ptr, thread| {
+                steps += 1;
+                self.hits[instruction_ptr] += 1;
+                if matches!(
+                    intepreter.program[instruction_ptr],
+                    Instruction::JmpForward(_) | Instruction::JmpBack(_)
+                ) && thread.instruction_ptr != instruction_ptr + 1
+                {
+                    self.jumps[instruction_ptr] += 1;
+                }
+                Ok(())
+            })
+        });
+        (steps, result)
+    }
+}
+
+/// Attribute the counts of the optimized program to the commands it has been
+/// folded from.
+fn attribute(
+    unoptimized: &[Instruction],
+    commands: &[Span],
+    spans: &[Span],
+    counts: &Counts,
+) -> Vec<CommandCoverage> {
+    unoptimized
+        .iter()
+        .zip(commands)
+        .map(|(instruction, &span)| {
+            // Folded instructions start at their first command
+            let position = spans.partition_point(|other| other.start <= span.start) - 1;
+            let hits = counts.hits[position];
+            let jumps = counts.jumps[position];
+            let branches = match instruction {
+                Instruction::JmpForward(_) => Some((Bracket::Open, [hits - jumps, jumps])),
+                Instruction::JmpBack(_) => Some((Bracket::Close, [jumps, hits - jumps])),
+                _ => None,
+            };
+            CommandCoverage {
+                span,
+                hits,
+                branches,
+            }
+        })
+        .collect()
+}
+
+/// Largest number of executions of the commands on every line, if it
+/// contains any, indexed by line number starting at 1.
+fn line_hits(commands: &[CommandCoverage]) -> Vec<Option<u64>> {
+    let last = commands.last().map_or(0, CommandCoverage::line);
+    let mut lines = vec![None; last + 1];
+    for command in commands {
+        let hits = &mut lines[command.line()];
+        *hits = Some(hits.unwrap_or(0).max(command.hits));
+    }
+    lines
+}
+
+fn ratio(covered: usize, total: usize) -> String {
+    if total == 0 {
+        return "0 of 0".to_string();
+    }
+    format!(
+        "{covered} of {total} ({:.1}%)",
+        covered as f64 * 100.0 / total as f64
+    )
+}
+
+/// Report in the lcov tracefile format, with a line record for every line
+/// containing commands and a branch record for both branches of every loop
+/// bracket.
+fn lcov(source: &ProgramSource, commands: &[CommandCoverage]) -> String {
+    let mut report = String::from("TN:\n");
+    let _ = match source {
+        ProgramSource::File(path) => writeln!(report, "SF:{path}"),
+        _ => writeln!(report, "SF:{source}"),
+    };
+
+    let mut branches = 0;
+    let mut taken = 0;
+    for (block, command) in commands
+        .iter()
+        .filter(|command| command.branches.is_some())
+        .enumerate()
+    {
+        let (_, counts) = command.branches.expect("loop brackets to have branches");
+        for (branch, count) in counts.iter().enumerate() {
+            branches += 1;
+            if *count > 0 {
+                taken += 1;
+            }
+            // Branches of brackets which never ran are not known to be taken
+            let _ = if command.hits == 0 {
+                writeln!(report, "BRDA:{},{block},{branch},-", command.line())
+            } else {
+                writeln!(report, "BRDA:{},{block},{branch},{count}", command.line())
+            };
+        }
+    }
+    let _ = writeln!(report, "BRF:{branches}\nBRH:{taken}");
+
+    let mut lines = 0;
+    let mut executed = 0;
+    for (line, hits) in line_hits(commands).into_iter().enumerate() {
+        if let Some(hits) = hits {
+            lines += 1;
+            if hits > 0 {
+                executed += 1;
+            }
+            let _ = writeln!(report, "DA:{line},{hits}");
+        }
+    }
+    let _ = writeln!(report, "LF:{lines}\nLH:{executed}\nend_of_record");
+    report
+}
+
+/// Print the source annotated with the execution count of every line, like
+/// gcov does, followed by a summary and the loops missing coverage.
+///
+/// Lines without commands are marked by `-` and lines of which nothing
+/// executed by `#####`. Commands which never executed on lines which did are
+/// marked by `^` below them.
+fn print_listing(
+    output: &mut dyn Write,
+    code: &[u8],
+    commands: &[CommandCoverage],
+) -> io::Result<()> {
+    let hits = line_hits(commands);
+    let mut lines = code.split(|&byte| byte == b'\n').collect::<Vec<_>>();
+    if lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    for (index, text) in lines.iter().enumerate() {
+        let number = index + 1;
+        let text = text.strip_suffix(b"\r").unwrap_or(text);
+        let count = hits.get(number).copied().flatten();
+        match count {
+            None => write!(output, "{:>9} | ", "-")?,
+            Some(0) => write!(output, "{:>9} | ", "#####")?,
+            Some(count) => write!(output, "{count:>9} | ")?,
+        }
+        output.write_all(text)?;
+        writeln!(output)?;
+
+        let missed = commands
+            .iter()
+            .filter(|command| command.line() == number && command.hits == 0)
+            .collect::<Vec<_>>();
+        if count == Some(0) || missed.is_empty() {
+            continue;
+        }
+        // Columns count bytes, the marks are placed below the first byte of
+        // every character and tabs kept to stay aligned
+        let mut marks = String::new();
+        for (offset, &byte) in text.iter().enumerate() {
+            if byte & 0xc0 == 0x80 {
+                continue;
+            }
+            let location = Location {
+                line: number,
+                column: offset + 1,
+            };
+            marks.push(
+                if missed.iter().any(|command| command.span.contains(location)) {
+                    '^'
+                } else if byte == b'\t' {
+                    '\t'
+                } else {
+                    ' '
+                },
+            );
+        }
+        writeln!(output, "{:>9} | {}", "", marks.trim_end())?;
+    }
+
+    let executed = commands.iter().filter(|command| command.hits > 0).count();
+    let branches = commands
+        .iter()
+        .filter_map(|command| command.branches)
+        .flat_map(|(_, counts)| counts)
+        .collect::<Vec<_>>();
+    let taken = branches.iter().filter(|&&count| count > 0).count();
+    writeln!(output)?;
+    writeln!(
+        output,
+        "Commands executed: {}",
+        ratio(executed, commands.len())
+    )?;
+    writeln!(output, "Branches taken: {}", ratio(taken, branches.len()))?;
+
+    for command in commands {
+        let Some((bracket, [first, second])) = command.branches else {
+            continue;
+        };
+        let Location { line, column } = command.span.start;
+        let note = match bracket {
+            Bracket::Open if command.hits == 0 => "loop is never reached",
+            Bracket::Open if first == 0 => "loop is never entered",
+            Bracket::Open if second == 0 => "loop is never skipped",
+            Bracket::Close if command.hits > 0 && first == 0 => "loop never runs more than once",
+            _ => continue,
+        };
+        writeln!(output, "{line}:{column}: {note}")?;
+    }
+    output.flush()
+}
+
+/// Run the program once for every input, recording which commands and which
+/// branches of loops executed. The output of the program is discarded.
+/// Prints the annotated source and writes the lcov report if requested.
+///
+/// Runs which fail are reported, their coverage up to the failure is kept.
+pub fn run(options: &CoverageOptions, dialect: &Rc<Dialect>, verbose: bool) -> Result<()> {
+    // Coverage refers to the source, so bytecode is not supported
+    let code = read_source(&options.source)?;
+    let mut parser = Parser::default();
+    let unoptimized = parser.parse_unoptimized(&mut Lexer::new(&code[..], Rc::clone(dialect)))?;
+    let (program, spans) = optimizer::optimize(&unoptimized, &parser.spans);
+
+    let mut counts = Counts {
+        hits: vec![0; program.len()],
+        jumps: vec![0; program.len()],
+    };
+    let stdin = [InputSource::Stdin];
+    let inputs = match options.inputs.as_slice() {
+        [] => &stdin[..],
+        inputs => inputs,
+    };
+    for (number, input) in inputs.iter().enumerate() {
+        let intepreter = Intepreter::new(program.clone(), &options.config);
+        let (steps, result) = counts.run(&intepreter, input);
+        match result {
+            Ok(()) if verbose => {
+                eprintln!("Run {} finished after {steps} instructions", number + 1)
+            }
+            Ok(()) => {}
+            Err(e) => {
+                eprintln!(
+                    "Run {} failed after {steps} instructions: {e:#}",
+                    number + 1
+                );
+            }
+        }
+    }
+
+    let commands = attribute(&unoptimized, &parser.spans, &spans, &counts);
+    if let Some(path) = &options.lcov {
+        fs::write(path, lcov(&options.source, &commands))
+            .with_context(|| format!("write lcov report to {path}"))?;
+        if verbose {
+            eprintln!("lcov report written to {path}");
+        }
+    }
+    print_listing(&mut io::stdout().lock(), &code, &commands).context("print annotated source")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::ExecutionConfig;
+
+    /// Loop entered once per input and a loop which is never entered.
+    const CODE: &str = ",[>+<-]\n[.]\n";
+
+    /// Write the lcov report of running `CODE` once for every input.
+    fn report(inputs: &[&str]) -> String {
+        let dialect = Rc::new(Dialect::load("brainfuck").unwrap());
+        let mut parser = Parser::default();
+        let unoptimized = parser
+            .parse_unoptimized(&mut Lexer::new(CODE.as_bytes(), dialect))
+            .unwrap();
+        let (program, spans) = optimizer::optimize(&unoptimized, &parser.spans);
+        let mut counts = Counts {
+            hits: vec![0; program.len()],
+            jumps: vec![0; program.len()],
+        };
+        for input in inputs {
+            let intepreter = Intepreter::new(program.clone(), &ExecutionConfig::default());
+            let (_, result) = counts.run(&intepreter, &InputSource::Text(input.to_string()));
+            result.unwrap();
+        }
+        let commands = attribute(&unoptimized, &parser.spans, &spans, &counts);
+        lcov(&ProgramSource::File("loops.b".to_string()), &commands)
+    }
+
+    #[test]
+    fn reports_partially_covered_branches() {
+        // The first loop is entered but never skipped, the second one never
+        // entered, so its closing bracket never runs
+        assert_eq!(
+            report(&["\x03"]),
+            "TN:\nSF:loops.b\n\
+             BRDA:1,0,0,1\nBRDA:1,0,1,0\nBRDA:1,1,0,2\nBRDA:1,1,1,1\n\
+             BRDA:2,2,0,0\nBRDA:2,2,1,1\nBRDA:2,3,0,-\nBRDA:2,3,1,-\n\
+             BRF:8\nBRH:4\n\
+             DA:1,3\nDA:2,1\nLF:2\nLH:2\nend_of_record\n"
+        );
+    }
+
+    #[test]
+    fn merges_counts_of_all_inputs() {
+        // Without input the cell stays 0 and the first loop is skipped
+        assert_eq!(
+            report(&["\x03", ""]),
+            "TN:\nSF:loops.b\n\
+             BRDA:1,0,0,1\nBRDA:1,0,1,1\nBRDA:1,1,0,2\nBRDA:1,1,1,1\n\
+             BRDA:2,2,0,0\nBRDA:2,2,1,2\nBRDA:2,3,0,-\nBRDA:2,3,1,-\n\
+             BRF:8\nBRH:5\n\
+             DA:1,3\nDA:2,2\nLF:2\nLH:2\nend_of_record\n"
+        );
+    }
+}
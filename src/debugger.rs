@@ -0,0 +1,498 @@
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Read, Write};
+use std::rc::Rc;
+use std::sync::atomic::Ordering;
+
+use crate::cli::{DebugOptions, ProgramSource};
+use crate::dialect::Dialect;
+use crate::repl::print_tape;
+use crate::{read_source, Instruction, Intepreter, Lexer, Location, Parser, Span};
+use anyhow::{anyhow, Context, Result};
+
+/// Default number of executed instructions kept to go back to. Older ones
+/// are forgotten, so long running programs do not exhaust the memory. Each
+/// `Change` takes 80 bytes, so a full history takes about 84 MB.
+pub const DEFAULT_HISTORY_LIMIT: usize = 1 << 20;
+
+const HELP: &str = "Commands:
+  step [count]             (s)  execute the next instruction(s)
+  continue                 (c)  run until a breakpoint or the end of the program
+  reverse-step [count]     (rs) undo the last executed instruction(s)
+  reverse-continue [cell]  (rc) run backwards to the last write of the cell, or
+                                to a breakpoint or the start if none is given
+  break <line>[:<column>]  (b)  stop before instructions at the location
+  delete <line>[:<column>] (d)  remove a breakpoint
+  tape                     (t)  show the cells around the pointer
+  output                   (o)  show the output of the program so far
+  help                     (h)  show this list
+  quit                     (q)  leave the debugger
+An empty line repeats the previous command.";
+
+/// Everything an executed instruction changed, to be able to undo it.
+struct Change {
+    instruction_ptr: usize,
+    addr: usize,
+    /// Cell written by the instruction together with its previous value
+    cell: Option<(usize, u32)>,
+    /// Procedure defined by the instruction together with its previous start
+    procedure: Option<(u32, Option<usize>)>,
+    input_position: usize,
+    output_length: usize,
+}
+
+/// A breakpoint on a line, or on a single column of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Breakpoint {
+    line: usize,
+    column: Option<usize>,
+}
+
+impl Breakpoint {
+    /// Whether the breakpoint is on the instruction. Instructions folded from
+    /// several commands match a breakpoint on any of them.
+    fn matches(&self, span: &Span) -> bool {
+        match self.column {
+            Some(column) => span.contains(Location {
+                line: self.line,
+                column,
+            }),
+            None => span.start.line == self.line,
+        }
+    }
+}
+
+impl std::str::FromStr for Breakpoint {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || anyhow!("Invalid location {s}, expected <line>[:<column>]");
+        let (line, column) = match s.split_once(':') {
+            Some((line, column)) => (line, Some(column.parse().map_err(|_| invalid())?)),
+            None => (s, None),
+        };
+        Ok(Self {
+            line: line.parse().map_err(|_| invalid())?,
+            column,
+        })
+    }
+}
+
+/// Single threaded execution of a program by the `Intepreter`, which is able
+/// to go back in time by undoing the executed instructions.
+struct Debugger {
+    intepreter: Intepreter,
+    /// Source span of every instruction of the program
+    spans: Vec<Span>,
+    input: Vec<u8>,
+    /// Number of input bytes consumed so far
+    input_position: usize,
+    output: Vec<u8>,
+    history: VecDeque<Change>,
+    /// Number of changes kept in the history
+    history_limit: usize,
+    breakpoints: Vec<Breakpoint>,
+}
+
+impl Debugger {
+    fn instruction_ptr(&self) -> usize {
+        self.intepreter.threads[0].instruction_ptr
+    }
+
+    fn finished(&self) -> bool {
+        self.instruction_ptr() >= self.intepreter.program.len()
+    }
+
+    fn at_breakpoint(&self) -> bool {
+        let span = &self.spans[self.instruction_ptr()];
+        self.breakpoints
+            .iter()
+            .any(|breakpoint| breakpoint.matches(span))
+    }
+
+    /// Execute the next instruction, recording how to undo it.
+    fn step(&mut self) -> Result<()> {
+        let intepreter = &self.intepreter;
+        let thread = &intepreter.threads[0];
+        let (instruction_ptr, addr) = (thread.instruction_ptr, thread.addr);
+        let mut change = Change {
+            instruction_ptr,
+            addr,
+            cell: None,
+            procedure: None,
+            input_position: self.input_position,
+            output_length: self.output.len(),
+        };
+        match intepreter.program[instruction_ptr] {
+            Instruction::Inc(_) | Instruction::Dec(_) | Instruction::Input(_) => {
+                change.cell = Some((addr, intepreter.cell(addr)));
+            }
+            Instruction::ProcedureStart(_) => {
+                let number = intepreter.cell(addr);
+                let previous = intepreter
+                    .procedures
+                    .read()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .get(&number)
+                    .copied();
+                change.procedure = Some((number, previous));
+            }
+            _ => {}
+        }
+
+        let mut thread = std::mem::take(&mut self.intepreter.threads[0]);
+        let mut input = &self.input[self.input_position..];
+        let result = self
+            .intepreter
+            .step(&mut thread, &mut input, &mut self.output);
+        self.input_position = self.input.len() - input.len();
+        self.intepreter.threads[0] = thread;
+        result?;
+
+        if self.history.len() == self.history_limit {
+            self.history.pop_front();
+        }
+        self.history.push_back(change);
+        Ok(())
+    }
+
+    /// Undo the last executed instruction, returning what it changed.
+    fn undo(&mut self) -> Option<Change> {
+        let change = self.history.pop_back()?;
+        let intepreter = &mut self.intepreter;
+        let thread = &mut intepreter.threads[0];
+        match intepreter.program[change.instruction_ptr] {
+            Instruction::Call => {
+                thread.call_stack.pop();
+            }
+            // Returning jumped to the address popped from the call stack
+            Instruction::ProcedureEnd(_) => thread.call_stack.push(thread.instruction_ptr),
+            _ => {}
+        }
+        thread.instruction_ptr = change.instruction_ptr;
+        thread.addr = change.addr;
+        if let Some((addr, value)) = change.cell {
+            intepreter.memory[addr].store(value, Ordering::Relaxed);
+        }
+        if let Some((number, previous)) = change.procedure {
+            let mut procedures = intepreter.procedures_mut();
+            match previous {
+                Some(start) => procedures.insert(number, start),
+                None => procedures.remove(&number),
+            };
+        }
+        self.input_position = change.input_position;
+        self.output.truncate(change.output_length);
+        Some(change)
+    }
+
+    fn step_forward(&mut self, count: usize) -> Result<()> {
+        for _ in 0..count {
+            if self.finished() {
+                break;
+            }
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    fn continue_forward(&mut self) -> Result<()> {
+        // Leave the breakpoint the program is currently stopped at
+        self.step_forward(1)?;
+        while !self.finished() && !self.at_breakpoint() {
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    fn step_back(&mut self, count: usize) {
+        for _ in 0..count {
+            if self.undo().is_none() {
+                println!("Reached the start of the recorded history");
+                return;
+            }
+        }
+    }
+
+    /// Go back until the instruction writing the given cell is the next one,
+    /// or to the previous breakpoint or the start if no cell is given.
+    fn continue_back(&mut self, cell: Option<usize>) {
+        while let Some(change) = self.history.back() {
+            let written = change.cell.filter(|&(addr, _)| Some(addr) == cell);
+            // The value the instruction wrote is only known before undoing it
+            let value = written.map(|(addr, _)| self.intepreter.cell(addr));
+            let change = self.undo().expect("history to contain the change");
+            if let (Some((addr, previous)), Some(value)) = (written, value) {
+                let Location { line, column } = self.spans[change.instruction_ptr].start;
+                println!(
+                    "Cell {addr} was last written at {line}:{column}, from {previous} to {value}"
+                );
+                return;
+            }
+            if cell.is_none() && self.at_breakpoint() {
+                return;
+            }
+        }
+        match cell {
+            Some(cell) => println!("Cell {cell} has not been written in the recorded history"),
+            None => println!("Reached the start of the recorded history"),
+        }
+    }
+
+    fn show_position(&self) {
+        if self.finished() {
+            println!("Program finished");
+        } else {
+            let instruction_ptr = self.instruction_ptr();
+            let Location { line, column } = self.spans[instruction_ptr].start;
+            println!(
+                "{line}:{column} {:?}",
+                self.intepreter.program[instruction_ptr]
+            );
+        }
+        print_tape(&self.intepreter);
+    }
+
+    /// Execute a single command, returning `false` once the debugger should
+    /// be left.
+    fn execute(&mut self, command: &str) -> Result<bool> {
+        let mut words = command.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let argument = words.next();
+        if let Some(extra) = words.next() {
+            return Err(anyhow!("Unexpected argument {extra}"));
+        }
+        let count = || -> Result<usize> {
+            argument.map_or(Ok(1), |count| {
+                count
+                    .parse()
+                    .with_context(|| format!("parse count {count}"))
+            })
+        };
+        let location = || -> Result<Breakpoint> {
+            argument
+                .ok_or_else(|| anyhow!("No location given, use {name} <line>[:<column>]"))?
+                .parse()
+        };
+
+        let output_length = self.output.len();
+        let result = match name {
+            "step" | "s" => self.step_forward(count()?),
+            "continue" | "c" => self.continue_forward(),
+            "reverse-step" | "rs" => {
+                self.step_back(count()?);
+                Ok(())
+            }
+            "reverse-continue" | "rc" => {
+                let cell = argument
+                    .map(|cell| cell.parse().with_context(|| format!("parse cell {cell}")))
+                    .transpose()?;
+                self.continue_back(cell);
+                Ok(())
+            }
+            "break" | "b" => {
+                let breakpoint = location()?;
+                if !self.breakpoints.contains(&breakpoint) {
+                    self.breakpoints.push(breakpoint);
+                }
+                return Ok(true);
+            }
+            "delete" | "d" => {
+                let breakpoint = location()?;
+                self.breakpoints.retain(|other| *other != breakpoint);
+                return Ok(true);
+            }
+            "tape" | "t" => {
+                print_tape(&self.intepreter);
+                return Ok(true);
+            }
+            "output" | "o" => {
+                println!("\"{}\"", self.output.escape_ascii());
+                return Ok(true);
+            }
+            "help" | "h" => {
+                println!("{HELP}");
+                return Ok(true);
+            }
+            "quit" | "q" => return Ok(false),
+            _ => return Err(anyhow!("Unknown command {name}, enter help for a list")),
+        };
+
+        if self.output.len() > output_length {
+            println!(
+                "output: \"{}\"",
+                self.output[output_length..].escape_ascii()
+            );
+        }
+        if let Err(e) = result {
+            println!("Error: {e}");
+        }
+        self.show_position();
+        Ok(true)
+    }
+}
+
+fn prompt() -> Result<()> {
+    print!("debug> ");
+    io::stdout().flush().context("flush prompt")
+}
+
+/// Debug the program interactively, reading commands from stdin. The input
+/// of the program is read completely in advance, and empty if none is given.
+pub fn run(options: &DebugOptions, dialect: &Rc<Dialect>) -> Result<()> {
+    if matches!(options.source, ProgramSource::Stdin) {
+        return Err(anyhow!(
+            "The program can not be read from stdin, which is used for commands"
+        ));
+    }
+    let code = read_source(&options.source)?;
+    let mut parser = Parser::default();
+    let program = parser.parse_program(&mut Lexer::new(&code[..], Rc::clone(dialect)))?;
+    if program
+        .iter()
+        .any(|instruction| matches!(instruction, Instruction::Fork))
+    {
+        return Err(anyhow!("Programs which fork can not be debugged"));
+    }
+    let mut input = vec![];
+    if let Some(source) = &options.input {
+        source
+            .open()?
+            .read_to_end(&mut input)
+            .context("read program input")?;
+    }
+
+    let mut debugger = Debugger {
+        intepreter: Intepreter::new(program, &options.config),
+        spans: parser.spans,
+        input,
+        input_position: 0,
+        output: vec![],
+        history: VecDeque::new(),
+        history_limit: options.history_limit,
+        breakpoints: vec![],
+    };
+
+    println!("rust_brain debugger, enter help for a list of commands");
+    debugger.show_position();
+    let stdin = io::stdin();
+    let mut line = String::new();
+    let mut previous = String::new();
+    loop {
+        prompt()?;
+        line.clear();
+        if stdin.lock().read_line(&mut line).context("read command")? == 0 {
+            println!();
+            return Ok(());
+        }
+        let command = match line.trim() {
+            "" => previous.clone(),
+            command => command.to_string(),
+        };
+        match debugger.execute(&command) {
+            Ok(true) => {}
+            Ok(false) => return Ok(()),
+            Err(e) => println!("Error: {e}"),
+        }
+        previous = command;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::ExecutionConfig;
+    use crate::snapshot::MachineState;
+
+    fn debugger(code: &str, input: &str) -> Debugger {
+        let dialect = Rc::new(Dialect::load("pbrain").unwrap());
+        let mut parser = Parser::default();
+        let program = parser
+            .parse_program(&mut Lexer::new(code.as_bytes(), dialect))
+            .unwrap();
+        let config = ExecutionConfig {
+            tape_size: 16,
+            ..ExecutionConfig::default()
+        };
+        Debugger {
+            intepreter: Intepreter::new(program, &config),
+            spans: parser.spans,
+            input: input.as_bytes().to_vec(),
+            input_position: 0,
+            output: vec![],
+            history: VecDeque::new(),
+            history_limit: DEFAULT_HISTORY_LIMIT,
+            breakpoints: vec![],
+        }
+    }
+
+    /// Everything undoing an instruction has to restore.
+    fn state(debugger: &Debugger) -> (MachineState, usize, Vec<u8>) {
+        (
+            debugger.intepreter.state(),
+            debugger.input_position,
+            debugger.output.clone(),
+        )
+    }
+
+    /// Run the program to the end, then undo every instruction, checking
+    /// that each undo restores the state before the instruction.
+    fn assert_round_trip(code: &str, input: &str) {
+        let mut debugger = debugger(code, input);
+        let mut states = vec![];
+        while !debugger.finished() {
+            states.push(state(&debugger));
+            debugger.step().unwrap();
+        }
+        while let Some(expected) = states.pop() {
+            assert!(debugger.undo().is_some());
+            assert_eq!(state(&debugger), expected);
+        }
+        assert!(debugger.undo().is_none());
+    }
+
+    #[test]
+    fn undoes_every_step() {
+        assert_round_trip(",+>,[-<+>]<.>+++.", "ab");
+        assert_round_trip(",,,.", "a");
+    }
+
+    #[test]
+    fn undoes_procedures() {
+        // The procedure is redefined, and calls to it return through the
+        // call stack
+        assert_round_trip("+(>+<)(>-<):>.", "");
+        assert_round_trip("(>+<)+[-:]>.", "");
+    }
+
+    #[test]
+    fn continues_back_to_last_write() {
+        let mut debugger = debugger("+>++<+>+", "");
+        debugger.continue_forward().unwrap();
+        debugger.continue_back(Some(0));
+        let Location { line, column } = debugger.spans[debugger.instruction_ptr()].start;
+        assert_eq!((line, column), (1, 6));
+        assert_eq!(debugger.intepreter.cell(0), 1);
+        assert_eq!(debugger.intepreter.cell(1), 2);
+
+        // A cell which was never written leads back to the start
+        debugger.continue_back(Some(5));
+        assert_eq!(debugger.instruction_ptr(), 0);
+        assert!(debugger.history.is_empty());
+    }
+
+    #[test]
+    fn forgets_oldest_changes() {
+        let mut debugger = debugger("+++>+++", "");
+        debugger.history_limit = 2;
+        debugger.continue_forward().unwrap();
+        assert_eq!(debugger.history.len(), 2);
+        debugger.step_back(3);
+        assert_eq!(debugger.intepreter.addr(), 0);
+        assert_eq!(debugger.intepreter.cell(0), 3);
+    }
+
+    #[test]
+    fn change_size_matches_documentation() {
+        assert_eq!(std::mem::size_of::<Change>(), 80);
+    }
+}
@@ -0,0 +1,372 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+
+/// The commands every dialect maps its keywords onto.
+const COMMANDS: &str = "<>+-.,[]";
+/// Commands of the pbrain extension, to define and call procedures.
+const PROCEDURE_COMMANDS: &str = "():";
+/// Command of the Brainfork extension, to fork the current thread.
+const FORK_COMMANDS: &str = "Y";
+
+/// A keyword of the surface syntax. Keywords without a command are recognized
+/// but have no effect, like the debugging extensions of Spoon.
+#[derive(Debug, Clone)]
+struct Keyword {
+    text: Vec<u8>,
+    command: Option<char>,
+}
+
+/// Surface syntax of a program, mapping keywords of the source onto the
+/// commands of brainfuck. Everything which is not a keyword is a comment.
+///
+/// Whitespace within a keyword matches any amount of whitespace in the
+/// source, so `Ook. Ook?` also matches `Ook.\nOok?`.
+#[derive(Debug, Clone)]
+pub struct Dialect {
+    pub name: String,
+    keywords: Vec<Keyword>,
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Self::brainfuck()
+    }
+}
+
+impl Dialect {
+    fn new(name: &str, keywords: Vec<(String, Option<char>)>) -> Self {
+        let mut keywords = keywords
+            .into_iter()
+            .map(|(text, command)| Keyword {
+                text: text.into_bytes(),
+                command,
+            })
+            .collect::<Vec<_>>();
+        // Prefer the longest keyword if one is the prefix of another
+        keywords.sort_by_key(|keyword| std::cmp::Reverse(keyword.text.len()));
+        Self {
+            name: name.to_string(),
+            keywords,
+        }
+    }
+
+    pub fn brainfuck() -> Self {
+        Self::new(
+            "brainfuck",
+            COMMANDS
+                .chars()
+                .map(|command| (command.to_string(), Some(command)))
+                .collect(),
+        )
+    }
+
+    /// Brainfuck extended by procedures: `(` and `)` enclose the definition
+    /// of the procedure numbered by the current cell, which is called by `:`.
+    pub fn pbrain() -> Self {
+        Self::new(
+            "pbrain",
+            COMMANDS
+                .chars()
+                .chain(PROCEDURE_COMMANDS.chars())
+                .map(|command| (command.to_string(), Some(command)))
+                .collect(),
+        )
+    }
+
+    /// Brainfuck extended by threads: `Y` forks the current thread. The cell
+    /// of the parent is cleared, while the child continues on the next cell
+    /// set to 1.
+    pub fn brainfork() -> Self {
+        Self::new(
+            "brainfork",
+            COMMANDS
+                .chars()
+                .chain(FORK_COMMANDS.chars())
+                .map(|command| (command.to_string(), Some(command)))
+                .collect(),
+        )
+    }
+
+    /// Dialects like Ook! and Blub, which spell every command as a pair of
+    /// the same word followed by different punctuation.
+    fn word_pairs(name: &str, word: &str) -> Self {
+        let pairs = [
+            (".?", '>'),
+            ("?.", '<'),
+            ("..", '+'),
+            ("!!", '-'),
+            ("!.", '.'),
+            (".!", ','),
+            ("!?", '['),
+            ("?!", ']'),
+        ];
+        let keywords = pairs
+            .iter()
+            .map(|(punctuation, command)| {
+                let mut punctuation = punctuation.chars();
+                let first = punctuation.next().expect("pair to have two characters");
+                let second = punctuation.next().expect("pair to have two characters");
+                (format!("{word}{first} {word}{second}"), Some(*command))
+            })
+            .collect();
+        Self::new(name, keywords)
+    }
+
+    pub fn ook() -> Self {
+        Self::word_pairs("ook", "Ook")
+    }
+
+    pub fn blub() -> Self {
+        Self::word_pairs("blub", "Blub")
+    }
+
+    /// Spoon encodes the commands as a prefix-free binary code. Codes have
+    /// to be written without anything in between their digits.
+    pub fn spoon() -> Self {
+        let codes = [
+            ("1", Some('+')),
+            ("000", Some('-')),
+            ("010", Some('>')),
+            ("011", Some('<')),
+            ("0011", Some(']')),
+            ("00100", Some('[')),
+            ("001010", Some('.')),
+            ("0010110", Some(',')),
+            // Debugging output and exit are not supported
+            ("00101110", None),
+            ("00101111", None),
+        ];
+        Self::new(
+            "spoon",
+            codes
+                .iter()
+                .map(|(code, command)| (code.to_string(), *command))
+                .collect(),
+        )
+    }
+
+    /// Load a built-in dialect by name or a user-defined one from a TOML file.
+    ///
+    /// A user-defined dialect maps commands onto their keywords:
+    ///
+    /// ```toml
+    /// name = "shouting"
+    ///
+    /// [commands]
+    /// "+" = "INC"
+    /// "-" = "DEC"
+    /// ```
+    pub fn load(name: &str) -> Result<Self> {
+        match name {
+            "brainfuck" | "bf" => Ok(Self::brainfuck()),
+            "pbrain" => Ok(Self::pbrain()),
+            "brainfork" => Ok(Self::brainfork()),
+            "ook" => Ok(Self::ook()),
+            "blub" => Ok(Self::blub()),
+            "spoon" => Ok(Self::spoon()),
+            _ if name.ends_with(".toml") || Path::new(name).is_file() => Self::from_file(name),
+            _ => Err(anyhow!(
+                "Unknown dialect {name}, expected brainfuck, pbrain, brainfork, ook, blub, spoon or a TOML file"
+            )),
+        }
+    }
+
+    fn from_file(path: &str) -> Result<Self> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("read dialect file {path}"))?;
+        let mut name = Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut keywords: Vec<(String, Option<char>)> = vec![];
+        for (table, key, value) in
+            parse_toml(&content).with_context(|| format!("parse dialect file {path}"))?
+        {
+            match (table.as_str(), key.as_str()) {
+                ("", "name") => name = value,
+                ("commands", command) => {
+                    let mut chars = command.chars();
+                    let command = match (chars.next(), chars.next()) {
+                        (Some(command), None)
+                            if COMMANDS.contains(command)
+                                || PROCEDURE_COMMANDS.contains(command)
+                                || FORK_COMMANDS.contains(command) =>
+                        {
+                            command
+                        }
+                        _ => return Err(anyhow!("Unknown command {command} in {path}")),
+                    };
+                    if value.trim().is_empty() {
+                        return Err(anyhow!("Empty keyword for {command} in {path}"));
+                    }
+                    if keywords.iter().any(|(keyword, _)| *keyword == value) {
+                        return Err(anyhow!("Keyword {value} is used twice in {path}"));
+                    }
+                    keywords.push((value, Some(command)));
+                }
+                _ => {
+                    let key = if table.is_empty() {
+                        key
+                    } else {
+                        format!("{table}.{key}")
+                    };
+                    return Err(anyhow!("Unknown key {key} in {path}"));
+                }
+            }
+        }
+
+        Ok(Self::new(&name, keywords))
+    }
+
+    /// The keyword spelling the command, if the dialect has one.
+    pub fn keyword(&self, command: char) -> Option<&str> {
+        self.keywords
+            .iter()
+            .find(|keyword| keyword.command == Some(command))
+            .and_then(|keyword| std::str::from_utf8(&keyword.text).ok())
+    }
+
+    /// Whether keywords written one after another have to be separated by a
+    /// space, which is the case unless all of them are single characters.
+    pub fn separates_keywords(&self) -> bool {
+        self.keywords.iter().any(|keyword| keyword.text.len() > 1)
+    }
+
+    /// Match a keyword at the start of the input, which is accessed by offset
+    /// through `peek`. Returns the command of the keyword and the number of
+    /// bytes it spans.
+    pub fn match_keyword<F>(&self, mut peek: F) -> Result<Option<(Option<char>, usize)>>
+    where
+        F: FnMut(usize) -> Result<Option<u8>>,
+    {
+        'keywords: for keyword in &self.keywords {
+            let mut offset = 0;
+            let mut expected = keyword.text.iter().peekable();
+            while let Some(&byte) = expected.next() {
+                if byte.is_ascii_whitespace() {
+                    while expected
+                        .next_if(|byte| byte.is_ascii_whitespace())
+                        .is_some()
+                    {}
+                    while peek(offset)?.is_some_and(|byte| byte.is_ascii_whitespace()) {
+                        offset += 1;
+                    }
+                    continue;
+                }
+                if peek(offset)? != Some(byte) {
+                    continue 'keywords;
+                }
+                offset += 1;
+            }
+            return Ok(Some((keyword.command, offset)));
+        }
+
+        Ok(None)
+    }
+}
+
+/// Parse the subset of TOML used by dialect files: tables and keys with
+/// string values. Returns the table, key and value of every entry.
+fn parse_toml(content: &str) -> Result<Vec<(String, String, String)>> {
+    let mut entries = vec![];
+    let mut table = String::new();
+    for (index, line) in content.lines().enumerate() {
+        let line_number = index + 1;
+        let mut rest = line.trim_start();
+        if rest.is_empty() || rest.starts_with('#') {
+            continue;
+        }
+
+        if let Some(header) = rest.strip_prefix('[') {
+            let (name, rest) = header
+                .split_once(']')
+                .ok_or_else(|| anyhow!("Unclosed table header in line {line_number}"))?;
+            expect_line_end(rest, line_number)?;
+            table = name.trim().to_string();
+            continue;
+        }
+
+        let key = if rest.starts_with(['"', '\'']) {
+            let (key, remaining) = parse_toml_string(rest, line_number)?;
+            rest = remaining;
+            key
+        } else {
+            let end = rest
+                .find(|char: char| !(char.is_ascii_alphanumeric() || char == '_' || char == '-'))
+                .unwrap_or(rest.len());
+            let (key, remaining) = rest.split_at(end);
+            rest = remaining;
+            key.to_string()
+        };
+        if key.is_empty() {
+            return Err(anyhow!("Missing key in line {line_number}"));
+        }
+
+        rest = rest
+            .trim_start()
+            .strip_prefix('=')
+            .ok_or_else(|| anyhow!("Expected = after key in line {line_number}"))?
+            .trim_start();
+        if !rest.starts_with(['"', '\'']) {
+            return Err(anyhow!("Expected string value in line {line_number}"));
+        }
+        let (value, rest) = parse_toml_string(rest, line_number)?;
+        expect_line_end(rest, line_number)?;
+
+        entries.push((table.clone(), key, value));
+    }
+
+    Ok(entries)
+}
+
+fn expect_line_end(rest: &str, line_number: usize) -> Result<()> {
+    let rest = rest.trim_start();
+    if rest.is_empty() || rest.starts_with('#') {
+        Ok(())
+    } else {
+        Err(anyhow!("Unexpected {rest} in line {line_number}"))
+    }
+}
+
+/// Parse a basic or literal string at the start of the input and return it
+/// together with the remaining input.
+fn parse_toml_string(input: &str, line_number: usize) -> Result<(String, &str)> {
+    let mut chars = input.char_indices();
+    let (_, quote) = chars.next().expect("string to start with a quote");
+    let mut value = String::new();
+    while let Some((index, char)) = chars.next() {
+        match char {
+            _ if char == quote => return Ok((value, &input[index + 1..])),
+            // Literal strings have no escapes
+            '\\' if quote == '"' => {
+                let escaped = match chars.next().map(|(_, char)| char) {
+                    Some('"') => '"',
+                    Some('\\') => '\\',
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some('r') => '\r',
+                    Some('u') => {
+                        let digits = chars
+                            .by_ref()
+                            .take(4)
+                            .map(|(_, char)| char)
+                            .collect::<String>();
+                        u32::from_str_radix(&digits, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| {
+                                anyhow!("Invalid unicode escape in line {line_number}")
+                            })?
+                    }
+                    _ => return Err(anyhow!("Invalid escape in line {line_number}")),
+                };
+                value.push(escaped);
+            }
+            _ => value.push(char),
+        }
+    }
+
+    Err(anyhow!("Unterminated string in line {line_number}"))
+}
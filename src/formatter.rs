@@ -0,0 +1,256 @@
+use std::io::Read;
+use std::rc::Rc;
+
+use crate::dialect::Dialect;
+use crate::{Lexer, SourceElement};
+use anyhow::{anyhow, Result};
+
+const INDENT: &str = "  ";
+
+pub struct FormatOptions {
+    /// Maximal width of a line of commands, including its indentation.
+    pub width: usize,
+    /// Whether comments are kept in the formatted output.
+    pub comments: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            width: 80,
+            comments: true,
+        }
+    }
+}
+
+struct Formatter<'a> {
+    /// Dialect the commands are written in
+    dialect: &'a Dialect,
+    options: FormatOptions,
+    output: String,
+    line: String,
+    depth: usize,
+    blank_line_pending: bool,
+}
+
+impl Formatter<'_> {
+    fn indent(&self) -> String {
+        INDENT.repeat(self.depth)
+    }
+
+    fn emit_line(&mut self, content: &str) {
+        if self.blank_line_pending && !self.output.is_empty() {
+            self.output.push('\n');
+        }
+        self.blank_line_pending = false;
+        self.output.push_str(&self.indent());
+        self.output.push_str(content);
+        self.output.push('\n');
+    }
+
+    fn flush_line(&mut self) {
+        if !self.line.is_empty() {
+            let line = std::mem::take(&mut self.line);
+            self.emit_line(&line);
+        }
+    }
+
+    fn push_command(&mut self, command: char) -> Result<()> {
+        let keyword = keyword(self.dialect, command)?;
+        match command {
+            '[' | '(' => {
+                self.flush_line();
+                self.emit_line(keyword);
+                self.depth += 1;
+            }
+            ']' | ')' => {
+                self.flush_line();
+                self.depth = self.depth.saturating_sub(1);
+                self.emit_line(keyword);
+            }
+            _ => {
+                // Wrap long runs of commands
+                if self.depth * INDENT.len() + self.line.len() >= self.options.width {
+                    self.flush_line();
+                }
+                if !self.line.is_empty() && self.dialect.separates_keywords() {
+                    self.line.push(' ');
+                }
+                self.line.push_str(keyword);
+            }
+        }
+        Ok(())
+    }
+
+    fn push_comment(&mut self, comment: &str) {
+        if !self.options.comments {
+            return;
+        }
+
+        let mut segments = comment.split('\n');
+        // Text on the same source line as the preceding commands stays
+        // attached to them as a trailing comment.
+        let first = segments.next().unwrap_or_default().trim();
+        if !first.is_empty() {
+            if self.line.is_empty() {
+                self.emit_line(first);
+            } else {
+                self.line.push(' ');
+                self.line.push_str(first);
+                self.flush_line();
+            }
+        }
+
+        let mut blank_lines = 0;
+        for segment in segments {
+            self.flush_line();
+            let segment = segment.trim();
+            if segment.is_empty() {
+                blank_lines += 1;
+                continue;
+            }
+            // Consecutive blank lines are collapsed into a single one
+            if blank_lines > 0 {
+                self.blank_line_pending = true;
+            }
+            blank_lines = 0;
+            self.emit_line(segment);
+        }
+        if blank_lines > 1 {
+            self.blank_line_pending = true;
+        }
+    }
+}
+
+fn keyword(dialect: &Dialect, command: char) -> Result<&str> {
+    dialect
+        .keyword(command)
+        .ok_or_else(|| anyhow!("Dialect {} has no keyword for {command}", dialect.name))
+}
+
+/// Ensure the output has the same commands as the source, which might not be
+/// the case if comments contain keywords of the dialect the output is written
+/// in.
+fn check_commands(output: &str, dialect: &Rc<Dialect>, commands: &[char]) -> Result<()> {
+    let mut lexer = Lexer::new(output.as_bytes(), Rc::clone(dialect));
+    let mut written = vec![];
+    while let Some(token) = lexer.chop()? {
+        written.push(token.char);
+    }
+    if written != commands {
+        return Err(anyhow!(
+            "Formatting would change the commands of the program, as comments contain keywords of {}; use --strip-comments",
+            dialect.name
+        ));
+    }
+    Ok(())
+}
+
+/// Render the source read by the lexer in canonical formatting, writing the
+/// commands in the given dialect.
+///
+/// Every loop and procedure opens and closes on a line of its own, with its
+/// body indented by the nesting depth. Runs of other commands are kept on a
+/// single line, which is wrapped once it exceeds the configured width.
+/// Comments are kept on lines of their own, unless they trail commands on the
+/// same source line.
+pub fn format_source<R: Read>(
+    lexer: &mut Lexer<R>,
+    dialect: &Rc<Dialect>,
+    options: FormatOptions,
+) -> Result<String> {
+    let mut formatter = Formatter {
+        dialect,
+        options,
+        output: String::new(),
+        line: String::new(),
+        depth: 0,
+        blank_line_pending: false,
+    };
+
+    let mut commands = vec![];
+    while let Some(element) = lexer.chop_element()? {
+        match element {
+            SourceElement::Command(token) => {
+                formatter.push_command(token.char)?;
+                commands.push(token.char);
+            }
+            SourceElement::Comment(comment) => formatter.push_comment(&comment),
+        }
+    }
+    formatter.flush_line();
+
+    check_commands(&formatter.output, dialect, &commands)?;
+    Ok(formatter.output)
+}
+
+/// Strip everything but the commands from the source read by the lexer,
+/// writing them in the given dialect.
+pub fn minify_source<R: Read>(lexer: &mut Lexer<R>, dialect: &Dialect) -> Result<String> {
+    let mut output = String::new();
+    while let Some(token) = lexer.chop()? {
+        if !output.is_empty() && dialect.separates_keywords() {
+            output.push(' ');
+        }
+        output.push_str(keyword(dialect, token.char)?);
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dialect(name: &str) -> Rc<Dialect> {
+        Rc::new(Dialect::load(name).unwrap())
+    }
+
+    fn format(code: &str, from: &str, to: &str) -> Result<String> {
+        let mut lexer = Lexer::new(code.as_bytes(), dialect(from));
+        format_source(&mut lexer, &dialect(to), FormatOptions::default())
+    }
+
+    fn minify(code: &str, from: &str, to: &str) -> Result<String> {
+        minify_source(
+            &mut Lexer::new(code.as_bytes(), dialect(from)),
+            &dialect(to),
+        )
+    }
+
+    #[test]
+    fn commands_are_written_in_the_dialect() {
+        let ook = "Ook. Ook. Ook! Ook? Ook. Ook? Ook. Ook. Ook? Ook. Ook! Ook! Ook? Ook!";
+        assert_eq!(
+            format(ook, "ook", "ook").unwrap(),
+            "Ook. Ook.\nOok! Ook?\n  Ook. Ook? Ook. Ook. Ook? Ook. Ook! Ook!\nOok? Ook!\n"
+        );
+        assert_eq!(minify(ook, "ook", "ook").unwrap(), ook);
+        assert_eq!(minify(ook, "ook", "brainfuck").unwrap(), "+[>+<-]");
+        assert_eq!(
+            minify("+[>+<-]", "brainfuck", "blub").unwrap(),
+            ook.replace("Ook", "Blub")
+        );
+    }
+
+    #[test]
+    fn missing_keywords_are_rejected() {
+        assert!(format("(+):", "pbrain", "brainfuck").is_err());
+        assert!(minify("(+):", "pbrain", "ook").is_err());
+    }
+
+    #[test]
+    fn comments_turning_into_commands_are_rejected() {
+        let ook = "Ook. Ook. note Blub. Blub.";
+        assert!(format(ook, "ook", "blub").is_err());
+
+        let mut lexer = Lexer::new(ook.as_bytes(), dialect("ook"));
+        let options = FormatOptions {
+            comments: false,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            format_source(&mut lexer, &dialect("blub"), options).unwrap(),
+            "Blub. Blub.\n"
+        );
+    }
+}
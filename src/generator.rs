@@ -0,0 +1,162 @@
+use std::rc::Rc;
+
+use crate::backend::ExecutionConfig;
+use crate::dialect::Dialect;
+use crate::{Intepreter, Lexer, Parser};
+use anyhow::{anyhow, Context, Result};
+
+/// Number of cells holding previously printed bytes, which later bytes are
+/// derived from. They follow the loop counter in cell 0.
+const REGISTERS: usize = 4;
+/// Largest factor tried for the counter of a multiplication loop.
+const MAX_FACTOR: usize = 16;
+/// Maximal width of a line of the generated program.
+const WIDTH: usize = 80;
+
+fn moves(from: usize, to: usize) -> String {
+    if to > from {
+        ">".repeat(to - from)
+    } else {
+        "<".repeat(from - to)
+    }
+}
+
+fn adjust(delta: isize) -> String {
+    if delta > 0 {
+        "+".repeat(delta as usize)
+    } else {
+        "-".repeat(delta.unsigned_abs())
+    }
+}
+
+/// Shortest code changing the register from `from` to `to`, either by a run of
+/// `+` or `-` or by a multiplication loop on the counter. Starts at the
+/// pointer and ends on the register.
+fn change_register(pointer: usize, register: usize, from: u8, to: u8) -> String {
+    let delta = to as isize - from as isize;
+    let mut best = moves(pointer, register) + &adjust(delta);
+
+    let sign = delta.signum();
+    let distance = delta.unsigned_abs();
+    for factor in 2..=MAX_FACTOR.min(distance) {
+        let step = (distance + factor / 2) / factor;
+        let remainder = distance as isize - (factor * step) as isize;
+        let code = format!(
+            "{}{}[{}{}{}-]{}{}",
+            moves(pointer, 0),
+            "+".repeat(factor),
+            moves(0, register),
+            adjust(sign * step as isize),
+            moves(register, 0),
+            moves(0, register),
+            adjust(sign * remainder),
+        );
+        if code.len() < best.len() {
+            best = code;
+        }
+    }
+
+    best
+}
+
+/// Generate a brainfuck program printing the given bytes.
+///
+/// Every byte is printed from the register which reaches it with the least
+/// code, including the moves of the pointer. Cells never wrap around, so the
+/// program works for every cell width.
+fn generate_unchecked(text: &[u8]) -> String {
+    let mut code = String::new();
+    let mut pointer = 0;
+    let mut registers = [0; REGISTERS];
+    for &byte in text {
+        let (register, change) = (0..REGISTERS)
+            .map(|index| {
+                let change = change_register(pointer, index + 1, registers[index], byte);
+                (index, change)
+            })
+            .min_by_key(|(_, change)| change.len())
+            .expect("at least one register");
+        code.push_str(&change);
+        code.push('.');
+        registers[register] = byte;
+        pointer = register + 1;
+    }
+
+    code.as_bytes()
+        .chunks(WIDTH)
+        .map(|line| String::from_utf8_lossy(line).into_owned())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Generate a brainfuck program printing the given bytes and make sure it
+/// does by running it.
+pub fn generate(text: &[u8]) -> Result<String> {
+    let code = generate_unchecked(text);
+
+    let mut lexer = Lexer::new(code.as_bytes(), Rc::new(Dialect::brainfuck()));
+    let program = Parser::default()
+        .parse_program(&mut lexer)
+        .context("parse generated program")?;
+    let mut intepreter = Intepreter::new(program, &ExecutionConfig::default());
+    let mut output = vec![];
+    intepreter
+        .run(&mut std::io::empty(), &mut output)
+        .context("run generated program")?;
+    if output != text {
+        return Err(anyhow!(
+            "Generated program prints {:?} instead of {:?}",
+            String::from_utf8_lossy(&output),
+            String::from_utf8_lossy(text)
+        ));
+    }
+
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::CellWidth;
+
+    /// Output of the generated program when run with the given cell width.
+    fn run(code: &str, cell_width: CellWidth) -> Vec<u8> {
+        let mut lexer = Lexer::new(code.as_bytes(), Rc::new(Dialect::brainfuck()));
+        let program = Parser::default().parse_program(&mut lexer).unwrap();
+        let config = ExecutionConfig {
+            cell_width,
+            ..ExecutionConfig::default()
+        };
+        let mut output = vec![];
+        Intepreter::new(program, &config)
+            .run(&mut std::io::empty(), &mut output)
+            .unwrap();
+        output
+    }
+
+    #[test]
+    fn generated_programs_print_the_text() {
+        let texts: [&[u8]; 6] = [
+            b"",
+            b"Hello World!\n",
+            &[b'a'; 100],
+            b"\x00\x00\x00",
+            b"\xff\xff\x00\xff",
+            &[0x00, 0xff, 0x7f, 0x80, 0x01, 0xfe],
+        ];
+        for text in texts {
+            let code = generate_unchecked(text);
+            for cell_width in [CellWidth::U8, CellWidth::U16, CellWidth::U32] {
+                assert_eq!(run(&code, cell_width), text, "{code}");
+            }
+            assert_eq!(generate(text).unwrap(), code);
+        }
+    }
+
+    #[test]
+    fn lines_are_wrapped() {
+        let code = generate_unchecked(&[b'z'; 200]);
+        assert!(code.lines().count() > 1);
+        assert!(code.lines().all(|line| line.len() <= WIDTH));
+    }
+}
@@ -0,0 +1,398 @@
+use std::io::{Read, Write};
+
+use crate::backend::{
+    read_byte, CellWidth, CompiledProgram, EofBehavior, ExecutionBackend, ExecutionConfig,
+    JitBackend, RunState, DEFAULT_TAPE_SIZE,
+};
+use crate::code_cache::CodeCache;
+use crate::snapshot::MachineState;
+use crate::{Instruction, Program, Thread};
+use anyhow::{anyhow, Context, Result};
+
+#[cfg(target_arch = "aarch64")]
+use crate::aarch64_jit::Aarch64Assembler as HostAssembler;
+#[cfg(target_arch = "x86_64")]
+use crate::x86_jit::X86Assembler as HostAssembler;
+
+type JitFn = extern "C" fn(*mut u8, *mut usize, *mut JitContext);
+type JitIoFn = extern "C" fn(*mut JitContext, *mut u8);
+
+/// Number of procedures addressable by an 8 bit cell.
+const PROCEDURE_COUNT: usize = 256;
+
+/// State passed to the jitted code, to call back into rust for I/O.
+#[repr(C)]
+pub struct JitContext<'a> {
+    // The following fields are accessed by the generated code and therefore
+    // need to stay at the beginning of the struct.
+    output_fn: JitIoFn,
+    input_fn: JitIoFn,
+    procedures: *mut usize,
+    stack_pointer: usize,
+    undefined_procedure_fn: JitIoFn,
+    loop_budget: usize,
+    resume_at: usize,
+    input: &'a mut dyn Read,
+    output: &'a mut dyn Write,
+    eof: EofBehavior,
+    error: Option<anyhow::Error>,
+}
+
+extern "C" fn jit_output(context: *mut JitContext, cell: *mut u8) {
+    let context = unsafe { &mut *context };
+    if context.error.is_some() {
+        return;
+    }
+    let value = unsafe { *cell };
+    if let Err(e) = context
+        .output
+        .write_all(&[value])
+        .and_then(|_| context.output.flush())
+    {
+        context.error = Some(anyhow::Error::new(e).context("writing data to output"));
+    }
+}
+
+extern "C" fn jit_input(context: *mut JitContext, cell: *mut u8) {
+    let context = unsafe { &mut *context };
+    if context.error.is_some() {
+        return;
+    }
+    match read_byte(context.input) {
+        Ok(Some(value)) => unsafe { *cell = value },
+        Ok(None) => unsafe { *cell = context.eof.apply((*cell).into(), CellWidth::U8) as u8 },
+        Err(e) => context.error = Some(e),
+    }
+}
+
+extern "C" fn jit_undefined_procedure(context: *mut JitContext, cell: *mut u8) {
+    let context = unsafe { &mut *context };
+    if context.error.is_some() {
+        return;
+    }
+    let number = unsafe { *cell };
+    context.error = Some(anyhow!("Call of undefined procedure {number}"));
+}
+
+pub struct JitCompiler<B: JitBackend> {
+    backend: B,
+    program: Program,
+    cell_width: CellWidth,
+    memory: Vec<u8>,
+    addr: usize,
+    snapshots: bool,
+    /// Index of the `]` the program has been paused at, zero if it has not
+    /// been started or paused
+    resume_at: usize,
+    cache: Option<CodeCache>,
+    executable: Option<ExecutableCode>,
+}
+
+impl<B: JitBackend> JitCompiler<B> {
+    pub fn new(program: Program, backend: B, config: &ExecutionConfig) -> Self {
+        Self {
+            backend,
+            program,
+            cell_width: config.cell_width,
+            memory: vec![0; config.tape_size],
+            addr: 0,
+            snapshots: config.snapshots,
+            resume_at: 0,
+            cache: config.cache_dir.clone().map(CodeCache::new),
+            executable: None,
+        }
+    }
+
+    pub fn compile(&mut self) -> Result<()> {
+        let architecture = self.backend.architecture();
+        let key = match self.cache {
+            Some(_) => Some(CodeCache::key(
+                &self.program,
+                architecture,
+                self.cell_width,
+                self.snapshots,
+            )?),
+            None => None,
+        };
+
+        if let (Some(cache), Some(key)) = (&self.cache, &key) {
+            // Artifacts are verified and copied before they are mapped as
+            // executable, so a file changed afterwards is never executed
+            if let Some(code) = cache.load(key, architecture) {
+                self.executable = Some(ExecutableCode::from_code(&code)?);
+                return Ok(());
+            }
+        }
+
+        self.backend.compile(&self.program, self.snapshots)?;
+        if let (Some(cache), Some(key)) = (&self.cache, &key) {
+            cache.store(key, architecture, self.backend.code())?;
+        }
+        self.executable = Some(ExecutableCode::from_code(self.backend.code())?);
+
+        Ok(())
+    }
+
+    /// Run the program until it ends, or, if compiled with snapshots enabled,
+    /// is about to start another loop iteration after the given number of
+    /// them.
+    pub fn run(
+        &mut self,
+        config: &ExecutionConfig,
+        iterations: u64,
+        input: &mut dyn Read,
+        output: &mut dyn Write,
+    ) -> Result<RunState> {
+        let mut procedures = [0; PROCEDURE_COUNT];
+        let mut context = JitContext {
+            output_fn: jit_output,
+            input_fn: jit_input,
+            procedures: procedures.as_mut_ptr(),
+            stack_pointer: 0,
+            undefined_procedure_fn: jit_undefined_procedure,
+            loop_budget: iterations.try_into().unwrap_or(usize::MAX),
+            resume_at: self.resume_at,
+            input,
+            output,
+            eof: config.eof,
+            error: None,
+        };
+        let jit_fn = self
+            .executable
+            .as_ref()
+            .expect("program to be compiled before running it")
+            .function();
+        jit_fn(
+            self.memory.as_mut_ptr(),
+            &mut self.addr as *mut usize,
+            &mut context as *mut JitContext,
+        );
+
+        self.resume_at = context.resume_at;
+        match context.error.take() {
+            Some(e) => Err(e),
+            None if self.resume_at != 0 => Ok(RunState::Paused),
+            None => Ok(RunState::Finished),
+        }
+    }
+
+    /// State of the paused program, which only ever has a single thread.
+    pub fn state(&self) -> MachineState {
+        MachineState {
+            cells: self.memory.iter().map(|&cell| cell.into()).collect(),
+            threads: vec![Thread {
+                addr: self.addr,
+                instruction_ptr: self.resume_at,
+                call_stack: vec![],
+            }],
+            procedures: vec![],
+        }
+    }
+
+    /// Continue from the given state, which has to be paused at the end of a
+    /// loop, once the program is run.
+    pub fn restore(&mut self, state: MachineState) -> Result<()> {
+        let [thread] = &state.threads[..] else {
+            return Err(anyhow!("The JIT can only continue a single thread"));
+        };
+        if !thread.call_stack.is_empty() || !state.procedures.is_empty() {
+            return Err(anyhow!(
+                "The JIT can not continue programs using procedures"
+            ));
+        }
+        let instruction_ptr = thread.instruction_ptr;
+        if instruction_ptr != 0
+            && !matches!(
+                self.program.get(instruction_ptr),
+                Some(Instruction::JmpBack(_))
+            )
+        {
+            return Err(anyhow!("The JIT can only continue at the end of a loop"));
+        }
+        self.memory = state.cells.iter().map(|&cell| cell as u8).collect();
+        self.addr = thread.addr;
+        self.resume_at = instruction_ptr;
+        Ok(())
+    }
+}
+
+/// The JIT for the architecture of the host.
+pub struct NativeJitBackend;
+
+impl ExecutionBackend for NativeJitBackend {
+    fn name(&self) -> &'static str {
+        "jit"
+    }
+
+    fn check(&self, program: &Program, config: &ExecutionConfig) -> Result<()> {
+        if config.cell_width != CellWidth::U8 {
+            return Err(anyhow!("only 8 bit cells are supported"));
+        }
+        // The generated code does not check the pointer to stay on the tape.
+        // Smaller tapes are meant to catch programs leaving them, which is
+        // left to the backends checking every move.
+        if config.tape_size < DEFAULT_TAPE_SIZE {
+            return Err(anyhow!(
+                "tapes smaller than the default of {DEFAULT_TAPE_SIZE} cells are not supported"
+            ));
+        }
+        if program
+            .iter()
+            .any(|instruction| matches!(instruction, Instruction::Fork))
+        {
+            return Err(anyhow!("forking threads is not supported"));
+        }
+        if config.snapshots
+            && program
+                .iter()
+                .any(|instruction| matches!(instruction, Instruction::ProcedureStart(_)))
+        {
+            return Err(anyhow!(
+                "snapshots of programs with procedures are not supported"
+            ));
+        }
+        Ok(())
+    }
+
+    fn compile(
+        &self,
+        program: Program,
+        config: &ExecutionConfig,
+    ) -> Result<Box<dyn CompiledProgram>> {
+        let mut jit_compiler = JitCompiler::new(program, HostAssembler::default(), config);
+        jit_compiler.compile()?;
+        Ok(Box::new(NativeJitProgram {
+            jit_compiler,
+            config: config.clone(),
+        }))
+    }
+}
+
+/// A program compiled by the JIT for the architecture of the host.
+struct NativeJitProgram {
+    jit_compiler: JitCompiler<HostAssembler>,
+    config: ExecutionConfig,
+}
+
+impl CompiledProgram for NativeJitProgram {
+    fn run(&mut self, input: &mut dyn Read, output: &mut dyn Write) -> Result<()> {
+        self.jit_compiler
+            .run(&self.config, u64::MAX, input, output)
+            .map(|_| ())
+    }
+
+    fn run_loops(
+        &mut self,
+        iterations: u64,
+        input: &mut dyn Read,
+        output: &mut dyn Write,
+    ) -> Result<RunState> {
+        self.jit_compiler
+            .run(&self.config, iterations, input, output)
+    }
+
+    fn state(&self) -> Result<MachineState> {
+        Ok(self.jit_compiler.state())
+    }
+
+    fn restore(&mut self, state: MachineState) -> Result<()> {
+        self.jit_compiler.restore(state)
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+extern "C" {
+    // Provided by libgcc/compiler-rt. Needed as aarch64 does not keep the
+    // instruction cache coherent with the data cache.
+    fn __clear_cache(start: *mut libc::c_char, end: *mut libc::c_char);
+}
+
+/// Machine code mapped into executable memory, which is unmapped again once
+/// dropped.
+struct ExecutableCode {
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+impl ExecutableCode {
+    fn from_code(code: &[u8]) -> Result<Self> {
+        unsafe {
+            // 1. mmap to map read/write anonymous memory of size code
+            let ptr = libc::mmap(
+                std::ptr::null_mut(),
+                code.len(),
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_ANON | libc::MAP_PRIVATE,
+                -1,
+                0,
+            );
+            if ptr == libc::MAP_FAILED {
+                return Err(std::io::Error::last_os_error())
+                    .context("memory mapping region for executable code");
+            }
+            let executable = Self {
+                ptr,
+                len: code.len(),
+            };
+            // 2. copy code to memory
+            std::ptr::copy_nonoverlapping(code.as_ptr(), ptr as *mut u8, code.len());
+            // 3. mprotect the memory to read/exec
+            let result = libc::mprotect(ptr, code.len(), libc::PROT_EXEC | libc::PROT_READ);
+            if result == -1 {
+                return Err(std::io::Error::last_os_error())
+                    .context("making memory mapped region executable");
+            }
+            executable.clear_instruction_cache();
+            Ok(executable)
+        }
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    fn clear_instruction_cache(&self) {
+        // invalidate stale instruction cache lines for the region
+        unsafe {
+            __clear_cache(
+                self.ptr as *mut libc::c_char,
+                (self.ptr as *mut libc::c_char).add(self.len),
+            );
+        }
+    }
+
+    #[cfg(not(target_arch = "aarch64"))]
+    fn clear_instruction_cache(&self) {}
+
+    fn function(&self) -> JitFn {
+        // reinterpret_cast memory pointer to function signature
+        unsafe { std::mem::transmute(self.ptr) }
+    }
+}
+
+impl Drop for ExecutableCode {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr, self.len);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::select_backend;
+
+    #[test]
+    fn small_tapes_run_on_checked_backends() {
+        let program = vec![Instruction::Inc(1)];
+        let config = ExecutionConfig {
+            tape_size: 10,
+            ..ExecutionConfig::default()
+        };
+        assert!(NativeJitBackend.check(&program, &config).is_err());
+        let backend = select_backend(Some("jit"), &program, &config).unwrap();
+        assert_ne!(backend.name(), "jit");
+
+        assert!(NativeJitBackend
+            .check(&program, &ExecutionConfig::default())
+            .is_ok());
+    }
+}
@@ -0,0 +1,306 @@
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+
+/// Minimal JSON document model, sufficient for the protocols spoken by
+/// rust_brain.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Create an object from the given key value pairs.
+    pub fn object<const N: usize>(entries: [(&str, JsonValue); N]) -> Self {
+        JsonValue::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(entries) => entries
+                .iter()
+                .find(|(candidate, _)| candidate == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Follow the given path of object keys.
+    pub fn pointer(&self, path: &[&str]) -> Option<&JsonValue> {
+        path.iter().try_fold(self, |value, key| value.get(key))
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            JsonValue::Number(value) if *value >= 0.0 && value.fract() == 0.0 => {
+                Some(*value as usize)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut parser = JsonParser {
+            input: input.as_bytes(),
+            position: 0,
+        };
+        let value = parser.parse_value()?;
+        parser.skip_whitespace();
+        if parser.position != parser.input.len() {
+            return Err(anyhow!(
+                "unexpected trailing data in JSON at {}",
+                parser.position
+            ));
+        }
+        Ok(value)
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(value: &str) -> Self {
+        JsonValue::String(value.to_string())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(value: String) -> Self {
+        JsonValue::String(value)
+    }
+}
+
+impl From<usize> for JsonValue {
+    fn from(value: usize) -> Self {
+        JsonValue::Number(value as f64)
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(value: bool) -> Self {
+        JsonValue::Bool(value)
+    }
+}
+
+pub fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for char in value.chars() {
+        match char {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            char if (char as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", char as u32)),
+            char => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonValue::Null => write!(f, "null"),
+            JsonValue::Bool(value) => write!(f, "{value}"),
+            JsonValue::Number(value) if value.fract() == 0.0 && value.abs() < 1e15 => {
+                write!(f, "{}", *value as i64)
+            }
+            JsonValue::Number(value) => write!(f, "{value}"),
+            JsonValue::String(value) => write!(f, "\"{}\"", escape(value)),
+            JsonValue::Array(values) => {
+                write!(f, "[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                write!(f, "]")
+            }
+            JsonValue::Object(entries) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "\"{}\":{value}", escape(key))?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+struct JsonParser<'a> {
+    input: &'a [u8],
+    position: usize,
+}
+
+impl JsonParser<'_> {
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.input.get(self.position) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: &[u8]) -> Result<()> {
+        if !self.input[self.position..].starts_with(expected) {
+            return Err(anyhow!(
+                "expected {} in JSON at {}",
+                String::from_utf8_lossy(expected),
+                self.position
+            ));
+        }
+        self.position += expected.len();
+        Ok(())
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue> {
+        self.skip_whitespace();
+        match self.input.get(self.position) {
+            Some(b'n') => self.expect(b"null").map(|_| JsonValue::Null),
+            Some(b't') => self.expect(b"true").map(|_| JsonValue::Bool(true)),
+            Some(b'f') => self.expect(b"false").map(|_| JsonValue::Bool(false)),
+            Some(b'"') => self.parse_string().map(JsonValue::String),
+            Some(b'[') => self.parse_array(),
+            Some(b'{') => self.parse_object(),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            _ => Err(anyhow!("unexpected input in JSON at {}", self.position)),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<JsonValue> {
+        let start = self.position;
+        while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') =
+            self.input.get(self.position)
+        {
+            self.position += 1;
+        }
+        let number = std::str::from_utf8(&self.input[start..self.position])?;
+        Ok(JsonValue::Number(number.parse().map_err(|_| {
+            anyhow!("invalid number {number} in JSON at {start}")
+        })?))
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32> {
+        let digits = self
+            .input
+            .get(self.position..self.position + 4)
+            .ok_or_else(|| anyhow!("unexpected end of JSON in unicode escape"))?;
+        self.position += 4;
+        u32::from_str_radix(std::str::from_utf8(digits)?, 16)
+            .map_err(|_| anyhow!("invalid unicode escape in JSON at {}", self.position))
+    }
+
+    fn parse_string(&mut self) -> Result<String> {
+        self.expect(b"\"")?;
+        let mut value = vec![];
+        loop {
+            let byte = *self
+                .input
+                .get(self.position)
+                .ok_or_else(|| anyhow!("unterminated string in JSON"))?;
+            self.position += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escape = *self
+                        .input
+                        .get(self.position)
+                        .ok_or_else(|| anyhow!("unterminated string in JSON"))?;
+                    self.position += 1;
+                    let char = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let mut code = self.parse_hex4()?;
+                            // Characters outside the BMP are encoded as
+                            // surrogate pairs
+                            if (0xD800..0xDC00).contains(&code) {
+                                self.expect(b"\\u")?;
+                                let low = self.parse_hex4()?;
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            }
+                            char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        _ => return Err(anyhow!("invalid escape in JSON at {}", self.position)),
+                    };
+                    let mut buf = [0; 4];
+                    value.extend_from_slice(char.encode_utf8(&mut buf).as_bytes());
+                }
+                byte => value.push(byte),
+            }
+        }
+        Ok(String::from_utf8(value)?)
+    }
+
+    fn parse_array(&mut self) -> Result<JsonValue> {
+        self.expect(b"[")?;
+        let mut values = vec![];
+        self.skip_whitespace();
+        if self.input.get(self.position) == Some(&b']') {
+            self.position += 1;
+            return Ok(JsonValue::Array(values));
+        }
+        loop {
+            values.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.input.get(self.position) {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(JsonValue::Array(values));
+                }
+                _ => return Err(anyhow!("expected , or ] in JSON at {}", self.position)),
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<JsonValue> {
+        self.expect(b"{")?;
+        let mut entries = vec![];
+        self.skip_whitespace();
+        if self.input.get(self.position) == Some(&b'}') {
+            self.position += 1;
+            return Ok(JsonValue::Object(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            self.expect(b":")?;
+            entries.push((key, self.parse_value()?));
+            self.skip_whitespace();
+            match self.input.get(self.position) {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(JsonValue::Object(entries));
+                }
+                _ => return Err(anyhow!("expected , or }} in JSON at {}", self.position)),
+            }
+        }
+    }
+}
@@ -0,0 +1,300 @@
+use std::fmt;
+
+use crate::json;
+use crate::{Instruction, Location, Program, Span};
+
+/// A possible mistake found in a program.
+#[derive(Debug)]
+pub struct Warning {
+    pub lint: &'static str,
+    /// Source of the instruction the warning refers to
+    pub span: Span,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Location { line, column } = self.span.start;
+        write!(
+            f,
+            "{line}:{column}: warning[{}]: {}",
+            self.lint, self.message
+        )
+    }
+}
+
+/// A warning identified by the position of the instruction it refers to.
+type Finding = (usize, &'static str, String);
+
+/// Run all analyses over the program. `spans` holds the source span of every
+/// instruction, as recorded by the `Parser`.
+pub fn lint(program: &Program, spans: &[Span]) -> Vec<Warning> {
+    let mut findings: Vec<Finding> = vec![];
+    let dead_loops = find_dead_loops(program);
+    for &(start, _) in &dead_loops {
+        findings.push((
+            start,
+            "dead-loop",
+            "loop is never entered, as the current cell is always 0 here".to_string(),
+        ));
+    }
+
+    let mut others = vec![];
+    find_cancelling_sequences(program, &mut others);
+    find_unbalanced_loops(program, &mut others);
+    find_eof_assumptions(program, &mut others);
+    // Code which never runs has already been reported as a whole
+    others.retain(|(position, _, _)| {
+        !dead_loops
+            .iter()
+            .any(|&(start, end)| (start..=end).contains(position))
+    });
+    findings.extend(others);
+
+    findings.sort_by_key(|(position, _, _)| *position);
+    findings
+        .into_iter()
+        .map(|(position, lint, message)| Warning {
+            lint,
+            span: spans[position],
+            message,
+        })
+        .collect()
+}
+
+/// Find loops which are never entered, because the current cell is known to
+/// be 0 once they are reached. This is the case at the start of the program,
+/// before any cell has been modified, and directly after another loop, as
+/// long as the `+` and `-` in between cancel out.
+///
+/// Returns the positions of the start and end of each dead loop.
+fn find_dead_loops(program: &Program) -> Vec<(usize, usize)> {
+    let mut dead_loops = vec![];
+    // Net change of the current cell since it was last known to be 0
+    let mut delta: Option<i64> = Some(0);
+    let mut tape_untouched = true;
+    // State outside of the procedures currently being defined
+    let mut outer_states = vec![];
+    let mut position = 0;
+    while position < program.len() {
+        match program[position] {
+            Instruction::JmpForward(target) if delta == Some(0) => {
+                dead_loops.push((position, target - 1));
+                // Skip the whole loop, the cell stays 0
+                position = target;
+                continue;
+            }
+            Instruction::JmpForward(_) => delta = None,
+            Instruction::JmpBack(_) => delta = Some(0),
+            Instruction::Inc(count) => {
+                delta = delta.map(|delta| delta + i64::from(count));
+                tape_untouched = false;
+            }
+            Instruction::Dec(count) => {
+                delta = delta.map(|delta| delta - i64::from(count));
+                tape_untouched = false;
+            }
+            Instruction::Input(_) => {
+                delta = None;
+                tape_untouched = false;
+            }
+            Instruction::AddrRight(_) | Instruction::AddrLeft(_) => {
+                delta = tape_untouched.then_some(0);
+            }
+            Instruction::Output(_) => {}
+            // Procedures run on whatever state they are called with
+            Instruction::ProcedureStart(_) => {
+                outer_states.push((delta, tape_untouched));
+                delta = None;
+                tape_untouched = false;
+            }
+            Instruction::ProcedureEnd(_) => {
+                (delta, tape_untouched) = outer_states.pop().unwrap_or_default();
+            }
+            // A fork clears the cell of the parent, but sets the one of the
+            // child
+            Instruction::Call | Instruction::Fork => {
+                delta = None;
+                tape_untouched = false;
+            }
+        }
+        position += 1;
+    }
+
+    dead_loops
+}
+
+fn find_cancelling_sequences(program: &Program, findings: &mut Vec<Finding>) {
+    for (position, pair) in program.windows(2).enumerate() {
+        let commands = match (&pair[0], &pair[1]) {
+            (Instruction::Inc(_), Instruction::Dec(_)) => ("-", "+"),
+            (Instruction::Dec(_), Instruction::Inc(_)) => ("+", "-"),
+            (Instruction::AddrRight(_), Instruction::AddrLeft(_)) => ("<", ">"),
+            (Instruction::AddrLeft(_), Instruction::AddrRight(_)) => (">", "<"),
+            _ => continue,
+        };
+        findings.push((
+            position + 1,
+            "cancelling-sequence",
+            format!(
+                "`{}` directly cancels out the preceding `{}`",
+                commands.0, commands.1
+            ),
+        ));
+    }
+}
+
+/// Find innermost loops, which do not return the pointer to the cell they
+/// started on. Those scan the tape for a zero cell and never terminate if
+/// there is none.
+fn find_unbalanced_loops(program: &Program, findings: &mut Vec<Finding>) {
+    let mut loop_start = None;
+    let mut movement: isize = 0;
+    for (position, instruction) in program.iter().enumerate() {
+        match *instruction {
+            Instruction::JmpForward(_) => {
+                loop_start = Some(position);
+                movement = 0;
+            }
+            Instruction::JmpBack(_) => {
+                if let Some(start) = loop_start.take() {
+                    if movement != 0 {
+                        findings.push((
+                            start,
+                            "unbalanced-loop",
+                            format!(
+                                "loop moves the pointer by {movement:+} cells per iteration \
+                                 and never terminates if no zero cell is found"
+                            ),
+                        ));
+                    }
+                }
+            }
+            Instruction::AddrRight(count) => movement += count as isize,
+            Instruction::AddrLeft(count) => movement -= count as isize,
+            // The movement of procedures is not known here
+            Instruction::ProcedureStart(_) | Instruction::ProcedureEnd(_) | Instruction::Call => {
+                loop_start = None
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Find loops which are controlled by a value just read by `,`. Their
+/// behavior depends on what the implementation stores on EOF.
+fn find_eof_assumptions(program: &Program, findings: &mut Vec<Finding>) {
+    for (position, instruction) in program.iter().enumerate() {
+        if !matches!(instruction, Instruction::Input(_)) {
+            continue;
+        }
+        let assumption = match (program.get(position + 1), program.get(position + 2)) {
+            (Some(Instruction::JmpForward(_)), _) => "0",
+            (Some(Instruction::Inc(1)), Some(Instruction::JmpForward(_))) => "-1",
+            _ => continue,
+        };
+        findings.push((
+            position,
+            "eof-assumption",
+            format!(
+                "loop condition assumes `,` stores {assumption} on EOF, \
+                 which differs between implementations"
+            ),
+        ));
+    }
+}
+
+/// Render the warnings as a JSON array of objects.
+pub fn to_json(warnings: &[Warning]) -> String {
+    let entries = warnings
+        .iter()
+        .map(|warning| {
+            format!(
+                r#"{{"lint":"{}","line":{},"column":{},"end_line":{},"end_column":{},"message":"{}"}}"#,
+                warning.lint,
+                warning.span.start.line,
+                warning.span.start.column,
+                warning.span.end.line,
+                warning.span.end.column,
+                json::escape(&warning.message)
+            )
+        })
+        .collect::<Vec<_>>();
+    format!("[{}]", entries.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::Dialect;
+    use crate::{Lexer, Parser};
+    use std::rc::Rc;
+
+    /// Lint the program and return the lint and column of every warning.
+    fn check(code: &str) -> Vec<(&'static str, usize)> {
+        let dialect = Rc::new(Dialect::load("pbrain").unwrap());
+        let mut parser = Parser::default();
+        let program = parser
+            .parse_program(&mut Lexer::new(code.as_bytes(), dialect))
+            .unwrap();
+        lint(&program, &parser.spans)
+            .into_iter()
+            .map(|warning| (warning.lint, warning.span.start.column))
+            .collect()
+    }
+
+    #[test]
+    fn dead_loops() {
+        assert_eq!(check("[-]"), [("dead-loop", 1)]);
+        assert_eq!(check("+[-][.]"), [("dead-loop", 5)]);
+        assert_eq!(
+            check("+-[.]"),
+            [("cancelling-sequence", 2), ("dead-loop", 3)]
+        );
+        assert_eq!(
+            check("+[-]++--[.]"),
+            [("cancelling-sequence", 7), ("dead-loop", 9)]
+        );
+        assert_eq!(check(">[.]"), [("dead-loop", 2)]);
+        assert_eq!(check("+[-]+[-]"), []);
+        assert_eq!(check("+>[-]"), []);
+        assert_eq!(check(",.[-]"), []);
+    }
+
+    #[test]
+    fn cancelling_sequences() {
+        assert_eq!(check("+>+-"), [("cancelling-sequence", 4)]);
+        assert_eq!(check("+><"), [("cancelling-sequence", 3)]);
+        assert_eq!(check("++>+<"), []);
+    }
+
+    #[test]
+    fn unbalanced_loops() {
+        assert_eq!(check("+[>]"), [("unbalanced-loop", 2)]);
+        assert_eq!(check("+[>+<-]"), []);
+    }
+
+    #[test]
+    fn eof_assumptions() {
+        assert_eq!(check(",[.,]"), [("eof-assumption", 1)]);
+        assert_eq!(check(",+[-.,+]"), [("eof-assumption", 1)]);
+        assert_eq!(check(",.+[-]"), []);
+    }
+
+    #[test]
+    fn json_output() {
+        let warnings = [Warning {
+            lint: "dead-loop",
+            span: Span {
+                start: Location { line: 1, column: 2 },
+                end: Location { line: 3, column: 4 },
+            },
+            message: "cell is \"0\"".to_string(),
+        }];
+        assert_eq!(
+            to_json(&warnings),
+            r#"[{"lint":"dead-loop","line":1,"column":2,"end_line":3,"end_column":4,"message":"cell is \"0\""}]"#
+        );
+        assert_eq!(to_json(&[]), "[]");
+    }
+}
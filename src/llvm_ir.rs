@@ -0,0 +1,375 @@
+use std::fmt::Write;
+use std::ops::Range;
+
+use crate::backend::{CellWidth, EofBehavior, ExecutionConfig};
+use crate::{Instruction, Program};
+use anyhow::{anyhow, Result};
+
+/// Size of the procedure table, as procedures are only supported for 8 bit
+/// cells.
+const PROCEDURE_COUNT: usize = 256;
+const UNDEFINED_PROCEDURE_MESSAGE: &str = "Error: Call of undefined procedure %u\\0A\\00";
+/// Length of the message above once the escapes are resolved.
+const UNDEFINED_PROCEDURE_MESSAGE_LENGTH: usize = 39;
+
+/// Translates a parsed program into a module of textual LLVM IR.
+///
+/// The tape and the pointer are globals, so procedures of pbrain can become
+/// functions of their own. Values are kept in memory and loaded by every
+/// instruction, which `opt` turns into registers again.
+struct Emitter<'a> {
+    program: &'a Program,
+    cell: &'static str,
+    eof: EofBehavior,
+    tape_type: String,
+    /// Counter for unique names of temporaries
+    temporaries: usize,
+    /// Functions emitted for the procedures of the program
+    procedures: Vec<String>,
+}
+
+impl Emitter<'_> {
+    fn temporary(&mut self) -> String {
+        self.temporaries += 1;
+        format!("%t{}", self.temporaries)
+    }
+
+    /// Emit the address of the current cell, returning its name.
+    fn emit_cell_address(&mut self, code: &mut String) -> String {
+        let ptr = self.temporary();
+        let address = self.temporary();
+        let _ = writeln!(code, "  {ptr} = load i64, ptr @ptr");
+        let _ = writeln!(
+            code,
+            "  {address} = getelementptr inbounds {}, ptr @tape, i64 0, i64 {ptr}",
+            self.tape_type
+        );
+        address
+    }
+
+    fn emit_move(&mut self, code: &mut String, operation: &str, count: usize) {
+        let ptr = self.temporary();
+        let moved = self.temporary();
+        let _ = writeln!(code, "  {ptr} = load i64, ptr @ptr");
+        let _ = writeln!(code, "  {moved} = {operation} i64 {ptr}, {count}");
+        let _ = writeln!(code, "  store i64 {moved}, ptr @ptr");
+    }
+
+    fn emit_arithmetic(&mut self, code: &mut String, operation: &str, count: u8) {
+        let address = self.emit_cell_address(code);
+        let value = self.temporary();
+        let result = self.temporary();
+        let _ = writeln!(code, "  {value} = load {}, ptr {address}", self.cell);
+        let _ = writeln!(
+            code,
+            "  {result} = {operation} {} {value}, {count}",
+            self.cell
+        );
+        let _ = writeln!(code, "  store {} {result}, ptr {address}", self.cell);
+    }
+
+    /// Load the current cell, returning the name of its value.
+    fn emit_load_cell(&mut self, code: &mut String) -> String {
+        let address = self.emit_cell_address(code);
+        let value = self.temporary();
+        let _ = writeln!(code, "  {value} = load {}, ptr {address}", self.cell);
+        value
+    }
+
+    /// Emit the instructions in the given range as the body of a function.
+    fn emit_body(&mut self, range: Range<usize>) -> Result<String> {
+        let mut code = String::new();
+        let mut open_loops = vec![];
+        let mut position = range.start;
+        while position < range.end {
+            match self.program[position] {
+                Instruction::AddrRight(count) => self.emit_move(&mut code, "add", count),
+                Instruction::AddrLeft(count) => self.emit_move(&mut code, "sub", count),
+                Instruction::Inc(count) => self.emit_arithmetic(&mut code, "add", count),
+                Instruction::Dec(count) => self.emit_arithmetic(&mut code, "sub", count),
+                Instruction::Output(count) => {
+                    let value = self.emit_load_cell(&mut code);
+                    let char = if self.cell == "i32" {
+                        value
+                    } else {
+                        let extended = self.temporary();
+                        let _ = writeln!(code, "  {extended} = zext {} {value} to i32", self.cell);
+                        extended
+                    };
+                    for _ in 0..count {
+                        let result = self.temporary();
+                        let _ = writeln!(code, "  {result} = call i32 @putchar(i32 {char})");
+                    }
+                }
+                Instruction::Input(count) => {
+                    for _ in 0..count {
+                        let address = self.emit_cell_address(&mut code);
+                        let old = self.temporary();
+                        let char = self.temporary();
+                        let is_eof = self.temporary();
+                        let _ = writeln!(code, "  {old} = load {}, ptr {address}", self.cell);
+                        let _ = writeln!(code, "  {char} = call i32 @getchar()");
+                        let _ = writeln!(code, "  {is_eof} = icmp eq i32 {char}, -1");
+                        let byte = if self.cell == "i32" {
+                            char
+                        } else {
+                            let truncated = self.temporary();
+                            let _ =
+                                writeln!(code, "  {truncated} = trunc i32 {char} to {}", self.cell);
+                            truncated
+                        };
+                        let on_eof = match self.eof {
+                            EofBehavior::Unchanged => old,
+                            EofBehavior::Zero => "0".to_string(),
+                            EofBehavior::MinusOne => "-1".to_string(),
+                        };
+                        let value = self.temporary();
+                        let _ = writeln!(
+                            code,
+                            "  {value} = select i1 {is_eof}, {cell} {on_eof}, {cell} {byte}",
+                            cell = self.cell
+                        );
+                        let _ = writeln!(code, "  store {} {value}, ptr {address}", self.cell);
+                    }
+                }
+                Instruction::JmpForward(_) => {
+                    open_loops.push(position);
+                    let _ = writeln!(code, "  br label %loop_{position}");
+                    let _ = writeln!(code, "loop_{position}:");
+                    let value = self.emit_load_cell(&mut code);
+                    let non_zero = self.temporary();
+                    let _ = writeln!(code, "  {non_zero} = icmp ne {} {value}, 0", self.cell);
+                    let _ = writeln!(
+                        code,
+                        "  br i1 {non_zero}, label %body_{position}, label %end_{position}"
+                    );
+                    let _ = writeln!(code, "body_{position}:");
+                }
+                Instruction::JmpBack(_) => {
+                    let start = open_loops.pop().expect("loops to be balanced");
+                    let _ = writeln!(code, "  br label %loop_{start}");
+                    let _ = writeln!(code, "end_{start}:");
+                }
+                Instruction::ProcedureStart(target) => {
+                    // The body ends right before the matching ProcedureEnd
+                    let body = self.emit_body(position + 1..target - 1)?;
+                    self.procedures.push(format!(
+                        "define internal void @procedure_{position}() {{\nentry:\n{body}  ret void\n}}\n"
+                    ));
+                    let entry = self.emit_procedure_entry(&mut code);
+                    let _ = writeln!(code, "  store ptr @procedure_{position}, ptr {entry}");
+                    position = target;
+                    continue;
+                }
+                Instruction::ProcedureEnd(_) => {
+                    unreachable!("procedure bodies are emitted without their end")
+                }
+                Instruction::Call => {
+                    let number = self.emit_load_cell(&mut code);
+                    let entry = self.emit_procedure_entry(&mut code);
+                    let procedure = self.temporary();
+                    let undefined = self.temporary();
+                    let _ = writeln!(code, "  {procedure} = load ptr, ptr {entry}");
+                    let _ = writeln!(code, "  {undefined} = icmp eq ptr {procedure}, null");
+                    let _ = writeln!(
+                        code,
+                        "  br i1 {undefined}, label %undefined_{position}, label %call_{position}"
+                    );
+                    let _ = writeln!(code, "undefined_{position}:");
+                    let extended = self.temporary();
+                    let result = self.temporary();
+                    let _ = writeln!(code, "  {extended} = zext {} {number} to i32", self.cell);
+                    let _ = writeln!(
+                        code,
+                        "  {result} = call i32 (i32, ptr, ...) @dprintf(i32 2, ptr @undefined_procedure, i32 {extended})"
+                    );
+                    let _ = writeln!(code, "  call void @exit(i32 1)");
+                    let _ = writeln!(code, "  unreachable");
+                    let _ = writeln!(code, "call_{position}:");
+                    let _ = writeln!(code, "  call void {procedure}()");
+                }
+                Instruction::Fork => {
+                    return Err(anyhow!(
+                        "Forking threads is not supported by the LLVM backend"
+                    ))
+                }
+            }
+            position += 1;
+        }
+        Ok(code)
+    }
+
+    /// Emit the address of the entry of the procedure table for the current
+    /// cell, returning its name.
+    fn emit_procedure_entry(&mut self, code: &mut String) -> String {
+        let number = self.emit_load_cell(code);
+        let index = self.temporary();
+        let entry = self.temporary();
+        let _ = writeln!(code, "  {index} = zext {} {number} to i64", self.cell);
+        let _ = writeln!(
+            code,
+            "  {entry} = getelementptr inbounds [{PROCEDURE_COUNT} x ptr], ptr @procedures, i64 0, i64 {index}"
+        );
+        entry
+    }
+}
+
+/// Translate the program into a module of textual LLVM IR, which defines
+/// `main` and uses `getchar` and `putchar` of the C library for I/O.
+///
+/// Like for the jit, the pointer is not checked to stay on the tape. Pointers
+/// are opaque, so the module requires LLVM 15 or later.
+pub fn to_llvm_ir(
+    program: &Program,
+    config: &ExecutionConfig,
+    source_name: &str,
+) -> Result<String> {
+    let uses_procedures = program.iter().any(|instruction| {
+        matches!(
+            instruction,
+            Instruction::ProcedureStart(_) | Instruction::Call
+        )
+    });
+    if uses_procedures && config.cell_width != CellWidth::U8 {
+        return Err(anyhow!(
+            "Procedures are only supported for 8 bit cells by the LLVM backend"
+        ));
+    }
+    let cell = match config.cell_width {
+        CellWidth::U8 => "i8",
+        CellWidth::U16 => "i16",
+        CellWidth::U32 => "i32",
+    };
+    let mut emitter = Emitter {
+        program,
+        cell,
+        eof: config.eof,
+        tape_type: format!("[{} x {cell}]", config.tape_size),
+        temporaries: 0,
+        procedures: vec![],
+    };
+    let body = emitter.emit_body(0..program.len())?;
+
+    // Writing to a String never fails
+    let mut code = String::new();
+    let _ = writeln!(
+        code,
+        "; Generated by rust_brain from {source_name}, do not edit.\n"
+    );
+    let _ = writeln!(
+        code,
+        "@tape = internal global {} zeroinitializer",
+        emitter.tape_type
+    );
+    let _ = writeln!(code, "@ptr = internal global i64 0");
+    if uses_procedures {
+        let _ = writeln!(
+            code,
+            "@procedures = internal global [{PROCEDURE_COUNT} x ptr] zeroinitializer"
+        );
+        let _ = writeln!(
+            code,
+            "@undefined_procedure = private unnamed_addr constant [{UNDEFINED_PROCEDURE_MESSAGE_LENGTH} x i8] c\"{UNDEFINED_PROCEDURE_MESSAGE}\""
+        );
+    }
+    let _ = writeln!(code);
+    let _ = writeln!(code, "declare i32 @getchar()");
+    let _ = writeln!(code, "declare i32 @putchar(i32)");
+    if uses_procedures {
+        let _ = writeln!(code, "declare i32 @dprintf(i32, ptr, ...)");
+        let _ = writeln!(code, "declare void @exit(i32) noreturn");
+    }
+    let _ = writeln!(code);
+    let _ = writeln!(code, "define i32 @main() {{\nentry:\n{body}  ret i32 0\n}}");
+    for procedure in &emitter.procedures {
+        let _ = write!(code, "\n{procedure}");
+    }
+
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::Dialect;
+    use crate::{Lexer, Parser};
+    use std::rc::Rc;
+
+    /// Compare the translation of a program to the expected module, which is
+    /// generated with `rust_brain [--dialect <dialect>] transpile --target
+    /// llvm [--cell-width <bits>] -o tests/llvm_ir/<name>.ll <path>`.
+    fn check(path: &str, source: &str, dialect: &str, cell_width: CellWidth, expected: &str) {
+        let dialect = Rc::new(Dialect::load(dialect).unwrap());
+        let program = Parser::default()
+            .parse_program(&mut Lexer::new(source.as_bytes(), dialect))
+            .unwrap();
+        let config = ExecutionConfig {
+            cell_width,
+            ..ExecutionConfig::default()
+        };
+        let code = to_llvm_ir(&program, &config, &format!("brainfuck file {path}")).unwrap();
+        assert!(code == expected, "translation of {path} changed:\n{code}");
+    }
+
+    #[test]
+    fn hello_world() {
+        check(
+            "examples/hello_world.brainfuck",
+            include_str!("../examples/hello_world.brainfuck"),
+            "brainfuck",
+            CellWidth::U8,
+            include_str!("../tests/llvm_ir/hello_world.ll"),
+        );
+    }
+
+    #[test]
+    fn procedures() {
+        check(
+            "tests/llvm_ir/procedures.pbrain",
+            include_str!("../tests/llvm_ir/procedures.pbrain"),
+            "pbrain",
+            CellWidth::U8,
+            include_str!("../tests/llvm_ir/procedures.ll"),
+        );
+    }
+
+    #[test]
+    fn cell_widths() {
+        let expected = [
+            (
+                CellWidth::U8,
+                include_str!("../tests/llvm_ir/cell_width_8.ll"),
+            ),
+            (
+                CellWidth::U16,
+                include_str!("../tests/llvm_ir/cell_width_16.ll"),
+            ),
+            (
+                CellWidth::U32,
+                include_str!("../tests/llvm_ir/cell_width_32.ll"),
+            ),
+        ];
+        for (cell_width, expected) in expected {
+            check(
+                "conformance/cell_width.b",
+                include_str!("../conformance/cell_width.b"),
+                "brainfuck",
+                cell_width,
+                expected,
+            );
+        }
+    }
+
+    #[test]
+    fn procedures_require_8_bit_cells() {
+        let dialect = Rc::new(Dialect::load("pbrain").unwrap());
+        let program = Parser::default()
+            .parse_program(&mut Lexer::new(&b"(+):"[..], dialect))
+            .unwrap();
+        let config = ExecutionConfig {
+            cell_width: CellWidth::U16,
+            ..ExecutionConfig::default()
+        };
+        assert!(to_llvm_ir(&program, &config, "test").is_err());
+    }
+}
@@ -0,0 +1,677 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::rc::Rc;
+
+use crate::dialect::Dialect;
+use crate::formatter::{self, FormatOptions};
+use crate::json::JsonValue;
+use crate::{linter, Instruction, Lexer, Location, Parser, Program, Span};
+use anyhow::{anyhow, Context, Result};
+
+const TEXT_DOCUMENT_SYNC_FULL: usize = 1;
+const SEVERITY_ERROR: usize = 1;
+const SEVERITY_WARNING: usize = 2;
+const PARSE_ERROR: f64 = -32700.0;
+const METHOD_NOT_FOUND: f64 = -32601.0;
+const INVALID_PARAMS: f64 = -32602.0;
+
+/// Read the next message, framed by a `Content-Length` header. Returns `None`
+/// once the client closed the connection, and the reason for messages which
+/// can not be decoded. Only failing to read is an error.
+fn read_message<R: BufRead>(reader: &mut R) -> Result<Option<Result<JsonValue>>> {
+    let mut content_length = None;
+    let mut header = String::new();
+    loop {
+        header.clear();
+        if reader.read_line(&mut header).context("read header")? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = Some(value.trim().parse::<usize>().map_err(|_| value.to_string()));
+            }
+        }
+    }
+
+    // Without a length the body can not be skipped, the following lines are
+    // read as headers of the next message instead
+    let content_length = match content_length {
+        Some(Ok(content_length)) => content_length,
+        Some(Err(value)) => return Ok(Some(Err(anyhow!("Invalid Content-Length {value}")))),
+        None => return Ok(Some(Err(anyhow!("Missing Content-Length header")))),
+    };
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).context("read message body")?;
+    Ok(Some(
+        String::from_utf8(body)
+            .context("decode message body")
+            .and_then(|body| JsonValue::parse(&body)),
+    ))
+}
+
+fn write_message<W: Write>(writer: &mut W, message: &JsonValue) -> Result<()> {
+    let body = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    writer.flush().context("flush message")
+}
+
+/// Open documents, which translate between `Location`s of the lexer and
+/// positions of the protocol.
+///
+/// Columns of the lexer count bytes, while the protocol counts UTF-16 code
+/// units. Both start at 1 for the lexer and at 0 for the protocol.
+struct Document {
+    text: String,
+    dialect: Rc<Dialect>,
+}
+
+impl Document {
+    fn lexer(&self) -> Lexer<&[u8]> {
+        Lexer::new(self.text.as_bytes(), Rc::clone(&self.dialect))
+    }
+
+    fn line(&self, line: usize) -> &str {
+        self.text.split('\n').nth(line).unwrap_or_default()
+    }
+
+    fn position(&self, location: Location) -> JsonValue {
+        let line = self.line(location.line - 1);
+        let end = (location.column - 1).min(line.len());
+        let character = String::from_utf8_lossy(&line.as_bytes()[..end])
+            .encode_utf16()
+            .count();
+        JsonValue::object([
+            ("line", (location.line - 1).into()),
+            ("character", character.into()),
+        ])
+    }
+
+    fn range(&self, span: Span) -> JsonValue {
+        JsonValue::object([
+            ("start", self.position(span.start)),
+            ("end", self.position(span.end)),
+        ])
+    }
+
+    fn location(&self, position: &JsonValue) -> Result<Location> {
+        let line = position
+            .get("line")
+            .and_then(JsonValue::as_usize)
+            .ok_or_else(|| anyhow!("Missing line of position"))?;
+        let character = position
+            .get("character")
+            .and_then(JsonValue::as_usize)
+            .ok_or_else(|| anyhow!("Missing character of position"))?;
+
+        let mut units = 0;
+        let mut column = 1;
+        for char in self.line(line).chars() {
+            if units >= character {
+                break;
+            }
+            units += char.len_utf16();
+            column += char.len_utf8();
+        }
+        Ok(Location {
+            line: line + 1,
+            column,
+        })
+    }
+
+    /// Find the command at the location, if any.
+    fn command_at(&self, location: Location) -> Option<char> {
+        let mut lexer = self.lexer();
+        while let Ok(Some(token)) = lexer.chop() {
+            if (token.location.line, token.location.column) == (location.line, location.column) {
+                return Some(token.char);
+            }
+        }
+        None
+    }
+
+    fn parse(&self) -> Result<(Program, Vec<Span>)> {
+        let mut parser = Parser::default();
+        let program = parser.parse_program(&mut self.lexer())?;
+        Ok((program, parser.spans))
+    }
+
+    /// Report every unbalanced bracket. Warnings of the linter are only
+    /// reported for programs which parse.
+    fn diagnostics(&self) -> Vec<JsonValue> {
+        let diagnostic = |span, severity: usize, code: &str, message: String| {
+            JsonValue::object([
+                ("range", self.range(span)),
+                ("severity", severity.into()),
+                ("code", code.into()),
+                ("source", "rust_brain".into()),
+                ("message", message.into()),
+            ])
+        };
+
+        let mut diagnostics = vec![];
+        let mut open_brackets: Vec<(char, Span)> = vec![];
+        let mut lexer = self.lexer();
+        while let Ok(Some(token)) = lexer.chop() {
+            let opening = match token.char {
+                '[' | '(' => {
+                    open_brackets.push((token.char, token.span()));
+                    continue;
+                }
+                ']' => '[',
+                ')' => '(',
+                _ => continue,
+            };
+            if open_brackets.last().map(|&(char, _)| char) == Some(opening) {
+                open_brackets.pop();
+                continue;
+            }
+            let counterpart = if opening == '[' {
+                "forward jump"
+            } else {
+                "procedure start"
+            };
+            diagnostics.push(diagnostic(
+                token.span(),
+                SEVERITY_ERROR,
+                "unbalanced-bracket",
+                format!(
+                    "Could not find corresponding {counterpart} for {}",
+                    token.char
+                ),
+            ));
+        }
+        for (char, span) in open_brackets {
+            let counterpart = if char == '[' {
+                "backward jump"
+            } else {
+                "procedure end"
+            };
+            diagnostics.push(diagnostic(
+                span,
+                SEVERITY_ERROR,
+                "unbalanced-bracket",
+                format!("Could not find corresponding {counterpart} for {char}"),
+            ));
+        }
+
+        if diagnostics.is_empty() {
+            if let Ok((program, spans)) = self.parse() {
+                for warning in linter::lint(&program, &spans) {
+                    diagnostics.push(diagnostic(
+                        warning.span,
+                        SEVERITY_WARNING,
+                        warning.lint,
+                        warning.message,
+                    ));
+                }
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Find the index of the instruction the command at the location has been
+    /// folded into.
+    fn instruction_at(&self, location: Location, spans: &[Span]) -> Option<usize> {
+        self.command_at(location)?;
+        spans.iter().position(|span| span.contains(location))
+    }
+
+    fn hover(&self, location: Location) -> JsonValue {
+        let Ok((program, spans)) = self.parse() else {
+            return JsonValue::Null;
+        };
+        let Some(index) = self.instruction_at(location, &spans) else {
+            return JsonValue::Null;
+        };
+
+        JsonValue::object([
+            (
+                "contents",
+                JsonValue::object([
+                    ("kind", "plaintext".into()),
+                    ("value", format!("{:?}", program[index]).into()),
+                ]),
+            ),
+            ("range", self.range(spans[index])),
+        ])
+    }
+
+    /// Span of the bracket matching the one at the location.
+    fn matching_bracket(&self, location: Location) -> Option<Span> {
+        let (program, spans) = self.parse().ok()?;
+        let index = self.instruction_at(location, &spans)?;
+
+        // Jumps target the instruction after the matching one
+        match program[index] {
+            Instruction::JmpForward(target)
+            | Instruction::JmpBack(target)
+            | Instruction::ProcedureStart(target)
+            | Instruction::ProcedureEnd(target) => Some(spans[target - 1]),
+            _ => None,
+        }
+    }
+
+    fn format(&self) -> Result<JsonValue> {
+        // Like the fmt command, refuse to format invalid programs
+        if self.parse().is_err() {
+            return Ok(JsonValue::Null);
+        }
+        let formatted =
+            formatter::format_source(&mut self.lexer(), &self.dialect, FormatOptions::default())?;
+
+        let lines = self.text.split('\n').count();
+        let end = Location {
+            line: lines,
+            column: self.line(lines - 1).len() + 1,
+        };
+        Ok(JsonValue::Array(vec![JsonValue::object([
+            (
+                "range",
+                JsonValue::object([
+                    ("start", self.position(Location::default())),
+                    ("end", self.position(end)),
+                ]),
+            ),
+            ("newText", formatted.into()),
+        ])]))
+    }
+}
+
+struct Server<W: Write> {
+    output: W,
+    dialect: Rc<Dialect>,
+    documents: HashMap<String, Document>,
+}
+
+impl<W: Write> Server<W> {
+    fn document<'a>(&'a self, params: &'a JsonValue) -> Result<(&'a str, &'a Document)> {
+        let uri = params
+            .pointer(&["textDocument", "uri"])
+            .and_then(JsonValue::as_str)
+            .ok_or_else(|| anyhow!("Missing text document uri"))?;
+        let document = self
+            .documents
+            .get(uri)
+            .ok_or_else(|| anyhow!("Unknown text document {uri}"))?;
+        Ok((uri, document))
+    }
+
+    fn publish_diagnostics(&mut self, uri: &str) -> Result<()> {
+        let diagnostics = self
+            .documents
+            .get(uri)
+            .map(Document::diagnostics)
+            .unwrap_or_default();
+        write_message(
+            &mut self.output,
+            &JsonValue::object([
+                ("jsonrpc", "2.0".into()),
+                ("method", "textDocument/publishDiagnostics".into()),
+                (
+                    "params",
+                    JsonValue::object([
+                        ("uri", uri.into()),
+                        ("diagnostics", JsonValue::Array(diagnostics)),
+                    ]),
+                ),
+            ]),
+        )
+    }
+
+    fn handle_notification(&mut self, method: &str, params: &JsonValue) -> Result<()> {
+        let uri = params
+            .pointer(&["textDocument", "uri"])
+            .and_then(JsonValue::as_str)
+            .map(str::to_string);
+        match (method, uri) {
+            ("textDocument/didOpen", Some(uri)) => {
+                let text = params
+                    .pointer(&["textDocument", "text"])
+                    .and_then(JsonValue::as_str)
+                    .unwrap_or_default();
+                self.documents.insert(
+                    uri.clone(),
+                    Document {
+                        text: text.to_string(),
+                        dialect: Rc::clone(&self.dialect),
+                    },
+                );
+                self.publish_diagnostics(&uri)
+            }
+            ("textDocument/didChange", Some(uri)) => {
+                // Documents are synchronized in full, so the last change
+                // holds the whole text.
+                let text = match params.get("contentChanges") {
+                    Some(JsonValue::Array(changes)) => changes
+                        .last()
+                        .and_then(|change| change.get("text"))
+                        .and_then(JsonValue::as_str),
+                    _ => None,
+                };
+                if let Some(text) = text {
+                    self.documents.insert(
+                        uri.clone(),
+                        Document {
+                            text: text.to_string(),
+                            dialect: Rc::clone(&self.dialect),
+                        },
+                    );
+                }
+                self.publish_diagnostics(&uri)
+            }
+            ("textDocument/didClose", Some(uri)) => {
+                self.documents.remove(&uri);
+                self.publish_diagnostics(&uri)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn handle_request(&mut self, method: &str, params: &JsonValue) -> Option<Result<JsonValue>> {
+        let result = match method {
+            "initialize" => Ok(JsonValue::object([
+                (
+                    "capabilities",
+                    JsonValue::object([
+                        ("textDocumentSync", TEXT_DOCUMENT_SYNC_FULL.into()),
+                        ("hoverProvider", true.into()),
+                        ("definitionProvider", true.into()),
+                        ("documentFormattingProvider", true.into()),
+                    ]),
+                ),
+                (
+                    "serverInfo",
+                    JsonValue::object([
+                        ("name", "rust_brain".into()),
+                        ("version", env!("CARGO_PKG_VERSION").into()),
+                    ]),
+                ),
+            ])),
+            "shutdown" => Ok(JsonValue::Null),
+            "textDocument/hover" => self.document(params).and_then(|(_, document)| {
+                let position = params.get("position").unwrap_or(&JsonValue::Null);
+                Ok(document.hover(document.location(position)?))
+            }),
+            "textDocument/definition" => self.document(params).and_then(|(uri, document)| {
+                let position = params.get("position").unwrap_or(&JsonValue::Null);
+                Ok(
+                    match document.matching_bracket(document.location(position)?) {
+                        Some(span) => JsonValue::object([
+                            ("uri", uri.into()),
+                            ("range", document.range(span)),
+                        ]),
+                        None => JsonValue::Null,
+                    },
+                )
+            }),
+            "textDocument/formatting" => self
+                .document(params)
+                .and_then(|(_, document)| document.format()),
+            _ => return None,
+        };
+        Some(result)
+    }
+
+    /// Answer a message which could not be decoded. As its id is unknown,
+    /// the response has none.
+    fn reject_message(&mut self, e: &anyhow::Error) -> Result<()> {
+        write_message(
+            &mut self.output,
+            &JsonValue::object([
+                ("jsonrpc", "2.0".into()),
+                ("id", JsonValue::Null),
+                (
+                    "error",
+                    JsonValue::object([
+                        ("code", JsonValue::Number(PARSE_ERROR)),
+                        ("message", format!("{e:#}").into()),
+                    ]),
+                ),
+            ]),
+        )
+    }
+
+    /// Handle messages read from the input, until the client sends `exit` or
+    /// closes the connection. Malformed messages are rejected, and the
+    /// following ones handled as usual.
+    fn serve<R: BufRead>(&mut self, input: &mut R) -> Result<()> {
+        while let Some(message) = read_message(input)? {
+            let message = match message {
+                Ok(message) => message,
+                Err(e) => {
+                    self.reject_message(&e)?;
+                    continue;
+                }
+            };
+            if message.get("method").and_then(JsonValue::as_str) == Some("exit") {
+                break;
+            }
+            self.handle_message(&message)?;
+        }
+        Ok(())
+    }
+
+    fn handle_message(&mut self, message: &JsonValue) -> Result<()> {
+        let method = message
+            .get("method")
+            .and_then(JsonValue::as_str)
+            .unwrap_or_default();
+        let params = message.get("params").unwrap_or(&JsonValue::Null);
+        let Some(id) = message.get("id") else {
+            return self.handle_notification(method, params);
+        };
+
+        let outcome = match self.handle_request(method, params) {
+            Some(Ok(result)) => ("result", result),
+            Some(Err(e)) => (
+                "error",
+                JsonValue::object([
+                    ("code", JsonValue::Number(INVALID_PARAMS)),
+                    ("message", e.to_string().into()),
+                ]),
+            ),
+            None => (
+                "error",
+                JsonValue::object([
+                    ("code", JsonValue::Number(METHOD_NOT_FOUND)),
+                    ("message", format!("Unsupported method {method}").into()),
+                ]),
+            ),
+        };
+        write_message(
+            &mut self.output,
+            &JsonValue::object([("jsonrpc", "2.0".into()), ("id", id.clone()), outcome]),
+        )
+    }
+}
+
+/// Serve the Language Server Protocol over stdin and stdout, until the client
+/// sends `exit` or closes the connection.
+pub fn run(dialect: &Rc<Dialect>) -> Result<()> {
+    let mut server = Server {
+        output: io::stdout().lock(),
+        dialect: Rc::clone(dialect),
+        documents: HashMap::new(),
+    };
+    server.serve(&mut io::stdin().lock())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(message: &str) -> String {
+        format!("Content-Length: {}\r\n\r\n{message}", message.len())
+    }
+
+    /// Send the messages to a server, returning every message it wrote.
+    fn exchange(messages: &[String]) -> Vec<JsonValue> {
+        let mut server = Server {
+            output: vec![],
+            dialect: Rc::new(Dialect::default()),
+            documents: HashMap::new(),
+        };
+        server.serve(&mut messages.concat().as_bytes()).unwrap();
+
+        let mut output = &server.output[..];
+        let mut responses = vec![];
+        while let Some(message) = read_message(&mut output).unwrap() {
+            responses.push(message.unwrap());
+        }
+        responses
+    }
+
+    fn request(id: usize, method: &str, params: &str) -> String {
+        frame(&format!(
+            r#"{{"jsonrpc":"2.0","id":{id},"method":"{method}","params":{params}}}"#
+        ))
+    }
+
+    fn notification(method: &str, params: &str) -> String {
+        frame(&format!(
+            r#"{{"jsonrpc":"2.0","method":"{method}","params":{params}}}"#
+        ))
+    }
+
+    fn position(line: usize, character: usize) -> String {
+        format!(
+            r#"{{"textDocument":{{"uri":"file:///a.b"}},"position":{{"line":{line},"character":{character}}}}}"#
+        )
+    }
+
+    fn response(responses: &[JsonValue], id: usize) -> &JsonValue {
+        responses
+            .iter()
+            .find(|response| response.get("id").and_then(JsonValue::as_usize) == Some(id))
+            .unwrap_or_else(|| panic!("no response to request {id}"))
+    }
+
+    fn range(value: &JsonValue) -> [usize; 4] {
+        let get = |path: &[&str]| {
+            let mut path = path.to_vec();
+            path.insert(0, "range");
+            value.pointer(&path).and_then(JsonValue::as_usize).unwrap()
+        };
+        [
+            get(&["start", "line"]),
+            get(&["start", "character"]),
+            get(&["end", "line"]),
+            get(&["end", "character"]),
+        ]
+    }
+
+    #[test]
+    fn scripted_session() {
+        let responses = exchange(&[
+            request(1, "initialize", r#"{"capabilities":{}}"#),
+            notification("initialized", "{}"),
+            notification(
+                "textDocument/didOpen",
+                r#"{"textDocument":{"uri":"file:///a.b","languageId":"brainfuck","version":1,"text":"+++[>+<-]\n>."}}"#,
+            ),
+            request(2, "textDocument/hover", &position(0, 1)),
+            request(3, "textDocument/definition", &position(0, 3)),
+            request(4, "textDocument/definition", &position(0, 8)),
+            request(
+                5,
+                "textDocument/formatting",
+                r#"{"textDocument":{"uri":"file:///a.b"},"options":{"tabSize":4,"insertSpaces":true}}"#,
+            ),
+            request(6, "shutdown", "null"),
+            notification("exit", "null"),
+            // Not handled anymore once the client asked to exit
+            request(7, "shutdown", "null"),
+        ]);
+
+        let capabilities = response(&responses, 1)
+            .pointer(&["result", "capabilities"])
+            .unwrap();
+        for capability in [
+            "hoverProvider",
+            "definitionProvider",
+            "documentFormattingProvider",
+        ] {
+            assert_eq!(capabilities.get(capability), Some(&JsonValue::Bool(true)));
+        }
+
+        let diagnostics = responses
+            .iter()
+            .find(|message| {
+                message.get("method").and_then(JsonValue::as_str)
+                    == Some("textDocument/publishDiagnostics")
+            })
+            .unwrap();
+        assert_eq!(
+            diagnostics.pointer(&["params", "diagnostics"]),
+            Some(&JsonValue::Array(vec![]))
+        );
+
+        // Hovering any command of a folded run shows the whole run
+        let hover = response(&responses, 2).get("result").unwrap();
+        assert_eq!(
+            hover
+                .pointer(&["contents", "value"])
+                .and_then(JsonValue::as_str),
+            Some("Inc(3)")
+        );
+        assert_eq!(range(hover), [0, 0, 0, 3]);
+
+        let definition = response(&responses, 3).get("result").unwrap();
+        assert_eq!(
+            definition.get("uri").and_then(JsonValue::as_str),
+            Some("file:///a.b")
+        );
+        assert_eq!(range(definition), [0, 8, 0, 9]);
+        let definition = response(&responses, 4).get("result").unwrap();
+        assert_eq!(range(definition), [0, 3, 0, 4]);
+
+        let JsonValue::Array(edits) = response(&responses, 5).get("result").unwrap() else {
+            panic!("formatting to return a list of edits");
+        };
+        assert_eq!(edits.len(), 1);
+        assert_eq!(range(&edits[0]), [0, 0, 1, 2]);
+        assert_eq!(
+            edits[0].get("newText").and_then(JsonValue::as_str),
+            Some("+++\n[\n  >+<-\n]\n>.\n")
+        );
+
+        assert_eq!(
+            response(&responses, 6).get("result"),
+            Some(&JsonValue::Null)
+        );
+        assert!(responses
+            .iter()
+            .all(|response| response.get("id").and_then(JsonValue::as_usize) != Some(7)));
+    }
+
+    #[test]
+    fn malformed_messages_are_rejected() {
+        let responses = exchange(&[
+            frame("{not json"),
+            "Content-Length: many\r\n\r\n".to_string(),
+            frame("\"unterminated"),
+            request(1, "shutdown", "null"),
+        ]);
+
+        let errors = responses
+            .iter()
+            .filter(|response| {
+                response.pointer(&["error", "code"]) == Some(&JsonValue::Number(-32700.0))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(errors.len(), 3);
+        assert!(errors
+            .iter()
+            .all(|error| error.get("id") == Some(&JsonValue::Null)));
+        // The server keeps answering afterwards
+        assert_eq!(
+            response(&responses, 1).get("result"),
+            Some(&JsonValue::Null)
+        );
+    }
+}
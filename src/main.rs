@@ -1,11 +1,64 @@
 use anyhow::{anyhow, Context, Result};
+use std::collections::{HashMap, VecDeque};
 use std::env;
-use std::fs::File;
-use std::io::{BufReader, Read, Write};
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Write};
 use std::process;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{mpsc, RwLock, RwLockWriteGuard};
+use std::thread;
 
-#[cfg(all(target_arch = "x86_64", target_os = "linux"))]
-use crate::x86_jit::{JitCompiler, X86Assembler};
+use crate::backend::{
+    read_byte, select_backend, CellWidth, EofBehavior, ExecutionConfig, RunState,
+};
+use crate::bytecode::BytecodeHeader;
+use crate::cli::{Command, ProgramSource};
+use crate::dialect::Dialect;
+use crate::snapshot::{MachineState, Snapshot};
+use crate::stats::ProgramStats;
+use crate::transpiler::{Target, TranspileOptions};
+
+// Built on every host so the encodings are checked by the tests, but only
+// executed on aarch64
+#[cfg_attr(
+    not(all(target_arch = "aarch64", target_os = "linux")),
+    allow(dead_code)
+)]
+mod aarch64_jit;
+mod backend;
+mod bench;
+mod bytecode;
+mod cli;
+#[cfg(all(
+    any(target_arch = "x86_64", target_arch = "aarch64"),
+    target_os = "linux"
+))]
+mod code_cache;
+mod conformance;
+mod coverage;
+mod debugger;
+mod dialect;
+mod formatter;
+mod generator;
+#[cfg(all(
+    any(target_arch = "x86_64", target_arch = "aarch64"),
+    target_os = "linux"
+))]
+mod jit;
+mod json;
+mod linter;
+mod llvm_ir;
+mod lsp;
+mod optimizer;
+mod repl;
+mod snapshot;
+mod stats;
+mod threads;
+mod trace;
+mod transpiler;
+mod visualizer;
+mod vm;
 #[cfg(all(target_arch = "x86_64", target_os = "linux"))]
 mod x86_jit;
 
@@ -13,9 +66,13 @@ struct Lexer<R: Read> {
     source: R,
     location: Location,
     peeked_token: Option<Token>,
+    dialect: Rc<Dialect>,
+    /// Bytes read from the source while matching keywords, but not consumed
+    /// yet
+    lookahead: VecDeque<(u8, Location)>,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 struct Location {
     line: usize,
     column: usize,
@@ -27,13 +84,50 @@ impl Default for Location {
     }
 }
 
+/// Extent of source text, from the location of its first byte up to the
+/// location right after its last byte.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+struct Span {
+    start: Location,
+    end: Location,
+}
+
+impl Span {
+    fn contains(&self, location: Location) -> bool {
+        (self.start..self.end).contains(&location)
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 struct Token {
     char: char,
     location: Location,
+    /// Location right after the keyword of the command
+    end: Location,
 }
 
+impl Token {
+    fn span(&self) -> Span {
+        Span {
+            start: self.location,
+            end: self.end,
+        }
+    }
+}
+
+/// Element of the lossless token stream, which keeps everything besides the
+/// language commands as comments.
 #[derive(Debug, Clone)]
+enum SourceElement {
+    Command(Token),
+    Comment(String),
+}
+
+/// Command of a keyword read by the lexer, its span and the bytes it spans.
+/// Comments have no command.
+type KeywordMatch = (Option<char>, Span, Vec<u8>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 enum Instruction {
     AddrRight(usize),
     AddrLeft(usize),
@@ -43,29 +137,101 @@ enum Instruction {
     Input(usize),
     JmpForward(usize),
     JmpBack(usize),
+    /// Define the procedure numbered by the current cell, which starts at the
+    /// next instruction. Execution continues at the given position, behind
+    /// the end of the procedure.
+    ProcedureStart(usize),
+    /// Return from the procedure starting at the given position.
+    ProcedureEnd(usize),
+    /// Call the procedure numbered by the current cell.
+    Call,
+    /// Fork the current thread, the child starting on the next cell.
+    Fork,
 }
 
 impl<R> Lexer<R>
 where
     R: Read,
 {
-    fn new(source: R) -> Self {
+    fn new(source: R, dialect: Rc<Dialect>) -> Self {
         Self {
             source,
             location: Location::default(),
             peeked_token: None,
+            dialect,
+            lookahead: VecDeque::new(),
+        }
+    }
+
+    /// Read the next byte from the source together with its location.
+    fn read_source_byte(&mut self) -> Result<Option<(u8, Location)>> {
+        let mut buf: [u8; 1] = [0; 1];
+        let location = self.location;
+        let read_bytes = self
+            .source
+            .read(&mut buf)
+            .context("read next byte from source")?;
+        if read_bytes != 1 {
+            return Ok(None);
+        }
+        self.location.column += 1;
+        if buf[0] == b'\n' {
+            self.location.column = 1;
+            self.location.line += 1;
         }
+
+        Ok(Some((buf[0], location)))
     }
 
-    fn is_char_in_language(candidate: char) -> bool {
-        let lang_chars = "<>+-.,[]";
-        for char in lang_chars.chars() {
-            if char == candidate {
-                return true;
+    /// Look at the byte at the given offset from the current position without
+    /// consuming it.
+    fn peek_byte(&mut self, offset: usize) -> Result<Option<(u8, Location)>> {
+        while self.lookahead.len() <= offset {
+            match self.read_source_byte()? {
+                Some(byte) => self.lookahead.push_back(byte),
+                None => return Ok(None),
             }
         }
+        Ok(Some(self.lookahead[offset]))
+    }
+
+    /// Read the next byte of the source together with its location.
+    fn read_byte(&mut self) -> Result<Option<(u8, Location)>> {
+        match self.lookahead.pop_front() {
+            Some(byte) => Ok(Some(byte)),
+            None => self.read_source_byte(),
+        }
+    }
+
+    /// Location of the next byte to be read.
+    fn next_location(&self) -> Location {
+        self.lookahead
+            .front()
+            .map_or(self.location, |&(_, location)| location)
+    }
+
+    /// Read the next keyword of the dialect or, if there is none at the
+    /// current position, a single byte of comment.
+    fn read_keyword(&mut self) -> Result<Option<KeywordMatch>> {
+        let Some((byte, start)) = self.peek_byte(0)? else {
+            return Ok(None);
+        };
+        let dialect = Rc::clone(&self.dialect);
+        let Some((command, length)) =
+            dialect.match_keyword(|offset| Ok(self.peek_byte(offset)?.map(|(byte, _)| byte)))?
+        else {
+            self.read_byte()?;
+            let end = self.next_location();
+            return Ok(Some((None, Span { start, end }, vec![byte])));
+        };
 
-        false
+        let bytes = self
+            .lookahead
+            .drain(..length)
+            .map(|(byte, _)| byte)
+            .collect();
+        let end = self.next_location();
+        Ok(Some((command, Span { start, end }, bytes)))
     }
 
     fn chop(&mut self) -> Result<Option<Token>> {
@@ -77,51 +243,51 @@ where
             return Ok(Some(token));
         }
 
-        let mut buf: [u8; 1] = [0; 1];
-        let mut location = self.location;
-        while !Self::is_char_in_language(buf[0].into()) {
-            location = self.location;
-            let read_bytes = self
-                .source
-                .read(&mut buf)
-                .context("read next byte from source")?;
-            if read_bytes != 1 {
-                return Ok(None);
-            }
-            self.location.column += 1;
-            if buf[0] == b'\n' {
-                self.location.column = 1;
-                self.location.line += 1;
+        while let Some((command, span, _)) = self.read_keyword()? {
+            if let Some(char) = command {
+                return Ok(Some(Token {
+                    char,
+                    location: span.start,
+                    end: span.end,
+                }));
             }
         }
 
-        Ok(Some(Token {
-            char: buf[0].into(),
-            location,
-        }))
+        Ok(None)
     }
 
-    fn peek(&mut self) -> Result<Option<Token>> {
-        if let Some(token) = self.peeked_token {
-            return Ok(Some(token));
+    /// Chop the next element of the lossless token stream. In contrast to
+    /// `chop` everything in between commands is returned as a comment.
+    fn chop_element(&mut self) -> Result<Option<SourceElement>> {
+        if let Some(token) = self.peeked_token.take() {
+            return Ok(Some(SourceElement::Command(token)));
         }
 
-        self.peeked_token = self.chop().context("reading next token to peek at it")?;
-        Ok(self.peeked_token)
-    }
-
-    fn chop_while(&mut self, token: &Token) -> Result<usize> {
-        let mut count: usize = 0;
-        while let Some(candidate) = self.peek()? {
-            if candidate.char == token.char {
-                self.chop()?;
-                count += 1;
-            } else {
+        let mut comment = vec![];
+        while let Some((command, span, bytes)) = self.read_keyword()? {
+            if let Some(char) = command {
+                let token = Token {
+                    char,
+                    location: span.start,
+                    end: span.end,
+                };
+                if comment.is_empty() {
+                    return Ok(Some(SourceElement::Command(token)));
+                }
+                // Hand out the comment first and keep the command for the
+                // next call.
+                self.peeked_token = Some(token);
                 break;
             }
+            comment.extend(bytes);
         }
 
-        Ok(count)
+        if comment.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(SourceElement::Comment(
+            String::from_utf8_lossy(&comment).into_owned(),
+        )))
     }
 }
 
@@ -129,29 +295,32 @@ type Program = Vec<Instruction>;
 
 #[derive(Default)]
 struct Parser {
-    forward_jumps: Vec<usize>,
+    /// Position and location of every loop and procedure, which has not been
+    /// closed yet
+    open_blocks: Vec<(usize, Location)>,
     program: Program,
+    /// Source span of every instruction in `program`, from its first to its
+    /// last command
+    spans: Vec<Span>,
 }
 
 impl Parser {
-    fn parse_instruction<R: Read>(
-        &mut self,
-        lexer: &mut Lexer<R>,
-        token: &Token,
-    ) -> Result<Instruction> {
+    /// Translate a single command into an instruction. Runs of commands are
+    /// only folded by the optimizer.
+    fn parse_instruction(&mut self, token: &Token) -> Result<Instruction> {
         match token {
-            Token { char: '<', .. } => Ok(Instruction::AddrLeft(1 + lexer.chop_while(token)?)),
-            Token { char: '>', .. } => Ok(Instruction::AddrRight(1 + lexer.chop_while(token)?)),
-            Token { char: '+', .. } => Ok(Instruction::Inc(
-                ((1 + lexer.chop_while(token)?) % 255) as u8,
-            )),
-            Token { char: '-', .. } => Ok(Instruction::Dec(
-                ((1 + lexer.chop_while(token)?) % 255) as u8,
-            )),
-            Token { char: '.', .. } => Ok(Instruction::Output(1 + lexer.chop_while(token)?)),
-            Token { char: ',', .. } => Ok(Instruction::Input(1 + lexer.chop_while(token)?)),
-            Token { char: '[', .. } => {
-                self.forward_jumps.push(self.program.len());
+            Token { char: '<', .. } => Ok(Instruction::AddrLeft(1)),
+            Token { char: '>', .. } => Ok(Instruction::AddrRight(1)),
+            Token { char: '+', .. } => Ok(Instruction::Inc(1)),
+            Token { char: '-', .. } => Ok(Instruction::Dec(1)),
+            Token { char: '.', .. } => Ok(Instruction::Output(1)),
+            Token { char: ',', .. } => Ok(Instruction::Input(1)),
+            Token {
+                char: '[',
+                location,
+                ..
+            } => {
+                self.open_blocks.push((self.program.len(), *location));
                 // Position will be backpatched once encountering corresponding
                 // JmpBack
                 Ok(Instruction::JmpForward(0))
@@ -159,111 +328,576 @@ impl Parser {
             Token {
                 char: ']',
                 location: Location { line, column },
-            } => {
-                if let Some(target) = self.forward_jumps.pop() {
+                ..
+            } => match self.close_block() {
+                Some((target, Instruction::JmpForward(_))) => {
                     self.program[target] = Instruction::JmpForward(self.program.len() + 1);
                     Ok(Instruction::JmpBack(target + 1))
-                } else {
-                    Err(anyhow!(
-                        "Could not find corresponding forward jump for ] at {line}:{column}"
-                    ))
                 }
+                _ => Err(anyhow!(
+                    "Could not find corresponding forward jump for ] at {line}:{column}"
+                )),
+            },
+            Token {
+                char: '(',
+                location,
+                ..
+            } => {
+                self.open_blocks.push((self.program.len(), *location));
+                // Backpatched just like loops
+                Ok(Instruction::ProcedureStart(0))
             }
+            Token {
+                char: ')',
+                location: Location { line, column },
+                ..
+            } => match self.close_block() {
+                Some((target, Instruction::ProcedureStart(_))) => {
+                    self.program[target] = Instruction::ProcedureStart(self.program.len() + 1);
+                    Ok(Instruction::ProcedureEnd(target + 1))
+                }
+                _ => Err(anyhow!(
+                    "Could not find corresponding procedure start for ) at {line}:{column}"
+                )),
+            },
+            Token { char: ':', .. } => Ok(Instruction::Call),
+            Token { char: 'Y', .. } => Ok(Instruction::Fork),
             _ => unreachable!("No other token than the defined set is expected."),
         }
     }
 
-    fn parse_program<R: Read>(&mut self, lexer: &mut Lexer<R>) -> Result<Program> {
-        self.program = vec![];
-        self.forward_jumps = vec![];
+    /// Remove the innermost open block and return its position together
+    /// with its instruction, to check whether it is closed by the right kind
+    /// of command.
+    fn close_block(&mut self) -> Option<(usize, Instruction)> {
+        let (position, _) = self.open_blocks.pop()?;
+        Some((position, self.program[position].clone()))
+    }
+
+    fn parse_tokens<R: Read>(&mut self, lexer: &mut Lexer<R>) -> Result<()> {
         while let Some(token) = lexer.chop()? {
-            let instruction = self.parse_instruction(lexer, &token)?;
+            let instruction = self.parse_instruction(&token)?;
             self.program.push(instruction);
+            self.spans.push(token.span());
+        }
+        Ok(())
+    }
+
+    /// Parse the tokens of the lexer, appending them to the instructions
+    /// parsed by previous calls. The program is only returned once all loops
+    /// have been closed, otherwise more input is required.
+    fn parse_partial<R: Read>(&mut self, lexer: &mut Lexer<R>) -> Result<Option<Program>> {
+        if let Err(e) = self.parse_tokens(lexer) {
+            self.reset();
+            return Err(e);
+        }
+        if !self.open_blocks.is_empty() {
+            return Ok(None);
+        }
+        let (program, _) = optimizer::optimize(&self.program, &self.spans);
+        self.reset();
+        Ok(Some(program))
+    }
+
+    fn reset(&mut self) {
+        self.program = vec![];
+        self.open_blocks = vec![];
+        self.spans = vec![];
+    }
+
+    /// Parse a complete program and optimize it. Afterwards `spans` refers to
+    /// the optimized program.
+    fn parse_program<R: Read>(&mut self, lexer: &mut Lexer<R>) -> Result<Program> {
+        let program = self.parse_unoptimized(lexer)?;
+        let (program, spans) = optimizer::optimize(&program, &self.spans);
+        self.spans = spans;
+        Ok(program)
+    }
+
+    /// Parse a complete program with one instruction per command.
+    fn parse_unoptimized<R: Read>(&mut self, lexer: &mut Lexer<R>) -> Result<Program> {
+        self.reset();
+        self.parse_tokens(lexer)?;
+        if let Some(&(position, Location { line, column })) = self.open_blocks.last() {
+            return Err(match self.program[position] {
+                Instruction::ProcedureStart(_) => {
+                    anyhow!("Could not find corresponding procedure end for ( at {line}:{column}")
+                }
+                _ => anyhow!("Could not find corresponding backward jump for [ at {line}:{column}"),
+            });
         }
         Ok(self.program.clone())
     }
 }
-struct Intepreter {
-    program: Program,
-    memory: Vec<u8>,
+
+/// State of a single thread of execution. Programs only ever have more than
+/// one thread if they fork using the Brainfork extension.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Thread {
     addr: usize,
     instruction_ptr: usize,
+    /// Return addresses of the procedures currently running
+    call_stack: Vec<usize>,
+}
+
+struct Intepreter {
+    program: Program,
+    /// Cells of the tape, shared by all threads
+    memory: Vec<AtomicU32>,
+    mask: u32,
+    eof: EofBehavior,
+    cell_width: CellWidth,
+    os_threads: bool,
+    /// Threads in the order they are scheduled in, starting with the main
+    /// thread
+    threads: Vec<Thread>,
+    /// Start of every procedure defined so far, by number
+    procedures: RwLock<HashMap<u32, usize>>,
 }
 
 impl Intepreter {
-    fn new(program: Program) -> Self {
+    /// Maximal nesting depth of procedure calls, to stop runaway recursion.
+    const MAX_CALL_DEPTH: usize = 1 << 20;
+
+    fn new(program: Program, config: &ExecutionConfig) -> Self {
         Self {
             program,
-            // @TODO: allocate dynamically
-            memory: vec![0; 640000],
-            addr: 0,
-            instruction_ptr: 0,
+            memory: (0..config.tape_size).map(|_| AtomicU32::new(0)).collect(),
+            mask: config.cell_width.mask(),
+            eof: config.eof,
+            cell_width: config.cell_width,
+            os_threads: config.os_threads,
+            threads: vec![Thread::default()],
+            procedures: RwLock::default(),
         }
     }
 
-    fn run(&mut self) -> Result<()> {
-        while self.instruction_ptr < self.program.len() {
-            match self.program[self.instruction_ptr] {
-                Instruction::AddrRight(count) => {
-                    self.addr += count;
-                    self.instruction_ptr += 1;
-                }
-                Instruction::AddrLeft(count) => {
-                    self.addr -= count;
-                    self.instruction_ptr += 1;
+    /// Position of the pointer of the main thread.
+    fn addr(&self) -> usize {
+        self.threads[0].addr
+    }
+
+    fn cell(&self, addr: usize) -> u32 {
+        self.memory[addr].load(Ordering::Relaxed)
+    }
+
+    /// Replace the program to execute, while keeping the state of the tape.
+    /// Procedures are forgotten, as they belong to the previous program.
+    fn load(&mut self, program: Program) {
+        self.program = program;
+        self.threads.truncate(1);
+        self.threads[0].instruction_ptr = 0;
+        self.threads[0].call_stack.clear();
+        self.procedures_mut().clear();
+    }
+
+    /// State of the tape, the threads and the procedures defined so far.
+    fn state(&self) -> MachineState {
+        let mut procedures = self
+            .procedures
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .iter()
+            .map(|(&number, &start)| (number, start))
+            .collect::<Vec<_>>();
+        procedures.sort_unstable();
+        MachineState {
+            cells: (0..self.memory.len()).map(|addr| self.cell(addr)).collect(),
+            threads: self.threads.clone(),
+            procedures,
+        }
+    }
+
+    /// Continue from the given state, replacing the current one.
+    fn restore(&mut self, state: MachineState) {
+        self.memory = state.cells.into_iter().map(AtomicU32::new).collect();
+        self.threads = state.threads;
+        *self.procedures_mut() = state.procedures.into_iter().collect();
+    }
+
+    fn procedures_mut(&self) -> RwLockWriteGuard<'_, HashMap<u32, usize>> {
+        // The table stays consistent even if another thread panicked
+        self.procedures
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn run(&mut self, input: &mut dyn Read, output: &mut dyn Write) -> Result<()> {
+        self.run_loops(u64::MAX, input, output).map(|_| ())
+    }
+
+    /// Run until the program ends or is about to start another loop iteration
+    /// after the given number of them. The state of a paused program is kept,
+    /// so running it again continues where it stopped.
+    fn run_loops(
+        &mut self,
+        iterations: u64,
+        input: &mut dyn Read,
+        output: &mut dyn Write,
+    ) -> Result<RunState> {
+        let mut threads = std::mem::take(&mut self.threads);
+        let result = if self.os_threads {
+            self.run_os_threads(&mut threads[0], input, output)
+                .map(|_| RunState::Finished)
+        } else if iterations == u64::MAX {
+            // Not counting at all keeps the interpreter fast for plain runs
+            self.run_round_robin::<false>(&mut threads, iterations, input, output)
+        } else {
+            self.run_round_robin::<true>(&mut threads, iterations, input, output)
+        };
+        // Only the main thread survives the end of the program
+        if !matches!(result, Ok(RunState::Paused)) {
+            threads.truncate(1);
+        }
+        self.threads = threads;
+        result
+    }
+
+    /// Deterministic scheduling, in which all running threads execute a single
+    /// instruction in turn, in the order they have been forked in.
+    ///
+    /// If loops are counted, every executed `]` is a loop iteration. Multiple
+    /// threads are only paused in between turns, once they ran out of
+    /// iterations.
+    fn run_round_robin<const COUNT_LOOPS: bool>(
+        &self,
+        threads: &mut Vec<Thread>,
+        mut iterations: u64,
+        input: &mut dyn Read,
+        output: &mut dyn Write,
+    ) -> Result<RunState> {
+        let end = self.program.len();
+        loop {
+            let running = threads
+                .iter()
+                .filter(|thread| thread.instruction_ptr < end)
+                .count();
+            if running == 0 {
+                return Ok(RunState::Finished);
+            }
+            if COUNT_LOOPS && running > 1 && iterations == 0 {
+                return Ok(RunState::Paused);
+            }
+
+            let mut forked = vec![];
+            for thread in threads
+                .iter_mut()
+                .filter(|thread| thread.instruction_ptr < end)
+            {
+                if running > 1 {
+                    if COUNT_LOOPS && self.is_loop_end(thread) {
+                        iterations = iterations.saturating_sub(1);
+                    }
+                    forked.extend(self.step(thread, input, output)?);
+                    continue;
                 }
-                Instruction::Inc(count) => {
-                    self.memory[self.addr] = self.memory[self.addr].wrapping_add(count);
-                    self.instruction_ptr += 1;
+                // A thread running on its own continues until it forks
+                while thread.instruction_ptr < end {
+                    if COUNT_LOOPS && self.is_loop_end(thread) {
+                        if iterations == 0 {
+                            return Ok(RunState::Paused);
+                        }
+                        iterations -= 1;
+                    }
+                    if let Some(child) = self.step(thread, input, output)? {
+                        forked.push(child);
+                        break;
+                    }
                 }
-                Instruction::Dec(count) => {
-                    self.memory[self.addr] = self.memory[self.addr].wrapping_sub(count);
-                    self.instruction_ptr += 1;
+            }
+            threads.extend(forked);
+
+            // Drop finished threads, except for the main thread
+            let mut index = 0;
+            threads.retain(|thread| {
+                index += 1;
+                index == 1 || thread.instruction_ptr < end
+            });
+        }
+    }
+
+    /// Run the program from the start with the same scheduling as
+    /// `run_round_robin`, but a single instruction at a time. After every
+    /// instruction `observe` is called with the number of the thread, in the
+    /// order the threads have been forked in, the position of the instruction
+    /// and the thread afterwards.
+    fn run_observed(
+        &self,
+        input: &mut dyn Read,
+        output: &mut dyn Write,
+        mut observe: impl FnMut(usize, usize, &Thread) -> Result<()>,
+    ) -> Result<()> {
+        let end = self.program.len();
+        let mut threads = vec![(0, Thread::default())];
+        let mut forks = 0;
+        loop {
+            threads.retain(|(_, thread)| thread.instruction_ptr < end);
+            if threads.is_empty() {
+                return Ok(());
+            }
+
+            let mut forked = vec![];
+            for (number, thread) in &mut threads {
+                let instruction_ptr = thread.instruction_ptr;
+                let child = self.step(thread, input, output)?;
+                observe(*number, instruction_ptr, thread)?;
+                if let Some(child) = child {
+                    forks += 1;
+                    forked.push((forks, child));
                 }
-                Instruction::Output(count) => {
-                    let mut stdout = std::io::stdout();
-                    for _ in 0..count {
-                        stdout
-                            .write(&self.memory[self.addr..self.addr + 1])
-                            .context("writing data to stdout")?;
+            }
+            threads.extend(forked);
+        }
+    }
+
+    /// Whether the next instruction of the thread is the end of a loop.
+    fn is_loop_end(&self, thread: &Thread) -> bool {
+        matches!(
+            self.program[thread.instruction_ptr],
+            Instruction::JmpBack(_)
+        )
+    }
+
+    /// Run every thread on a thread of the operating system. All I/O is
+    /// performed by the calling thread on behalf of the others.
+    fn run_os_threads(
+        &self,
+        main: &mut Thread,
+        input: &mut dyn Read,
+        output: &mut dyn Write,
+    ) -> Result<()> {
+        let (sender, receiver) = mpsc::channel();
+        let stop = AtomicBool::new(false);
+        thread::scope(|scope| {
+            let main_thread = std::mem::take(main);
+            let handle = {
+                let sender = sender.clone();
+                let stop = &stop;
+                scope.spawn(move || self.run_os_thread(scope, main_thread, sender, stop))
+            };
+            // The requests end once all threads are done and dropped their
+            // senders.
+            drop(sender);
+
+            let mut error = None;
+            for request in receiver {
+                let result = match request {
+                    threads::Request::Output(bytes) => output
+                        .write_all(&bytes)
+                        .and_then(|_| output.flush())
+                        .context("writing data to output"),
+                    threads::Request::Input(reply) => {
+                        // The thread might have been stopped in the meantime
+                        let _ = reply.send(read_byte(input));
+                        Ok(())
                     }
-                    stdout.flush().context("flush stdout")?;
-                    self.instruction_ptr += 1;
+                    threads::Request::Error(e) => Err(e),
+                };
+                if let Err(e) = result {
+                    stop.store(true, Ordering::Relaxed);
+                    error.get_or_insert(e);
                 }
-                Instruction::Input(_) => todo!(),
-                Instruction::JmpForward(target) => {
-                    if self.memory[self.addr] == 0 {
-                        self.instruction_ptr = target;
-                    } else {
-                        self.instruction_ptr += 1;
-                    }
+            }
+
+            *main = handle
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+            error.map_or(Ok(()), Err)
+        })
+    }
+
+    fn run_os_thread<'scope, 'env>(
+        &'env self,
+        scope: &'scope thread::Scope<'scope, 'env>,
+        mut thread: Thread,
+        sender: mpsc::Sender<threads::Request>,
+        stop: &'env AtomicBool,
+    ) -> Thread {
+        let mut input = threads::ChannelReader::new(sender.clone());
+        let mut output = threads::ChannelWriter::new(sender.clone());
+        while thread.instruction_ptr < self.program.len() && !stop.load(Ordering::Relaxed) {
+            match self.step(&mut thread, &mut input, &mut output) {
+                Ok(Some(child)) => {
+                    let sender = sender.clone();
+                    scope.spawn(move || self.run_os_thread(scope, child, sender, stop));
                 }
-                Instruction::JmpBack(target) => {
-                    if self.memory[self.addr] != 0 {
-                        self.instruction_ptr = target;
-                    } else {
-                        self.instruction_ptr += 1;
-                    }
+                Ok(None) => {}
+                Err(e) => {
+                    // The receiving end only vanishes once all threads are
+                    // done
+                    let _ = sender.send(threads::Request::Error(e));
+                    break;
                 }
             }
         }
-        Ok(())
+        thread
+    }
+
+    /// Execute the next instruction of the thread. Returns the thread forked
+    /// by the instruction, if any.
+    ///
+    /// Instructions which fail leave the thread unchanged, so the pointer
+    /// always stays on the tape.
+    ///
+    /// Cells are not updated atomically, so concurrent changes of the same
+    /// cell by threads of the operating system might get lost.
+    #[inline(always)]
+    fn step(
+        &self,
+        thread: &mut Thread,
+        input: &mut dyn Read,
+        output: &mut dyn Write,
+    ) -> Result<Option<Thread>> {
+        let cell = &self.memory[thread.addr];
+        match self.program[thread.instruction_ptr] {
+            Instruction::AddrRight(count) => {
+                let addr = thread.addr.saturating_add(count);
+                if addr >= self.memory.len() {
+                    return Err(anyhow!(
+                        "Pointer moved beyond the end of the tape to cell {addr}"
+                    ));
+                }
+                thread.addr = addr;
+                thread.instruction_ptr += 1;
+            }
+            Instruction::AddrLeft(count) => {
+                let addr = thread
+                    .addr
+                    .checked_sub(count)
+                    .ok_or_else(|| anyhow!("Pointer moved before the start of the tape"))?;
+                thread.addr = addr;
+                thread.instruction_ptr += 1;
+            }
+            Instruction::Inc(count) => {
+                let value = cell.load(Ordering::Relaxed).wrapping_add(count.into()) & self.mask;
+                cell.store(value, Ordering::Relaxed);
+                thread.instruction_ptr += 1;
+            }
+            Instruction::Dec(count) => {
+                let value = cell.load(Ordering::Relaxed).wrapping_sub(count.into()) & self.mask;
+                cell.store(value, Ordering::Relaxed);
+                thread.instruction_ptr += 1;
+            }
+            Instruction::Output(count) => {
+                for _ in 0..count {
+                    output
+                        .write_all(&[cell.load(Ordering::Relaxed) as u8])
+                        .context("writing data to output")?;
+                }
+                output.flush().context("flush output")?;
+                thread.instruction_ptr += 1;
+            }
+            Instruction::Input(count) => {
+                for _ in 0..count {
+                    let value = match read_byte(input)? {
+                        Some(value) => value.into(),
+                        None => self
+                            .eof
+                            .apply(cell.load(Ordering::Relaxed), self.cell_width),
+                    };
+                    cell.store(value, Ordering::Relaxed);
+                }
+                thread.instruction_ptr += 1;
+            }
+            Instruction::JmpForward(target) => {
+                if cell.load(Ordering::Relaxed) == 0 {
+                    thread.instruction_ptr = target;
+                } else {
+                    thread.instruction_ptr += 1;
+                }
+            }
+            Instruction::JmpBack(target) => {
+                if cell.load(Ordering::Relaxed) != 0 {
+                    thread.instruction_ptr = target;
+                } else {
+                    thread.instruction_ptr += 1;
+                }
+            }
+            Instruction::ProcedureStart(target) => {
+                self.procedures_mut()
+                    .insert(cell.load(Ordering::Relaxed), thread.instruction_ptr + 1);
+                thread.instruction_ptr = target;
+            }
+            Instruction::ProcedureEnd(_) => {
+                thread.instruction_ptr = thread
+                    .call_stack
+                    .pop()
+                    .ok_or_else(|| anyhow!("End of procedure reached without a call"))?;
+            }
+            Instruction::Call => {
+                let number = cell.load(Ordering::Relaxed);
+                let start = *self
+                    .procedures
+                    .read()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .get(&number)
+                    .ok_or_else(|| anyhow!("Call of undefined procedure {number}"))?;
+                if thread.call_stack.len() >= Self::MAX_CALL_DEPTH {
+                    return Err(anyhow!(
+                        "Procedure calls nested deeper than {}",
+                        Self::MAX_CALL_DEPTH
+                    ));
+                }
+                thread.call_stack.push(thread.instruction_ptr + 1);
+                thread.instruction_ptr = start;
+            }
+            Instruction::Fork => {
+                // The current cell of the parent is cleared, while the child
+                // starts on the next cell set to 1.
+                let child_addr = thread.addr + 1;
+                if child_addr >= self.memory.len() {
+                    return Err(anyhow!(
+                        "Pointer of forked thread beyond the end of the tape at cell {child_addr}"
+                    ));
+                }
+                cell.store(0, Ordering::Relaxed);
+                self.memory[child_addr].store(1, Ordering::Relaxed);
+                thread.instruction_ptr += 1;
+                return Ok(Some(Thread {
+                    addr: child_addr,
+                    instruction_ptr: thread.instruction_ptr,
+                    call_stack: thread.call_stack.clone(),
+                }));
+            }
+        }
+        Ok(None)
     }
 }
 
-#[cfg(not(all(target_arch = "x86_64", target_os = "linux")))]
-fn execute(program: Program) -> Result<()> {
-    println!("Using iterpreter for execution");
-    let mut intepreter = Intepreter::new(program);
-    intepreter.run()
+fn load_program(
+    source: &ProgramSource,
+    dialect: &Rc<Dialect>,
+    config: &mut ExecutionConfig,
+) -> Result<Program> {
+    let code = source.read()?;
+    if bytecode::is_bytecode(&code) {
+        let (header, program) = bytecode::read_program(&mut &code[..])
+            .with_context(|| format!("load bytecode from {source}"))?;
+        if config.cell_width_given && config.cell_width != header.cell_width {
+            return Err(anyhow!(
+                "{source} has been built for {} bit cells, but {} bit cells are requested",
+                header.cell_width.bits(),
+                config.cell_width.bits()
+            ));
+        }
+        config.cell_width = header.cell_width;
+        return Ok(program);
+    }
+
+    let mut lexer = Lexer::new(&code[..], Rc::clone(dialect));
+    let mut parser = Parser::default();
+    parser.parse_program(&mut lexer)
 }
-#[cfg(all(target_arch = "x86_64", target_os = "linux"))]
-fn execute(program: Program) -> Result<()> {
-    println!("Using JIT for execution");
-    let mut jit_compiler = JitCompiler::new(program, X86Assembler::default());
-    jit_compiler.compile()?;
-    jit_compiler.run();
-    Ok(())
+
+/// Read the brainfuck source code of the given source, rejecting bytecode.
+fn read_source(source: &ProgramSource) -> Result<Vec<u8>> {
+    let code = source.read()?;
+    if bytecode::is_bytecode(&code) {
+        return Err(anyhow!(
+            "{source} is bytecode, brainfuck source is required"
+        ));
+    }
+    Ok(code)
 }
 
 fn main() -> Result<()> {
@@ -271,20 +905,251 @@ fn main() -> Result<()> {
     let (command, args) = args
         .split_first()
         .expect("expected to have at least the command in the args array");
-    if args.is_empty() {
-        eprintln!("Usage:");
-        eprintln!("  {command} <brainfuck_file>");
-        process::exit(1);
-    }
-
-    let input = &args[0];
-    println!("Opening brainfuck file {input} for execution");
-    let reader = BufReader::new(
-        File::open(input).with_context(|| format!("open file {input} for reading"))?,
-    );
-    let mut lexer = Lexer::new(reader);
-    let mut parser = Parser::default();
-    let program = parser.parse_program(&mut lexer)?;
-    execute(program)?;
+
+    let cli = cli::parse(args.to_vec())?;
+    match cli.command {
+        Command::Help { requested: true } => println!("{}", cli::usage(command)),
+        Command::Help { requested: false } => {
+            eprintln!("{}", cli::usage(command));
+            process::exit(1);
+        }
+        Command::Repl(config) => repl::run(&config, &cli.dialect)?,
+        Command::Debug(options) => debugger::run(&options, &cli.dialect)?,
+        Command::Replay(options) => trace::replay(&options)?,
+        Command::Coverage(options) => coverage::run(&options, &cli.dialect, cli.verbose)?,
+        Command::Lsp => lsp::run(&cli.dialect)?,
+        Command::Run(mut options) => {
+            if cli.verbose {
+                eprintln!(
+                    "Opening {} for execution as {}",
+                    options.source, cli.dialect.name
+                );
+            }
+            if options.trace.is_some() || options.visualize {
+                // Both refer to the source, so bytecode is not supported
+                let code = read_source(&options.source)?;
+                let mut parser = Parser::default();
+                let program =
+                    parser.parse_program(&mut Lexer::new(&code[..], Rc::clone(&cli.dialect)))?;
+                let mut output = std::io::stdout().lock();
+                if options.visualize {
+                    visualizer::run(program, parser.spans, &code, &options, &mut output)?;
+                } else {
+                    if cli.verbose {
+                        eprintln!("Recording trace using the interpreter");
+                    }
+                    trace::record(
+                        program,
+                        &parser.spans,
+                        &options,
+                        &mut options.input.open()?,
+                        &mut output,
+                        cli.verbose,
+                    )?;
+                }
+                return Ok(());
+            }
+            let program = load_program(&options.source, &cli.dialect, &mut options.config)?;
+            let resume = match &options.resume {
+                Some(path) => {
+                    let snapshot = Snapshot::load(path)?;
+                    snapshot.configure(&mut options.config);
+                    snapshot.check(&program)?;
+                    Some(snapshot)
+                }
+                None => None,
+            };
+            options.config.snapshots = resume.is_some() || options.snapshot.is_some();
+            let backend = select_backend(options.backend.as_deref(), &program, &options.config)?;
+            if cli.verbose {
+                eprintln!("Using {} for execution", backend.name());
+            }
+            let mut input = options.input.open()?;
+            let mut output = std::io::stdout().lock();
+            if options.config.snapshots {
+                snapshot::run(
+                    backend.as_ref(),
+                    program,
+                    &options,
+                    resume,
+                    &mut input,
+                    &mut output,
+                    cli.verbose,
+                )?;
+            } else {
+                backend.execute(program, &options.config, &mut input, &mut output)?;
+            }
+        }
+        Command::Build(mut options) => {
+            if cli.verbose {
+                eprintln!("Compiling {} to {}", options.source, options.output);
+            }
+            let program = load_program(&options.source, &cli.dialect, &mut options.config)?;
+            let header = BytecodeHeader {
+                cell_width: options.config.cell_width,
+                optimization_level: optimizer::OPTIMIZATION_LEVEL,
+            };
+            let output = &options.output;
+            let mut writer = BufWriter::new(
+                File::create(output).with_context(|| format!("create bytecode file {output}"))?,
+            );
+            bytecode::write_program(&mut writer, &header, &program)
+                .with_context(|| format!("write bytecode to {output}"))?;
+        }
+        Command::Transpile(mut options) => {
+            if cli.verbose {
+                eprintln!("Transpiling {} to {:?}", options.source, options.target);
+            }
+            let program = load_program(&options.source, &cli.dialect, &mut options.config)?;
+            let source_name = options.source.to_string();
+            let code = match options.target {
+                Target::Rust => transpiler::to_rust(
+                    &program,
+                    &options.config,
+                    &TranspileOptions {
+                        library: options.library,
+                        source_name,
+                    },
+                )?,
+                Target::LlvmIr if options.library => {
+                    return Err(anyhow!("--lib is only supported for the rust target"))
+                }
+                Target::LlvmIr => llvm_ir::to_llvm_ir(&program, &options.config, &source_name)?,
+            };
+            match &options.output {
+                Some(output) => fs::write(output, code)
+                    .with_context(|| format!("write translated code to {output}"))?,
+                None => print!("{code}"),
+            }
+        }
+        Command::Check(options) => {
+            let code = read_source(&options.source)?;
+            let mut parser = Parser::default();
+            let program =
+                parser.parse_program(&mut Lexer::new(&code[..], Rc::clone(&cli.dialect)))?;
+            let warnings = linter::lint(&program, &parser.spans);
+            if options.json {
+                println!("{}", linter::to_json(&warnings));
+            } else {
+                for warning in &warnings {
+                    println!("{warning}");
+                }
+            }
+            if cli.verbose {
+                eprintln!("{} warnings found in {}", warnings.len(), options.source);
+            }
+        }
+        Command::Fmt(options) => {
+            let code = read_source(&options.source)?;
+            // Only well formed programs are formatted
+            Parser::default().parse_program(&mut Lexer::new(&code[..], Rc::clone(&cli.dialect)))?;
+
+            let mut lexer = Lexer::new(&code[..], Rc::clone(&cli.dialect));
+            let dialect = options.to.as_ref().unwrap_or(&cli.dialect);
+            if options.minify {
+                print!("{}", formatter::minify_source(&mut lexer, dialect)?);
+            } else {
+                print!(
+                    "{}",
+                    formatter::format_source(&mut lexer, dialect, options.format)?
+                );
+            }
+        }
+        Command::Stats(source) => {
+            let program = load_program(&source, &cli.dialect, &mut ExecutionConfig::default())?;
+            println!("{}", ProgramStats::collect(&program));
+        }
+        Command::Conformance(backends) => conformance::run(&backends, cli.verbose)?,
+        Command::Bench(options) => bench::run(&options, &cli.dialect, cli.verbose)?,
+        Command::Gen(input) => {
+            let mut text = vec![];
+            input
+                .open()?
+                .read_to_end(&mut text)
+                .context("read text to generate a program for")?;
+            println!("{}", generator::generate(&text)?);
+        }
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    fn parse(code: &str, dialect: &str) -> Program {
+        let dialect = Rc::new(Dialect::load(dialect).unwrap());
+        Parser::default()
+            .parse_program(&mut Lexer::new(code.as_bytes(), dialect))
+            .unwrap()
+    }
+
+    #[test]
+    fn failing_moves_keep_the_pointer_on_the_tape() {
+        let config = ExecutionConfig {
+            tape_size: 4,
+            ..ExecutionConfig::default()
+        };
+        let mut intepreter = Intepreter::new(parse(">>>>>", "brainfuck"), &config);
+        assert!(intepreter.run(&mut io::empty(), &mut io::sink()).is_err());
+        assert_eq!(intepreter.addr(), 0);
+
+        intepreter.load(parse(">>><<<<", "brainfuck"));
+        assert!(intepreter.run(&mut io::empty(), &mut io::sink()).is_err());
+        assert_eq!(intepreter.addr(), 3);
+
+        // The tape is still usable afterwards
+        intepreter.load(parse("+", "brainfuck"));
+        intepreter.run(&mut io::empty(), &mut io::sink()).unwrap();
+        assert_eq!(intepreter.cell(3), 1);
+    }
+
+    #[test]
+    fn bytecode_keeps_a_requested_cell_width() {
+        let mut bytecode = vec![];
+        let header = bytecode::BytecodeHeader {
+            cell_width: CellWidth::U16,
+            optimization_level: optimizer::OPTIMIZATION_LEVEL,
+        };
+        bytecode::write_program(&mut bytecode, &header, &parse("-.", "brainfuck")).unwrap();
+        let source = ProgramSource::Inline(String::from_utf8(bytecode).unwrap());
+        let dialect = Rc::new(Dialect::default());
+
+        let mut config = ExecutionConfig::default();
+        load_program(&source, &dialect, &mut config).unwrap();
+        assert_eq!(config.cell_width, CellWidth::U16);
+
+        let mut config = ExecutionConfig {
+            cell_width: CellWidth::U8,
+            cell_width_given: true,
+            ..ExecutionConfig::default()
+        };
+        assert!(load_program(&source, &dialect, &mut config).is_err());
+        config.cell_width = CellWidth::U16;
+        load_program(&source, &dialect, &mut config).unwrap();
+    }
+
+    #[test]
+    fn forked_threads_run_in_turns() {
+        // The parent prints 1 to 3 from its cell, the child 2 to 4 from the
+        // next one. Taking turns, they print one byte each in fork order.
+        let program = parse("Y+.+.+.", "brainfork");
+        let expected = [1, 2, 2, 3, 3, 4];
+
+        let mut output = vec![];
+        Intepreter::new(program.clone(), &ExecutionConfig::default())
+            .run(&mut io::empty(), &mut output)
+            .unwrap();
+        assert_eq!(output, expected);
+
+        // Counting loops keeps the same order
+        let mut output = vec![];
+        let state = Intepreter::new(program, &ExecutionConfig::default())
+            .run_loops(1000, &mut io::empty(), &mut output)
+            .unwrap();
+        assert!(matches!(state, RunState::Finished));
+        assert_eq!(output, expected);
+    }
+}
@@ -0,0 +1,65 @@
+use crate::{Instruction, Program, Span};
+
+/// Level of optimization applied to parsed programs. Level 1 folds runs of
+/// identical commands into a single instruction.
+pub const OPTIMIZATION_LEVEL: u8 = 1;
+
+/// Optimize a freshly parsed program, returning it together with the source
+/// span of every instruction, covering all commands it has been made of.
+pub fn optimize(program: &Program, spans: &[Span]) -> (Program, Vec<Span>) {
+    fold_runs(program, spans)
+}
+
+/// Combine two instructions of a run into one, if they are of the same kind.
+/// Runs of `+` and `-` longer than the largest count of an instruction are
+/// split into several instructions.
+fn fold(previous: &Instruction, next: &Instruction) -> Option<Instruction> {
+    use Instruction::*;
+    match (previous, next) {
+        (AddrRight(a), AddrRight(b)) => Some(AddrRight(a + b)),
+        (AddrLeft(a), AddrLeft(b)) => Some(AddrLeft(a + b)),
+        (Inc(a), Inc(b)) => a.checked_add(*b).map(Inc),
+        (Dec(a), Dec(b)) => a.checked_add(*b).map(Dec),
+        (Output(a), Output(b)) => Some(Output(a + b)),
+        (Input(a), Input(b)) => Some(Input(a + b)),
+        _ => None,
+    }
+}
+
+/// Fold runs of identical commands into a single instruction and move jump
+/// targets accordingly.
+fn fold_runs(program: &Program, spans: &[Span]) -> (Program, Vec<Span>) {
+    let mut folded: Program = Vec::with_capacity(program.len());
+    let mut folded_spans: Vec<Span> = Vec::with_capacity(program.len());
+    // Index of the folded instruction every instruction ended up in
+    let mut positions = Vec::with_capacity(program.len() + 1);
+    for (position, instruction) in program.iter().enumerate() {
+        // The last folded instruction always contains the previous one
+        match folded
+            .last()
+            .and_then(|previous| fold(previous, instruction))
+        {
+            Some(combined) => {
+                *folded.last_mut().expect("a previous instruction") = combined;
+                folded_spans.last_mut().expect("a previous span").end = spans[position].end;
+            }
+            None => {
+                folded.push(instruction.clone());
+                folded_spans.push(spans[position]);
+            }
+        }
+        positions.push(folded.len() - 1);
+    }
+    positions.push(folded.len());
+
+    for instruction in &mut folded {
+        match instruction {
+            Instruction::JmpForward(target)
+            | Instruction::JmpBack(target)
+            | Instruction::ProcedureStart(target)
+            | Instruction::ProcedureEnd(target) => *target = positions[*target],
+            _ => {}
+        }
+    }
+    (folded, folded_spans)
+}
@@ -0,0 +1,124 @@
+use std::io::{self, BufRead, Write};
+use std::rc::Rc;
+
+use crate::backend::ExecutionConfig;
+use crate::dialect::Dialect;
+use crate::{Intepreter, Lexer, Parser, Program};
+use anyhow::{Context, Result};
+
+/// Number of cells shown on each side of the current one.
+const TAPE_WINDOW: usize = 4;
+
+/// Writer keeping track of the last written byte, to be able to tell whether
+/// the program output ended with a newline.
+struct TrackingWriter<W: Write> {
+    inner: W,
+    last_byte: Option<u8>,
+}
+
+impl<W: Write> Write for TrackingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        if written > 0 {
+            self.last_byte = Some(buf[written - 1]);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Print the cells around the pointer, marking the current one.
+pub fn print_tape(intepreter: &Intepreter) {
+    let start = intepreter.addr().saturating_sub(TAPE_WINDOW);
+    let end = (intepreter.addr() + TAPE_WINDOW + 1).min(intepreter.memory.len());
+
+    let mut cells = String::from("cell: ");
+    let mut values = String::from("value:");
+    let mut marker = String::from("      ");
+    for addr in start..end {
+        cells.push_str(&format!(" {addr:>6}"));
+        values.push_str(&format!(" {:>6}", intepreter.cell(addr)));
+        marker.push_str(if addr == intepreter.addr() {
+            "      ^"
+        } else {
+            "       "
+        });
+    }
+    println!("{cells}");
+    println!("{values}");
+    println!("{}", marker.trim_end());
+}
+
+fn prompt(text: &str) -> Result<()> {
+    print!("{text}");
+    io::stdout().flush().context("flush prompt")
+}
+
+/// Read brainfuck snippets line by line and execute each of them on the same
+/// tape, until EOF or `:quit` is reached.
+///
+/// Lines starting with `:` are commands to the REPL itself:
+/// `:reset` clears the tape, `:quit` leaves the REPL.
+pub fn run(config: &ExecutionConfig, dialect: &Rc<Dialect>) -> Result<()> {
+    let mut intepreter = Intepreter::new(Program::new(), config);
+    let mut parser = Parser::default();
+    let stdin = io::stdin();
+
+    println!("rust_brain repl, enter :quit to exit");
+    prompt("bf> ")?;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if stdin.lock().read_line(&mut line).context("read line")? == 0 {
+            println!();
+            return Ok(());
+        }
+
+        match line.trim() {
+            ":quit" => return Ok(()),
+            ":reset" => {
+                intepreter = Intepreter::new(Program::new(), config);
+                parser.reset();
+                print_tape(&intepreter);
+                prompt("bf> ")?;
+                continue;
+            }
+            _ => {}
+        }
+
+        let mut lexer = Lexer::new(line.as_bytes(), Rc::clone(dialect));
+        let program = match parser.parse_partial(&mut lexer) {
+            Ok(Some(program)) => program,
+            Ok(None) => {
+                // Loops are still open, wait for the rest of the snippet
+                prompt("... ")?;
+                continue;
+            }
+            Err(e) => {
+                eprintln!("Error: {e}");
+                prompt("bf> ")?;
+                continue;
+            }
+        };
+
+        intepreter.load(program);
+        let mut output = TrackingWriter {
+            inner: io::stdout().lock(),
+            last_byte: None,
+        };
+        let result = intepreter.run(&mut io::stdin(), &mut output);
+        if output.last_byte.is_some_and(|byte| byte != b'\n') {
+            println!();
+        }
+        drop(output);
+        if let Err(e) = result {
+            eprintln!("Error: {e}");
+        }
+
+        print_tape(&intepreter);
+        prompt("bf> ")?;
+    }
+}
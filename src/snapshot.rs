@@ -0,0 +1,568 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::process;
+
+use crate::backend::{CellWidth, EofBehavior, ExecutionBackend, ExecutionConfig, RunState};
+use crate::bytecode::{
+    self, fnv1a, read_bytes, read_u8, read_varint, write_varint, BytecodeHeader,
+};
+use crate::cli::RunOptions;
+use crate::optimizer::OPTIMIZATION_LEVEL;
+use crate::{Instruction, Program, Thread};
+use anyhow::{anyhow, Context, Result};
+
+/// Magic bytes every snapshot file starts with.
+pub const MAGIC: &[u8; 4] = b"RBSN";
+/// Version of the snapshot format. Needs to be increased on every
+/// incompatible change.
+pub const VERSION: u8 = 1;
+
+/// State of a program paused by a backend.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MachineState {
+    /// Every cell of the tape
+    pub cells: Vec<u32>,
+    /// Threads in the order they are scheduled in, starting with the main
+    /// thread
+    pub threads: Vec<Thread>,
+    /// Start of every procedure defined so far, ordered by number
+    pub procedures: Vec<(u32, usize)>,
+}
+
+/// Everything required to continue a paused program later, possibly on
+/// another machine.
+///
+/// Layout (all integers little endian, lengths and counts as unsigned LEB128
+/// like in bytecode):
+///
+/// ```text
+/// magic "RBSN" | version: u8 | cell width in bits: u8 | eof behavior: u8 | reserved: u8 | program hash: u64
+/// tape size | stored cells | cell...
+/// thread count | (addr | instruction pointer | call depth | return address...)...
+/// procedure count | (number | start)...
+/// pending input length | pending input
+/// ```
+///
+/// Only the cells up to the last one which is not zero are stored, the rest
+/// of the tape is empty.
+pub struct Snapshot {
+    /// Hash of the program the state belongs to, see `program_hash`
+    pub program_hash: u64,
+    pub cell_width: CellWidth,
+    pub eof: EofBehavior,
+    pub state: MachineState,
+    /// Input which has been read, but not consumed by the program yet
+    pub pending_input: Vec<u8>,
+}
+
+/// Hash identifying the program, as instruction pointers are only meaningful
+/// for exactly the same program. Programs differing in their optimization are
+/// considered different as well.
+pub fn program_hash(program: &Program, cell_width: CellWidth) -> Result<u64> {
+    let mut data = vec![];
+    bytecode::write_program(
+        &mut data,
+        &BytecodeHeader {
+            cell_width,
+            optimization_level: OPTIMIZATION_LEVEL,
+        },
+        program,
+    )?;
+    Ok(fnv1a(&data))
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32> {
+    read_varint(reader)?
+        .try_into()
+        .map_err(|_| anyhow!("value in snapshot is too large"))
+}
+
+impl Snapshot {
+    pub fn load(path: &str) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("open snapshot {path}"))?;
+        Self::read(&mut BufReader::new(file)).with_context(|| format!("load snapshot {path}"))
+    }
+
+    /// Write the snapshot to the given file. It is moved into place once
+    /// completely written, so an interrupted write keeps the previous one.
+    pub fn save(&self, path: &str) -> Result<()> {
+        let target = Path::new(path);
+        let temporary = target.with_file_name(format!(
+            ".{}.{}.tmp",
+            target
+                .file_name()
+                .ok_or_else(|| anyhow!("Invalid snapshot file {path}"))?
+                .to_string_lossy(),
+            process::id()
+        ));
+        let mut writer = BufWriter::new(
+            File::create(&temporary)
+                .with_context(|| format!("create snapshot file {}", temporary.display()))?,
+        );
+        self.write(&mut writer)
+            .with_context(|| format!("write snapshot to {}", temporary.display()))?;
+        drop(writer);
+        fs::rename(&temporary, target)
+            .with_context(|| format!("move snapshot into place at {path}"))
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let cell_width: u8 = match self.cell_width {
+            CellWidth::U8 => 8,
+            CellWidth::U16 => 16,
+            CellWidth::U32 => 32,
+        };
+        let eof: u8 = match self.eof {
+            EofBehavior::Unchanged => 0,
+            EofBehavior::Zero => 1,
+            EofBehavior::MinusOne => 2,
+        };
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION, cell_width, eof, 0])?;
+        writer.write_all(&self.program_hash.to_le_bytes())?;
+
+        let cells = &self.state.cells;
+        let used = cells
+            .iter()
+            .rposition(|&cell| cell != 0)
+            .map_or(0, |last| last + 1);
+        write_varint(writer, cells.len())?;
+        write_varint(writer, used)?;
+        for &cell in &cells[..used] {
+            write_varint(writer, cell as usize)?;
+        }
+
+        write_varint(writer, self.state.threads.len())?;
+        for thread in &self.state.threads {
+            write_varint(writer, thread.addr)?;
+            write_varint(writer, thread.instruction_ptr)?;
+            write_varint(writer, thread.call_stack.len())?;
+            for &return_address in &thread.call_stack {
+                write_varint(writer, return_address)?;
+            }
+        }
+
+        write_varint(writer, self.state.procedures.len())?;
+        for &(number, start) in &self.state.procedures {
+            write_varint(writer, number as usize)?;
+            write_varint(writer, start)?;
+        }
+
+        write_varint(writer, self.pending_input.len())?;
+        writer.write_all(&self.pending_input)?;
+        writer.flush().context("flush snapshot")?;
+
+        Ok(())
+    }
+
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut magic: [u8; 4] = [0; 4];
+        reader
+            .read_exact(&mut magic)
+            .context("reading snapshot magic")?;
+        if &magic != MAGIC {
+            return Err(anyhow!("not a rust_brain snapshot file"));
+        }
+
+        let version = read_u8(reader)?;
+        if version != VERSION {
+            return Err(anyhow!(
+                "unsupported snapshot version {version}, expected {VERSION}"
+            ));
+        }
+        let cell_width = match read_u8(reader)? {
+            8 => CellWidth::U8,
+            16 => CellWidth::U16,
+            32 => CellWidth::U32,
+            other => return Err(anyhow!("invalid cell width {other} in snapshot header")),
+        };
+        let eof = match read_u8(reader)? {
+            0 => EofBehavior::Unchanged,
+            1 => EofBehavior::Zero,
+            2 => EofBehavior::MinusOne,
+            other => return Err(anyhow!("invalid EOF behavior {other} in snapshot header")),
+        };
+        let _reserved = read_u8(reader)?;
+        let mut program_hash: [u8; 8] = [0; 8];
+        reader
+            .read_exact(&mut program_hash)
+            .context("reading snapshot program hash")?;
+
+        let tape_size = read_varint(reader)?;
+        if tape_size == 0 {
+            return Err(anyhow!("snapshot has an empty tape"));
+        }
+        let used = read_varint(reader)?;
+        if used > tape_size {
+            return Err(anyhow!("more cells stored than the tape holds"));
+        }
+        // Counts are not trusted to allocate memory, so the cells grow with
+        // the ones actually stored and the rest of the tape is only reserved
+        // once they have been read
+        let mut cells = vec![];
+        for _ in 0..used {
+            let cell = read_u32(reader)?;
+            if cell & !cell_width.mask() != 0 {
+                return Err(anyhow!("cell value {cell} exceeds the cell width"));
+            }
+            cells.push(cell);
+        }
+        cells
+            .try_reserve_exact(tape_size - used)
+            .map_err(|_| anyhow!("tape of {tape_size} cells is too large"))?;
+        cells.resize(tape_size, 0);
+
+        let mut threads = vec![];
+        for _ in 0..read_varint(reader)? {
+            let addr = read_varint(reader)?;
+            let instruction_ptr = read_varint(reader)?;
+            let call_stack = (0..read_varint(reader)?)
+                .map(|_| read_varint(reader))
+                .collect::<Result<_>>()?;
+            threads.push(Thread {
+                addr,
+                instruction_ptr,
+                call_stack,
+            });
+        }
+
+        let mut procedures = vec![];
+        for _ in 0..read_varint(reader)? {
+            procedures.push((read_u32(reader)?, read_varint(reader)?));
+        }
+
+        let length = read_varint(reader)?;
+        let pending_input =
+            read_bytes(reader, length).context("reading pending input of snapshot")?;
+
+        let mut trailing: [u8; 1] = [0; 1];
+        if reader.read(&mut trailing)? != 0 {
+            return Err(anyhow!("trailing data after snapshot"));
+        }
+
+        Ok(Self {
+            program_hash: u64::from_le_bytes(program_hash),
+            cell_width,
+            eof,
+            state: MachineState {
+                cells,
+                threads,
+                procedures,
+            },
+            pending_input,
+        })
+    }
+
+    /// Adopt the configuration the snapshot has been taken with, which takes
+    /// precedence over the given one.
+    pub fn configure(&self, config: &mut ExecutionConfig) {
+        config.cell_width = self.cell_width;
+        config.eof = self.eof;
+        config.tape_size = self.state.cells.len();
+    }
+
+    /// Ensure the snapshot has been taken of the given program and its state
+    /// is consistent with it.
+    pub fn check(&self, program: &Program) -> Result<()> {
+        if program_hash(program, self.cell_width)? != self.program_hash {
+            return Err(anyhow!("Snapshot has been taken of a different program"));
+        }
+
+        let state = &self.state;
+        if state.threads.is_empty() {
+            return Err(anyhow!("Snapshot does not contain any thread"));
+        }
+        // Positions within the body of a procedure can only be reached by a
+        // call, which leaves a return address behind the `:`
+        let bodies = program
+            .iter()
+            .enumerate()
+            .filter_map(|(position, instruction)| match instruction {
+                Instruction::ProcedureStart(end) => Some(position + 1..*end),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let in_body = |position: usize| bodies.iter().any(|body| body.contains(&position));
+        let in_program = |position: usize| position <= program.len();
+        let after_call = |position: usize| {
+            position > 0 && matches!(program.get(position - 1), Some(Instruction::Call))
+        };
+        for thread in &state.threads {
+            if thread.addr >= state.cells.len() {
+                return Err(anyhow!(
+                    "Pointer in snapshot is beyond the end of the tape at cell {}",
+                    thread.addr
+                ));
+            }
+            // Every position is reached from the frame below it, the first
+            // one from the top level
+            let positions = thread
+                .call_stack
+                .iter()
+                .chain([&thread.instruction_ptr])
+                .enumerate();
+            for (depth, &position) in positions {
+                if !in_program(position) || (depth == 0 && in_body(position)) {
+                    return Err(anyhow!("Instruction pointer in snapshot is out of range"));
+                }
+            }
+            if !thread
+                .call_stack
+                .iter()
+                .all(|&position| after_call(position))
+            {
+                return Err(anyhow!("Return address in snapshot does not follow a call"));
+            }
+        }
+        let procedure_start = |start: usize| {
+            start > 0 && matches!(program[start - 1], Instruction::ProcedureStart(_))
+        };
+        if !state
+            .procedures
+            .iter()
+            .all(|&(_, start)| in_program(start) && procedure_start(start))
+        {
+            return Err(anyhow!("Procedure in snapshot starts out of range"));
+        }
+        Ok(())
+    }
+}
+
+/// Run the program on the given backend, which has to support snapshots.
+/// Continues from the given snapshot, if any, and writes snapshots as
+/// requested by the options.
+///
+/// The input pending in the snapshot is read before the given input. If
+/// snapshots are written, the input is read completely before the program
+/// starts, so the part not consumed yet can be stored with them.
+pub fn run(
+    backend: &dyn ExecutionBackend,
+    program: Program,
+    options: &RunOptions,
+    resume: Option<Snapshot>,
+    input: &mut dyn Read,
+    output: &mut dyn Write,
+    verbose: bool,
+) -> Result<()> {
+    let config = &options.config;
+    let program_hash = program_hash(&program, config.cell_width)?;
+    let mut compiled = backend.compile(program, config)?;
+    let mut pending_input = vec![];
+    if let Some(snapshot) = resume {
+        compiled.restore(snapshot.state)?;
+        pending_input = snapshot.pending_input;
+    }
+
+    let Some(snapshot_options) = &options.snapshot else {
+        return compiled.run(&mut (&pending_input[..]).chain(input), output);
+    };
+    input
+        .read_to_end(&mut pending_input)
+        .context("reading data from input")?;
+    let mut input = io::Cursor::new(pending_input);
+
+    let mut iterations: u64 = 0;
+    loop {
+        let next_every = snapshot_options
+            .every
+            .map(|every| every - iterations % every);
+        let next_after = snapshot_options.after.map(|after| after - iterations);
+        let next = next_every
+            .into_iter()
+            .chain(next_after)
+            .min()
+            .expect("snapshots to be taken every or after some iterations");
+        if compiled.run_loops(next, &mut input, output)? == RunState::Finished {
+            return Ok(());
+        }
+        iterations += next;
+
+        let position = input.position() as usize;
+        let snapshot = Snapshot {
+            program_hash,
+            cell_width: config.cell_width,
+            eof: config.eof,
+            state: compiled.state()?,
+            pending_input: input.get_ref()[position..].to_vec(),
+        };
+        let path = &snapshot_options.path;
+        snapshot.save(path)?;
+        if snapshot_options.after == Some(iterations) {
+            eprintln!("Stopped after {iterations} loop iterations, snapshot written to {path}");
+            return Ok(());
+        }
+        if verbose {
+            eprintln!("Snapshot after {iterations} loop iterations written to {path}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::InterpreterBackend;
+    use crate::dialect::Dialect;
+    use crate::{Lexer, Parser};
+    use std::rc::Rc;
+
+    fn parse(code: &str) -> Program {
+        let dialect = Rc::new(Dialect::load("pbrain").unwrap());
+        Parser::default()
+            .parse_program(&mut Lexer::new(code.as_bytes(), dialect))
+            .unwrap()
+    }
+
+    /// Defines procedure 1 and calls it, echoing four bytes of input in a
+    /// loop within the procedure.
+    const PROGRAM: &str = "+(+++[>,.<-]):";
+
+    /// Run the program for the given number of loop iterations, returning the
+    /// output so far and the snapshot taken afterwards.
+    fn pause(program: &Program, iterations: u64, input: &[u8]) -> (Vec<u8>, Snapshot) {
+        let config = ExecutionConfig::default();
+        let mut compiled = InterpreterBackend
+            .compile(program.clone(), &config)
+            .unwrap();
+        let mut input = io::Cursor::new(input);
+        let mut output = vec![];
+        let state = compiled
+            .run_loops(iterations, &mut input, &mut output)
+            .unwrap();
+        assert_eq!(state, RunState::Paused);
+        let position = input.position() as usize;
+        let snapshot = Snapshot {
+            program_hash: program_hash(program, config.cell_width).unwrap(),
+            cell_width: config.cell_width,
+            eof: config.eof,
+            state: compiled.state().unwrap(),
+            pending_input: input.get_ref()[position..].to_vec(),
+        };
+        (output, snapshot)
+    }
+
+    fn write(snapshot: &Snapshot) -> Vec<u8> {
+        let mut data = vec![];
+        snapshot.write(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn snapshots_are_read_as_written() {
+        let snapshot = Snapshot {
+            program_hash: 0x0123456789abcdef,
+            cell_width: CellWidth::U16,
+            eof: EofBehavior::MinusOne,
+            state: MachineState {
+                cells: vec![0, 300, 0, 65535, 0, 0],
+                threads: vec![
+                    Thread {
+                        addr: 3,
+                        instruction_ptr: 7,
+                        call_stack: vec![2, 200],
+                    },
+                    Thread {
+                        addr: 4,
+                        instruction_ptr: 0,
+                        call_stack: vec![],
+                    },
+                ],
+                procedures: vec![(1, 3), (255, 9)],
+            },
+            pending_input: b"rest".to_vec(),
+        };
+        let read = Snapshot::read(&mut &write(&snapshot)[..]).unwrap();
+        assert_eq!(read.program_hash, snapshot.program_hash);
+        assert_eq!(read.cell_width, snapshot.cell_width);
+        assert_eq!(read.eof, snapshot.eof);
+        assert_eq!(read.state, snapshot.state);
+        assert_eq!(read.pending_input, snapshot.pending_input);
+    }
+
+    #[test]
+    fn resumed_programs_continue_where_they_stopped() {
+        let program = parse(PROGRAM);
+        let input = b"abcdef";
+        let mut expected = vec![];
+        InterpreterBackend
+            .execute(
+                program.clone(),
+                &ExecutionConfig::default(),
+                &mut &input[..],
+                &mut expected,
+            )
+            .unwrap();
+        assert_eq!(expected, b"abcd");
+
+        for iterations in 1..4 {
+            let (mut output, snapshot) = pause(&program, iterations, input);
+            let snapshot = Snapshot::read(&mut &write(&snapshot)[..]).unwrap();
+            snapshot.check(&program).unwrap();
+            assert_eq!(snapshot.state.threads[0].call_stack.len(), 1);
+
+            let mut config = ExecutionConfig::default();
+            snapshot.configure(&mut config);
+            let mut compiled = InterpreterBackend
+                .compile(program.clone(), &config)
+                .unwrap();
+            compiled.restore(snapshot.state).unwrap();
+            compiled
+                .run(&mut &snapshot.pending_input[..], &mut output)
+                .unwrap();
+            assert_eq!(output, expected, "paused after {iterations} iterations");
+        }
+    }
+
+    #[test]
+    fn snapshots_of_other_programs_are_rejected() {
+        let (_, snapshot) = pause(&parse(PROGRAM), 1, b"abcdef");
+        assert!(snapshot.check(&parse("+(++++[>,.<-]):")).is_err());
+    }
+
+    #[test]
+    fn inconsistent_states_are_rejected() {
+        let program = parse(PROGRAM);
+        let (_, snapshot) = pause(&program, 1, b"abcdef");
+        let check = |change: &dyn Fn(&mut Thread)| {
+            let mut state = snapshot.state.clone();
+            change(&mut state.threads[0]);
+            Snapshot {
+                state,
+                pending_input: vec![],
+                ..snapshot
+            }
+            .check(&program)
+        };
+        assert!(check(&|_| ()).is_ok());
+        // Within the procedure, but not called
+        assert!(check(&|thread| thread.call_stack.clear()).is_err());
+        // Returning into the procedure from the top level
+        assert!(check(&|thread| thread.call_stack[0] = 3).is_err());
+        assert!(check(&|thread| thread.call_stack[0] = program.len() + 1).is_err());
+        assert!(check(&|thread| thread.instruction_ptr = program.len() + 1).is_err());
+        assert!(check(&|thread| thread.addr = snapshot.state.cells.len()).is_err());
+    }
+
+    #[test]
+    fn broken_files_are_rejected() {
+        let (_, snapshot) = pause(&parse(PROGRAM), 1, b"abcdef");
+        let data = write(&snapshot);
+        for length in 0..data.len() {
+            assert!(Snapshot::read(&mut &data[..length]).is_err(), "{length}");
+        }
+        let mut trailing = data.clone();
+        trailing.push(0);
+        assert!(Snapshot::read(&mut &trailing[..]).is_err());
+
+        // Sizes exceeding the data are not trusted to allocate memory
+        let header = MAGIC.len() + 4 + 8;
+        let mut oversized = data[..header].to_vec();
+        write_varint(&mut oversized, usize::MAX).unwrap();
+        write_varint(&mut oversized, 0).unwrap();
+        assert!(Snapshot::read(&mut &oversized[..]).is_err());
+
+        let mut pending = data[..data.len() - snapshot.pending_input.len() - 1].to_vec();
+        write_varint(&mut pending, usize::MAX).unwrap();
+        assert!(Snapshot::read(&mut &pending[..]).is_err());
+    }
+}
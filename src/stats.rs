@@ -0,0 +1,98 @@
+use std::fmt;
+
+use crate::{Instruction, Program};
+
+/// Static statistics about a parsed program.
+#[derive(Debug, Default)]
+pub struct ProgramStats {
+    instructions: usize,
+    commands: usize,
+    moves: usize,
+    arithmetic: usize,
+    outputs: usize,
+    inputs: usize,
+    loops: usize,
+    max_depth: usize,
+    procedures: usize,
+    calls: usize,
+    forks: usize,
+}
+
+impl ProgramStats {
+    pub fn collect(program: &Program) -> Self {
+        let mut stats = Self {
+            instructions: program.len(),
+            ..Default::default()
+        };
+        let mut depth: usize = 0;
+
+        for instruction in program {
+            match *instruction {
+                Instruction::AddrRight(count) | Instruction::AddrLeft(count) => {
+                    stats.moves += count;
+                    stats.commands += count;
+                }
+                Instruction::Inc(count) | Instruction::Dec(count) => {
+                    stats.arithmetic += count as usize;
+                    stats.commands += count as usize;
+                }
+                Instruction::Output(count) => {
+                    stats.outputs += count;
+                    stats.commands += count;
+                }
+                Instruction::Input(count) => {
+                    stats.inputs += count;
+                    stats.commands += count;
+                }
+                Instruction::JmpForward(_) => {
+                    stats.loops += 1;
+                    stats.commands += 1;
+                    depth += 1;
+                    stats.max_depth = stats.max_depth.max(depth);
+                }
+                Instruction::JmpBack(_) => {
+                    stats.commands += 1;
+                    depth = depth.saturating_sub(1);
+                }
+                Instruction::ProcedureStart(_) => {
+                    stats.procedures += 1;
+                    stats.commands += 1;
+                }
+                Instruction::ProcedureEnd(_) => stats.commands += 1,
+                Instruction::Call => {
+                    stats.calls += 1;
+                    stats.commands += 1;
+                }
+                Instruction::Fork => {
+                    stats.forks += 1;
+                    stats.commands += 1;
+                }
+            }
+        }
+
+        stats
+    }
+}
+
+impl fmt::Display for ProgramStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "commands:          {}", self.commands)?;
+        writeln!(f, "instructions:      {}", self.instructions)?;
+        writeln!(f, "pointer moves:     {}", self.moves)?;
+        writeln!(f, "arithmetic:        {}", self.arithmetic)?;
+        writeln!(f, "outputs:           {}", self.outputs)?;
+        writeln!(f, "inputs:            {}", self.inputs)?;
+        writeln!(f, "loops:             {}", self.loops)?;
+        write!(f, "max loop depth:    {}", self.max_depth)?;
+        // Only shown for programs using the pbrain extension
+        if self.procedures > 0 || self.calls > 0 {
+            write!(f, "\nprocedures:        {}", self.procedures)?;
+            write!(f, "\ncalls:             {}", self.calls)?;
+        }
+        // Only shown for programs using the Brainfork extension
+        if self.forks > 0 {
+            write!(f, "\nforks:             {}", self.forks)?;
+        }
+        Ok(())
+    }
+}
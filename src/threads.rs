@@ -0,0 +1,70 @@
+use std::io::{self, Read, Write};
+use std::sync::mpsc::{self, Sender};
+
+use anyhow::Result;
+
+/// Requests of the threads of a forking program to the thread owning the
+/// input and output of the program.
+pub enum Request {
+    Output(Vec<u8>),
+    /// Read a single byte, which is sent back through the given channel
+    Input(Sender<Result<Option<u8>>>),
+    /// The thread stopped because of an error
+    Error(anyhow::Error),
+}
+
+/// Input of a thread, read on its behalf by the owner of the input.
+pub struct ChannelReader {
+    sender: Sender<Request>,
+}
+
+impl ChannelReader {
+    pub fn new(sender: Sender<Request>) -> Self {
+        Self { sender }
+    }
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let (reply, response) = mpsc::channel();
+        self.sender
+            .send(Request::Input(reply))
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        match response.recv() {
+            Ok(Ok(Some(byte))) => {
+                buf[0] = byte;
+                Ok(1)
+            }
+            Ok(Ok(None)) => Ok(0),
+            Ok(Err(e)) => Err(io::Error::other(e)),
+            Err(_) => Err(io::Error::from(io::ErrorKind::BrokenPipe)),
+        }
+    }
+}
+
+/// Output of a thread, written on its behalf by the owner of the output.
+pub struct ChannelWriter {
+    sender: Sender<Request>,
+}
+
+impl ChannelWriter {
+    pub fn new(sender: Sender<Request>) -> Self {
+        Self { sender }
+    }
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.sender
+            .send(Request::Output(buf.to_vec()))
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
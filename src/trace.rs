@@ -0,0 +1,680 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::str::FromStr;
+
+use crate::backend::ExecutionConfig;
+use crate::bytecode::{self, read_bytes, read_varint, write_varint, BytecodeHeader};
+use crate::cli::{ReplayOptions, ReplayQuery, RunOptions};
+use crate::json::JsonValue;
+use crate::optimizer::OPTIMIZATION_LEVEL;
+use crate::{Instruction, Intepreter, Location, Program, Span};
+use anyhow::{anyhow, Context, Result};
+
+/// Magic bytes every binary trace starts with.
+pub const MAGIC: &[u8; 4] = b"RBTR";
+/// Version of the binary trace format. Needs to be increased on every
+/// incompatible change.
+pub const VERSION: u8 = 2;
+
+/// How a trace is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// Compact binary log, see `TraceWriter`
+    Binary,
+    /// A JSON object per executed instruction and line
+    Jsonl,
+}
+
+impl FromStr for TraceFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "binary" => Ok(TraceFormat::Binary),
+            "jsonl" => Ok(TraceFormat::Jsonl),
+            _ => Err(anyhow!(
+                "Invalid trace format {s}, expected one of binary, jsonl"
+            )),
+        }
+    }
+}
+
+/// A single executed instruction, together with the pointer and the value
+/// of the cell it points to right after the instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Record {
+    /// Number of the thread, in the order the threads have been forked in
+    thread: usize,
+    instruction: Instruction,
+    span: Span,
+    pointer: usize,
+    cell: u32,
+}
+
+impl Record {
+    /// Whether the instruction has set the cell to the value.
+    fn writes(&self, cell: usize, value: u32) -> bool {
+        match self.instruction {
+            Instruction::Inc(_) | Instruction::Dec(_) | Instruction::Input(_) => {
+                self.pointer == cell && self.cell == value
+            }
+            // Forking clears the cell of the parent and sets the next one,
+            // which the child starts on, to 1
+            Instruction::Fork => {
+                (self.pointer == cell && value == 0) || (self.pointer + 1 == cell && value == 1)
+            }
+            _ => false,
+        }
+    }
+
+    fn write_json<W: Write + ?Sized>(&self, writer: &mut W, step: u64) -> Result<()> {
+        writeln!(
+            writer,
+            r#"{{"step":{step},"thread":{},"line":{},"column":{},"end_line":{},"end_column":{},"instruction":"{:?}","pointer":{},"cell":{}}}"#,
+            self.thread,
+            self.span.start.line,
+            self.span.start.column,
+            self.span.end.line,
+            self.span.end.column,
+            self.instruction,
+            self.pointer,
+            self.cell
+        )
+        .context("write trace record")
+    }
+
+    fn read_json(line: &str) -> Result<Self> {
+        let json = JsonValue::parse(line)?;
+        let number = |key: &str| {
+            json.get(key)
+                .and_then(JsonValue::as_usize)
+                .ok_or_else(|| anyhow!("trace record is missing {key}"))
+        };
+        let instruction = json
+            .get("instruction")
+            .and_then(JsonValue::as_str)
+            .ok_or_else(|| anyhow!("trace record is missing instruction"))?;
+        Ok(Self {
+            thread: number("thread")?,
+            instruction: parse_instruction(instruction)?,
+            span: Span {
+                start: Location {
+                    line: number("line")?,
+                    column: number("column")?,
+                },
+                end: Location {
+                    line: number("end_line")?,
+                    column: number("end_column")?,
+                },
+            },
+            pointer: number("pointer")?,
+            cell: number("cell")?
+                .try_into()
+                .map_err(|_| anyhow!("cell value in trace record is too large"))?,
+        })
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} at {}:{} in thread {}, pointer at cell {} holding {}",
+            self.instruction,
+            self.span.start.line,
+            self.span.start.column,
+            self.thread,
+            self.pointer,
+            self.cell
+        )
+    }
+}
+
+/// Parse an instruction in the notation of its `Debug` output, e.g. `Inc(7)`.
+fn parse_instruction(text: &str) -> Result<Instruction> {
+    let invalid = || anyhow!("invalid instruction {text} in trace");
+    let (name, argument) = match text.strip_suffix(')').and_then(|rest| rest.split_once('(')) {
+        Some((name, argument)) => (
+            name,
+            Some(argument.parse::<usize>().map_err(|_| invalid())?),
+        ),
+        None => (text, None),
+    };
+    let count = || u8::try_from(argument.ok_or_else(invalid)?).map_err(|_| invalid());
+    let argument = || argument.ok_or_else(invalid);
+    Ok(match name {
+        "AddrRight" => Instruction::AddrRight(argument()?),
+        "AddrLeft" => Instruction::AddrLeft(argument()?),
+        "Inc" => Instruction::Inc(count()?),
+        "Dec" => Instruction::Dec(count()?),
+        "Output" => Instruction::Output(argument()?),
+        "Input" => Instruction::Input(argument()?),
+        "JmpForward" => Instruction::JmpForward(argument()?),
+        "JmpBack" => Instruction::JmpBack(argument()?),
+        "ProcedureStart" => Instruction::ProcedureStart(argument()?),
+        "ProcedureEnd" => Instruction::ProcedureEnd(argument()?),
+        "Call" => Instruction::Call,
+        "Fork" => Instruction::Fork,
+        _ => return Err(invalid()),
+    })
+}
+
+/// Writes a record of every executed instruction to a file.
+///
+/// Binary traces start with the program, so the records only need to refer
+/// to the instruction by its index (all integers as unsigned LEB128 like in
+/// bytecode):
+///
+/// ```text
+/// magic "RBTR" | version: u8 | bytecode length | program as bytecode
+/// span count | (start line | start column | end line | end column)...
+/// (thread | instruction index | pointer | cell)...
+/// ```
+struct TraceWriter<W: Write> {
+    writer: W,
+    format: TraceFormat,
+    program: Program,
+    spans: Vec<Span>,
+    steps: u64,
+}
+
+impl<W: Write> TraceWriter<W> {
+    fn new(
+        mut writer: W,
+        format: TraceFormat,
+        program: &Program,
+        spans: &[Span],
+        config: &ExecutionConfig,
+    ) -> Result<Self> {
+        if format == TraceFormat::Binary {
+            writer.write_all(MAGIC)?;
+            writer.write_all(&[VERSION])?;
+            let mut bytecode = vec![];
+            bytecode::write_program(
+                &mut bytecode,
+                &BytecodeHeader {
+                    cell_width: config.cell_width,
+                    optimization_level: OPTIMIZATION_LEVEL,
+                },
+                program,
+            )?;
+            write_varint(&mut writer, bytecode.len())?;
+            writer.write_all(&bytecode)?;
+            write_varint(&mut writer, spans.len())?;
+            for span in spans {
+                for location in [span.start, span.end] {
+                    write_varint(&mut writer, location.line)?;
+                    write_varint(&mut writer, location.column)?;
+                }
+            }
+        }
+        Ok(Self {
+            writer,
+            format,
+            program: program.clone(),
+            spans: spans.to_vec(),
+            steps: 0,
+        })
+    }
+
+    fn write(
+        &mut self,
+        thread: usize,
+        instruction_ptr: usize,
+        pointer: usize,
+        cell: u32,
+    ) -> Result<()> {
+        match self.format {
+            TraceFormat::Binary => {
+                write_varint(&mut self.writer, thread)?;
+                write_varint(&mut self.writer, instruction_ptr)?;
+                write_varint(&mut self.writer, pointer)?;
+                write_varint(&mut self.writer, cell as usize)?;
+            }
+            TraceFormat::Jsonl => {
+                let record = Record {
+                    thread,
+                    instruction: self.program[instruction_ptr].clone(),
+                    span: self.spans[instruction_ptr],
+                    pointer,
+                    cell,
+                };
+                record.write_json(&mut self.writer, self.steps)?;
+            }
+        }
+        self.steps += 1;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<u64> {
+        self.writer.flush().context("flush trace")?;
+        Ok(self.steps)
+    }
+}
+
+enum TraceKind {
+    Binary { program: Program, spans: Vec<Span> },
+    Jsonl,
+}
+
+/// Reads the records of a trace in either format one by one, as traces tend
+/// to be too large to be read completely.
+struct TraceReader<R: BufRead> {
+    reader: R,
+    kind: TraceKind,
+    line: String,
+}
+
+impl TraceReader<BufReader<File>> {
+    fn open(path: &str) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("open trace {path}"))?;
+        Self::new(BufReader::new(file)).with_context(|| format!("load trace {path}"))
+    }
+}
+
+impl<R: BufRead> TraceReader<R> {
+    fn new(mut reader: R) -> Result<Self> {
+        let binary = reader.fill_buf().context("read trace")?.starts_with(MAGIC);
+        let kind = if binary {
+            Self::read_header(&mut reader)?
+        } else {
+            TraceKind::Jsonl
+        };
+        Ok(Self {
+            reader,
+            kind,
+            line: String::new(),
+        })
+    }
+
+    fn read_header(reader: &mut R) -> Result<TraceKind> {
+        reader.consume(MAGIC.len());
+        let version = bytecode::read_u8(reader)?;
+        if version != VERSION {
+            return Err(anyhow!(
+                "unsupported trace version {version}, expected {VERSION}"
+            ));
+        }
+        let length = read_varint(reader)?;
+        let bytecode = read_bytes(reader, length).context("reading program of trace")?;
+        let (_, program) = bytecode::read_program(&mut &bytecode[..])?;
+        let count = read_varint(reader)?;
+        if count != program.len() {
+            return Err(anyhow!("trace does not contain a span per instruction"));
+        }
+        let mut read_location = || -> Result<Location> {
+            Ok(Location {
+                line: read_varint(reader)?,
+                column: read_varint(reader)?,
+            })
+        };
+        let spans = (0..count)
+            .map(|_| {
+                Ok(Span {
+                    start: read_location()?,
+                    end: read_location()?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(TraceKind::Binary { program, spans })
+    }
+
+    fn next(&mut self) -> Result<Option<Record>> {
+        match &self.kind {
+            TraceKind::Binary { program, spans } => {
+                if self.reader.fill_buf().context("read trace")?.is_empty() {
+                    return Ok(None);
+                }
+                let reader = &mut self.reader;
+                let thread = read_varint(reader)?;
+                let instruction_ptr = read_varint(reader)?;
+                let pointer = read_varint(reader)?;
+                let cell = read_varint(reader)?
+                    .try_into()
+                    .map_err(|_| anyhow!("cell value in trace is too large"))?;
+                let instruction = program
+                    .get(instruction_ptr)
+                    .ok_or_else(|| anyhow!("instruction in trace is out of range"))?;
+                Ok(Some(Record {
+                    thread,
+                    instruction: instruction.clone(),
+                    span: spans[instruction_ptr],
+                    pointer,
+                    cell,
+                }))
+            }
+            TraceKind::Jsonl => loop {
+                self.line.clear();
+                if self
+                    .reader
+                    .read_line(&mut self.line)
+                    .context("read trace")?
+                    == 0
+                {
+                    return Ok(None);
+                }
+                if !self.line.trim().is_empty() {
+                    return Record::read_json(&self.line).map(Some);
+                }
+            },
+        }
+    }
+}
+
+/// Run the program in the interpreter, recording every executed instruction
+/// to the trace file. Threads are scheduled exactly like by the interpreter,
+/// executing a single instruction each in turn.
+///
+/// If the program fails, the trace ends with the last instruction executed
+/// successfully.
+pub fn record(
+    program: Program,
+    spans: &[Span],
+    options: &RunOptions,
+    input: &mut dyn Read,
+    output: &mut dyn Write,
+    verbose: bool,
+) -> Result<()> {
+    let path = options
+        .trace
+        .as_deref()
+        .expect("trace file to be given when recording a trace");
+    let config = &options.config;
+    let file = File::create(path).with_context(|| format!("create trace file {path}"))?;
+    let mut writer = TraceWriter::new(
+        BufWriter::new(file),
+        options.trace_format,
+        &program,
+        spans,
+        config,
+    )?;
+    let result = run_traced(&mut writer, Intepreter::new(program, config), input, output);
+
+    let steps = writer.finish()?;
+    if verbose {
+        eprintln!("Trace of {steps} executed instructions written to {path}");
+    }
+    result
+}
+
+fn run_traced<W: Write>(
+    writer: &mut TraceWriter<W>,
+    intepreter: Intepreter,
+    input: &mut dyn Read,
+    output: &mut dyn Write,
+) -> Result<()> {
+    intepreter.run_observed(input, output, |number, instruction_ptr, thread| {
+        writer.write(
+            number,
+            instruction_ptr,
+            thread.addr,
+            intepreter.cell(thread.addr),
+        )
+    })
+}
+
+/// Answer the query about the given trace, printing the result to stdout.
+pub fn replay(options: &ReplayOptions) -> Result<()> {
+    let mut trace = TraceReader::open(&options.trace)?;
+    let mut other = match &options.query {
+        ReplayQuery::Diff(path) => Some(TraceReader::open(path)?),
+        _ => None,
+    };
+    answer(
+        &options.trace,
+        &mut trace,
+        &options.query,
+        other.as_mut(),
+        &mut std::io::stdout().lock(),
+    )
+}
+
+/// Answer the query about the trace named `name`, given the trace to compare
+/// against for a diff.
+fn answer<R: BufRead>(
+    name: &str,
+    trace: &mut TraceReader<R>,
+    query: &ReplayQuery,
+    other: Option<&mut TraceReader<R>>,
+    output: &mut dyn Write,
+) -> Result<()> {
+    let mut step: u64 = 0;
+    match query {
+        ReplayQuery::Print => {
+            while let Some(record) = trace.next()? {
+                record.write_json(output, step)?;
+                step += 1;
+            }
+        }
+        &ReplayQuery::CellValue { cell, value } => {
+            while let Some(record) = trace.next()? {
+                if record.writes(cell, value) {
+                    let Location { line, column } = record.span.start;
+                    writeln!(
+                        output,
+                        "Cell {cell} became {value} in step {step} at {line}:{column} by {:?}",
+                        record.instruction
+                    )?;
+                    return Ok(());
+                }
+                step += 1;
+            }
+            writeln!(output, "Cell {cell} never became {value}")?;
+        }
+        &ReplayQuery::OutputByte(index) => {
+            let mut produced = 0;
+            while let Some(record) = trace.next()? {
+                if let Instruction::Output(count) = record.instruction {
+                    produced += count;
+                    if produced > index {
+                        let Location { line, column } = record.span.start;
+                        writeln!(
+                            output,
+                            "Output byte #{index} ({:#04x}) was produced in step {step} at {line}:{column} by {:?}",
+                            record.cell as u8, record.instruction
+                        )?;
+                        return Ok(());
+                    }
+                }
+                step += 1;
+            }
+            writeln!(output, "The trace contains only {produced} output bytes")?;
+        }
+        ReplayQuery::Diff(other_path) => {
+            let other = other.expect("trace to compare against to be given for a diff");
+            loop {
+                match (trace.next()?, other.next()?) {
+                    (None, None) => {
+                        writeln!(output, "Traces are identical, {step} steps")?;
+                        return Ok(());
+                    }
+                    (Some(_), None) => {
+                        writeln!(
+                            output,
+                            "{other_path} ends after {step} steps, {} continues",
+                            name
+                        )?;
+                        return Ok(());
+                    }
+                    (None, Some(_)) => {
+                        writeln!(
+                            output,
+                            "{} ends after {step} steps, {other_path} continues",
+                            name
+                        )?;
+                        return Ok(());
+                    }
+                    (Some(record), Some(other_record)) if record != other_record => {
+                        writeln!(output, "Traces differ in step {step}:")?;
+                        writeln!(output, "  {name}: {record}")?;
+                        writeln!(output, "  {other_path}: {other_record}")?;
+                        return Ok(());
+                    }
+                    _ => step += 1,
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::CellWidth;
+    use crate::dialect::Dialect;
+    use crate::{Lexer, Parser};
+    use std::rc::Rc;
+
+    /// Record the trace of running the program without input.
+    fn record(code: &str, dialect: &str, format: TraceFormat) -> Vec<u8> {
+        let dialect = Rc::new(Dialect::load(dialect).unwrap());
+        let mut parser = Parser::default();
+        let program = parser
+            .parse_program(&mut Lexer::new(code.as_bytes(), dialect))
+            .unwrap();
+        let config = ExecutionConfig::default();
+        let mut data = vec![];
+        let mut writer =
+            TraceWriter::new(&mut data, format, &program, &parser.spans, &config).unwrap();
+        run_traced(
+            &mut writer,
+            Intepreter::new(program, &config),
+            &mut std::io::empty(),
+            &mut std::io::sink(),
+        )
+        .unwrap();
+        writer.finish().unwrap();
+        data
+    }
+
+    fn records(data: &[u8]) -> Vec<Record> {
+        let mut reader = TraceReader::new(data).unwrap();
+        let mut records = vec![];
+        while let Some(record) = reader.next().unwrap() {
+            records.push(record);
+        }
+        records
+    }
+
+    fn query(data: &[u8], query: ReplayQuery, other: Option<&[u8]>) -> String {
+        let mut trace = TraceReader::new(data).unwrap();
+        let mut other = other.map(|other| TraceReader::new(other).unwrap());
+        let mut output = vec![];
+        answer("a", &mut trace, &query, other.as_mut(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    const PROGRAM: &str = "++>+\n[-<.>]";
+
+    #[test]
+    fn both_formats_hold_the_same_records() {
+        let binary = records(&record(PROGRAM, "brainfuck", TraceFormat::Binary));
+        let jsonl = records(&record(PROGRAM, "brainfuck", TraceFormat::Jsonl));
+        assert_eq!(binary, jsonl);
+        assert_eq!(binary.len(), 9);
+        assert_eq!(
+            binary[6],
+            Record {
+                thread: 0,
+                instruction: Instruction::Output(1),
+                span: Span {
+                    start: Location { line: 2, column: 4 },
+                    end: Location { line: 2, column: 5 },
+                },
+                pointer: 0,
+                cell: 2,
+            }
+        );
+
+        // Printing a binary trace yields the JSON lines trace
+        let printed = query(
+            &record(PROGRAM, "brainfuck", TraceFormat::Binary),
+            ReplayQuery::Print,
+            None,
+        );
+        assert_eq!(
+            printed.as_bytes(),
+            record(PROGRAM, "brainfuck", TraceFormat::Jsonl)
+        );
+    }
+
+    #[test]
+    fn queries() {
+        let trace = record(PROGRAM, "brainfuck", TraceFormat::Binary);
+        assert_eq!(
+            query(&trace, ReplayQuery::CellValue { cell: 1, value: 0 }, None),
+            "Cell 1 became 0 in step 4 at 2:2 by Dec(1)\n"
+        );
+        assert_eq!(
+            query(&trace, ReplayQuery::CellValue { cell: 0, value: 3 }, None),
+            "Cell 0 never became 3\n"
+        );
+        assert_eq!(
+            query(&trace, ReplayQuery::OutputByte(0), None),
+            "Output byte #0 (0x02) was produced in step 6 at 2:4 by Output(1)\n"
+        );
+        assert_eq!(
+            query(&trace, ReplayQuery::OutputByte(1), None),
+            "The trace contains only 1 output bytes\n"
+        );
+
+        let diff = |other: &[u8]| query(&trace, ReplayQuery::Diff("b".to_string()), Some(other));
+        assert_eq!(diff(&trace), "Traces are identical, 9 steps\n");
+        assert_eq!(
+            diff(&record("++>+\n[-<.>]+", "brainfuck", TraceFormat::Jsonl)),
+            "a ends after 9 steps, b continues\n"
+        );
+        assert_eq!(
+            diff(&record("++>+\n[-<,>]", "brainfuck", TraceFormat::Binary)),
+            "Traces differ in step 6:\n  a: Output(1) at 2:4 in thread 0, pointer at cell 0 holding 2\n  b: Input(1) at 2:4 in thread 0, pointer at cell 0 holding 2\n"
+        );
+    }
+
+    #[test]
+    fn forks_write_the_cells_of_both_threads() {
+        let trace = record("+>+<Y", "brainfork", TraceFormat::Binary);
+        assert_eq!(
+            query(&trace, ReplayQuery::CellValue { cell: 0, value: 0 }, None),
+            "Cell 0 became 0 in step 4 at 1:5 by Fork\n"
+        );
+        assert_eq!(
+            query(&trace, ReplayQuery::CellValue { cell: 1, value: 1 }, None),
+            "Cell 1 became 1 in step 2 at 1:3 by Inc(1)\n"
+        );
+        let trace = record(">Y", "brainfork", TraceFormat::Binary);
+        assert_eq!(
+            query(&trace, ReplayQuery::CellValue { cell: 2, value: 1 }, None),
+            "Cell 2 became 1 in step 1 at 1:2 by Fork\n"
+        );
+    }
+
+    #[test]
+    fn broken_headers_are_rejected() {
+        let trace = record(PROGRAM, "brainfuck", TraceFormat::Binary);
+        let header = trace.len() - records(&trace).len() * 4;
+        for length in MAGIC.len()..header {
+            assert!(TraceReader::new(&trace[..length]).is_err(), "{length}");
+        }
+
+        // Lengths exceeding the data are not trusted
+        assert!(TraceReader::new(&b"RBTR\x02\xff\xff\xff\xff\xff\x1f"[..]).is_err());
+        let mut oversized = MAGIC.to_vec();
+        oversized.push(VERSION);
+        let mut bytecode = vec![];
+        let header = BytecodeHeader {
+            cell_width: CellWidth::U8,
+            optimization_level: OPTIMIZATION_LEVEL,
+        };
+        bytecode::write_program(&mut bytecode, &header, &vec![Instruction::Inc(1)]).unwrap();
+        write_varint(&mut oversized, bytecode.len()).unwrap();
+        oversized.extend_from_slice(&bytecode);
+        write_varint(&mut oversized, usize::MAX).unwrap();
+        assert!(TraceReader::new(&oversized[..]).is_err());
+
+        let mut version = trace.clone();
+        version[4] = VERSION + 1;
+        assert!(TraceReader::new(&version[..]).is_err());
+    }
+}
@@ -0,0 +1,388 @@
+use std::fmt::Write;
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::backend::{CellWidth, EofBehavior, ExecutionConfig};
+use crate::{Instruction, Program};
+use anyhow::{anyhow, Result};
+
+const INDENT: &str = "    ";
+
+/// Language a program is translated to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Rust,
+    LlvmIr,
+}
+
+impl FromStr for Target {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "rust" => Ok(Target::Rust),
+            "llvm" => Ok(Target::LlvmIr),
+            _ => Err(anyhow!("Invalid target {s}, expected one of rust, llvm")),
+        }
+    }
+}
+
+pub struct TranspileOptions {
+    /// Emit a module exposing `run` instead of a standalone `main.rs`.
+    pub library: bool,
+    /// Name of the source, mentioned in the header of the generated code.
+    pub source_name: String,
+}
+
+/// Translates a parsed program into Rust source code.
+///
+/// The whole program becomes a single function operating on a `State`, with
+/// loops turned into `while` loops. Procedures of pbrain become functions of
+/// their own, which are registered in a table of the state once their
+/// definition is executed.
+struct Transpiler<'a> {
+    program: &'a Program,
+    eof: EofBehavior,
+    /// Functions emitted for the procedures of the program
+    procedures: Vec<String>,
+}
+
+impl Transpiler<'_> {
+    fn line(code: &mut String, depth: usize, line: &str) {
+        code.push_str(&INDENT.repeat(depth));
+        code.push_str(line);
+        code.push('\n');
+    }
+
+    /// Emit the instructions in the given range as the body of a function.
+    fn emit_body(&mut self, range: Range<usize>) -> Result<String> {
+        let mut code = String::new();
+        let mut depth = 1;
+        let mut position = range.start;
+        while position < range.end {
+            match self.program[position] {
+                Instruction::AddrRight(count) => {
+                    Self::line(&mut code, depth, &format!("s.ptr += {count};"))
+                }
+                Instruction::AddrLeft(count) => {
+                    Self::line(&mut code, depth, &format!("s.ptr -= {count};"))
+                }
+                Instruction::Inc(count) => Self::line(
+                    &mut code,
+                    depth,
+                    &format!("s.tape[s.ptr] = s.tape[s.ptr].wrapping_add({count});"),
+                ),
+                Instruction::Dec(count) => Self::line(
+                    &mut code,
+                    depth,
+                    &format!("s.tape[s.ptr] = s.tape[s.ptr].wrapping_sub({count});"),
+                ),
+                Instruction::Output(1) => Self::line(
+                    &mut code,
+                    depth,
+                    "output.write_all(&[s.tape[s.ptr] as u8])?;",
+                ),
+                Instruction::Output(count) => Self::line(
+                    &mut code,
+                    depth,
+                    &format!("output.write_all(&[s.tape[s.ptr] as u8; {count}])?;"),
+                ),
+                Instruction::Input(count) => {
+                    let on_eof = match self.eof {
+                        EofBehavior::Unchanged => "None => {}",
+                        EofBehavior::Zero => "None => s.tape[s.ptr] = 0,",
+                        EofBehavior::MinusOne => "None => s.tape[s.ptr] = Cell::MAX,",
+                    };
+                    let mut inner = depth;
+                    if count > 1 {
+                        Self::line(&mut code, depth, &format!("for _ in 0..{count} {{"));
+                        inner += 1;
+                    }
+                    Self::line(&mut code, inner, "match read_byte(input)? {");
+                    Self::line(
+                        &mut code,
+                        inner + 1,
+                        "Some(byte) => s.tape[s.ptr] = byte.into(),",
+                    );
+                    Self::line(&mut code, inner + 1, on_eof);
+                    Self::line(&mut code, inner, "}");
+                    if count > 1 {
+                        Self::line(&mut code, depth, "}");
+                    }
+                }
+                Instruction::JmpForward(_) => {
+                    Self::line(&mut code, depth, "while s.tape[s.ptr] != 0 {");
+                    depth += 1;
+                }
+                Instruction::JmpBack(_) => {
+                    depth -= 1;
+                    Self::line(&mut code, depth, "}");
+                }
+                Instruction::ProcedureStart(target) => {
+                    // The body ends right before the matching ProcedureEnd
+                    let body = self.emit_body(position + 1..target - 1)?;
+                    self.procedures.push(format!(
+                        "fn procedure_{position}(s: &mut State, input: &mut dyn Read, output: &mut dyn Write) -> io::Result<()> {{\n{body}{INDENT}Ok(())\n}}\n"
+                    ));
+                    Self::line(
+                        &mut code,
+                        depth,
+                        &format!("s.procedures.insert(s.tape[s.ptr], procedure_{position});"),
+                    );
+                    position = target;
+                    continue;
+                }
+                Instruction::ProcedureEnd(_) => {
+                    unreachable!("procedure bodies are emitted without their end")
+                }
+                Instruction::Call => {
+                    Self::line(
+                        &mut code,
+                        depth,
+                        "let procedure = *s.procedures.get(&s.tape[s.ptr]).ok_or_else(|| {",
+                    );
+                    Self::line(
+                        &mut code,
+                        depth + 1,
+                        "io::Error::other(format!(\"Call of undefined procedure {}\", s.tape[s.ptr]))",
+                    );
+                    Self::line(&mut code, depth, "})?;");
+                    Self::line(&mut code, depth, "procedure(s, input, output)?;");
+                }
+                Instruction::Fork => {
+                    return Err(anyhow!(
+                        "Forking threads is not supported by the Rust transpiler"
+                    ))
+                }
+            }
+            position += 1;
+        }
+        Ok(code)
+    }
+}
+
+/// Translate the program into Rust source code, either a standalone
+/// `main.rs` or a module exposing
+/// `run(input: &mut impl Read, output: &mut impl Write) -> io::Result<()>`.
+///
+/// The tape is a `Vec` indexed with bounds checks, so moving the pointer off
+/// the tape panics instead of corrupting memory.
+pub fn to_rust(
+    program: &Program,
+    config: &ExecutionConfig,
+    options: &TranspileOptions,
+) -> Result<String> {
+    let mut transpiler = Transpiler {
+        program,
+        eof: config.eof,
+        procedures: vec![],
+    };
+    let body = transpiler.emit_body(0..program.len())?;
+    let uses_procedures = program.iter().any(|instruction| {
+        matches!(
+            instruction,
+            Instruction::ProcedureStart(_) | Instruction::Call
+        )
+    });
+    let uses_input = program
+        .iter()
+        .any(|instruction| matches!(instruction, Instruction::Input(_)));
+    let cell = match config.cell_width {
+        CellWidth::U8 => "u8",
+        CellWidth::U16 => "u16",
+        CellWidth::U32 => "u32",
+    };
+
+    // Writing to a String never fails
+    let mut code = String::new();
+    let _ = writeln!(
+        code,
+        "// Generated by rust_brain from {}, do not edit.",
+        options.source_name
+    );
+    // Generated code is not meant to be read, so lints about unused code or
+    // style do not apply
+    let _ = writeln!(
+        code,
+        "#![allow(unused_variables, dead_code, clippy::all)]\n"
+    );
+    if uses_procedures {
+        let _ = writeln!(code, "use std::collections::HashMap;");
+    }
+    let _ = writeln!(code, "use std::io::{{self, Read, Write}};\n");
+    let _ = writeln!(code, "const TAPE_SIZE: usize = {};\n", config.tape_size);
+    let _ = writeln!(code, "type Cell = {cell};");
+    if uses_procedures {
+        let _ = writeln!(
+            code,
+            "type Procedure = fn(&mut State, &mut dyn Read, &mut dyn Write) -> io::Result<()>;"
+        );
+    }
+    let _ = writeln!(code, "\nstruct State {{");
+    let _ = writeln!(code, "{INDENT}tape: Vec<Cell>,");
+    let _ = writeln!(code, "{INDENT}ptr: usize,");
+    if uses_procedures {
+        let _ = writeln!(code, "{INDENT}procedures: HashMap<Cell, Procedure>,");
+    }
+    let _ = writeln!(code, "}}\n");
+
+    if uses_input {
+        code.push_str(
+            "fn read_byte(input: &mut dyn Read) -> io::Result<Option<u8>> {
+    let mut buf = [0];
+    loop {
+        match input.read(&mut buf) {
+            Ok(0) => return Ok(None),
+            Ok(_) => return Ok(Some(buf[0])),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+",
+        );
+    }
+
+    let _ = writeln!(
+        code,
+        "fn program(s: &mut State, input: &mut dyn Read, output: &mut dyn Write) -> io::Result<()> {{\n{body}{INDENT}Ok(())\n}}\n"
+    );
+    for procedure in &transpiler.procedures {
+        let _ = writeln!(code, "{procedure}");
+    }
+
+    let _ = writeln!(code, "/// Run the program on a fresh tape.");
+    let _ = writeln!(
+        code,
+        "pub fn run(input: &mut impl Read, output: &mut impl Write) -> io::Result<()> {{"
+    );
+    let _ = writeln!(code, "{INDENT}let mut state = State {{");
+    let _ = writeln!(code, "{INDENT}{INDENT}tape: vec![0; TAPE_SIZE],");
+    let _ = writeln!(code, "{INDENT}{INDENT}ptr: 0,");
+    if uses_procedures {
+        let _ = writeln!(code, "{INDENT}{INDENT}procedures: HashMap::new(),");
+    }
+    let _ = writeln!(code, "{INDENT}}};");
+    let _ = writeln!(code, "{INDENT}program(&mut state, input, output)?;");
+    let _ = writeln!(code, "{INDENT}output.flush()");
+    let _ = writeln!(code, "}}");
+
+    if !options.library {
+        code.push_str(
+            "
+fn main() {
+    let mut output = io::stdout().lock();
+    if let Err(e) = run(&mut io::stdin().lock(), &mut output) {
+        eprintln!(\"Error: {e}\");
+        std::process::exit(1);
+    }
+}
+",
+        );
+    }
+
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::Dialect;
+    use crate::{Lexer, Parser};
+    use std::rc::Rc;
+
+    fn parse(source: &str, dialect: &str) -> Program {
+        let dialect = Rc::new(Dialect::load(dialect).unwrap());
+        Parser::default()
+            .parse_program(&mut Lexer::new(source.as_bytes(), dialect))
+            .unwrap()
+    }
+
+    /// Compare the translation of a program to the expected code, which is
+    /// generated with `rust_brain [--dialect <dialect>] transpile [--lib]
+    /// [--eof <behavior>] -o tests/rust/<name>.rs <path>`.
+    fn check(
+        path: &str,
+        source: &str,
+        dialect: &str,
+        eof: EofBehavior,
+        library: bool,
+        expected: &str,
+    ) {
+        let config = ExecutionConfig {
+            eof,
+            ..ExecutionConfig::default()
+        };
+        let options = TranspileOptions {
+            library,
+            source_name: format!("brainfuck file {path}"),
+        };
+        let code = to_rust(&parse(source, dialect), &config, &options).unwrap();
+        assert!(code == expected, "translation of {path} changed:\n{code}");
+    }
+
+    #[test]
+    fn hello_world() {
+        check(
+            "examples/hello_world.brainfuck",
+            include_str!("../examples/hello_world.brainfuck"),
+            "brainfuck",
+            EofBehavior::Unchanged,
+            false,
+            include_str!("../tests/rust/hello_world.rs"),
+        );
+    }
+
+    #[test]
+    fn library_with_procedures() {
+        check(
+            "tests/llvm_ir/procedures.pbrain",
+            include_str!("../tests/llvm_ir/procedures.pbrain"),
+            "pbrain",
+            EofBehavior::Unchanged,
+            true,
+            include_str!("../tests/rust/procedures.rs"),
+        );
+    }
+
+    #[test]
+    fn eof_behaviors() {
+        // The program reads several bytes at once
+        let expected = [
+            (
+                EofBehavior::Unchanged,
+                include_str!("../tests/rust/eof_unchanged.rs"),
+            ),
+            (EofBehavior::Zero, include_str!("../tests/rust/eof_zero.rs")),
+            (
+                EofBehavior::MinusOne,
+                include_str!("../tests/rust/eof_minus_one.rs"),
+            ),
+        ];
+        for (eof, expected) in expected {
+            check(
+                "tests/rust/eof.b",
+                include_str!("../tests/rust/eof.b"),
+                "brainfuck",
+                eof,
+                false,
+                expected,
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_forks() {
+        let options = TranspileOptions {
+            library: false,
+            source_name: "test".to_string(),
+        };
+        let program = parse("+[-Y]", "brainfork");
+        let error = to_rust(&program, &ExecutionConfig::default(), &options).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Forking threads is not supported by the Rust transpiler"
+        );
+    }
+}
@@ -0,0 +1,379 @@
+use std::fmt::Write as _;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::fd::AsRawFd;
+use std::time::{Duration, Instant};
+
+use crate::cli::{InputSource, RunOptions};
+use crate::{Instruction, Intepreter, Program, Span};
+use anyhow::{anyhow, Context, Result};
+
+/// Executed instructions per second when starting.
+const DEFAULT_SPEED: u64 = 10;
+const MAX_SPEED: u64 = 1 << 20;
+/// Maximal number of frames drawn per second, faster speeds execute multiple
+/// instructions per frame.
+const FRAME_RATE: u64 = 30;
+/// Number of lines of output shown below the tape.
+const OUTPUT_LINES: usize = 8;
+/// Width of a cell on the tape, including the space separating it.
+const CELL_WIDTH: usize = 6;
+
+/// The controlling terminal, switched to reading single key presses without
+/// echoing them and to the alternate screen. Everything is restored once
+/// dropped.
+struct Terminal {
+    tty: File,
+    original: libc::termios,
+}
+
+impl Terminal {
+    fn open() -> Result<Self> {
+        let tty = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")
+            .context("open terminal, visualizing requires one")?;
+        let fd = tty.as_raw_fd();
+        let original = unsafe {
+            let mut original = std::mem::zeroed::<libc::termios>();
+            if libc::tcgetattr(fd, &mut original) == -1 {
+                return Err(io::Error::last_os_error()).context("reading terminal attributes");
+            }
+            original
+        };
+        let mut raw = original;
+        // Ctrl-C is handled as a key press, so the terminal is always restored
+        raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG);
+        raw.c_cc[libc::VMIN] = 1;
+        raw.c_cc[libc::VTIME] = 0;
+        if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &raw) } == -1 {
+            return Err(io::Error::last_os_error()).context("setting terminal attributes");
+        }
+
+        let mut terminal = Self { tty, original };
+        // Switch to the alternate screen and hide the cursor
+        terminal.tty.write_all(b"\x1b[?1049h\x1b[?25l")?;
+        Ok(terminal)
+    }
+
+    /// Number of columns of the terminal.
+    fn width(&self) -> usize {
+        let mut size = unsafe { std::mem::zeroed::<libc::winsize>() };
+        let result = unsafe { libc::ioctl(self.tty.as_raw_fd(), libc::TIOCGWINSZ, &mut size) };
+        if result == -1 || size.ws_col == 0 {
+            80
+        } else {
+            size.ws_col.into()
+        }
+    }
+
+    /// Wait for the next key press, at most for the given time if any.
+    fn wait_key(&mut self, timeout: Option<Duration>) -> Result<Option<u8>> {
+        let mut poll = libc::pollfd {
+            fd: self.tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout = timeout.map_or(-1, |timeout| {
+            timeout.as_millis().try_into().unwrap_or(libc::c_int::MAX)
+        });
+        match unsafe { libc::poll(&mut poll, 1, timeout) } {
+            -1 => {
+                let error = io::Error::last_os_error();
+                if error.kind() == io::ErrorKind::Interrupted {
+                    return Ok(None);
+                }
+                Err(error).context("waiting for key press")
+            }
+            0 => Ok(None),
+            _ => {
+                let mut key: [u8; 1] = [0; 1];
+                self.tty.read_exact(&mut key).context("read key press")?;
+                Ok(Some(key[0]))
+            }
+        }
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = self.tty.write_all(b"\x1b[?25h\x1b[?1049l");
+        unsafe {
+            libc::tcsetattr(self.tty.as_raw_fd(), libc::TCSANOW, &self.original);
+        }
+    }
+}
+
+enum State {
+    Running,
+    Paused,
+    Finished,
+    Failed(anyhow::Error),
+}
+
+/// Single threaded execution of a program by the `Intepreter`, drawn to the
+/// terminal after every step.
+struct Visualizer {
+    intepreter: Intepreter,
+    /// Source span of every instruction of the program
+    spans: Vec<Span>,
+    /// Lines of the source code
+    lines: Vec<Vec<u8>>,
+    input: Vec<u8>,
+    /// Number of input bytes consumed so far
+    input_position: usize,
+    output: Vec<u8>,
+    steps: u64,
+    /// Executed instructions per second
+    speed: u64,
+    state: State,
+}
+
+impl Visualizer {
+    fn instruction_ptr(&self) -> usize {
+        self.intepreter.threads[0].instruction_ptr
+    }
+
+    /// Execute up to the given number of instructions, stopping early if the
+    /// program ends or fails.
+    fn step(&mut self, count: u64) {
+        for _ in 0..count {
+            if self.instruction_ptr() >= self.intepreter.program.len() {
+                self.state = State::Finished;
+                return;
+            }
+            let mut thread = std::mem::take(&mut self.intepreter.threads[0]);
+            let mut input = &self.input[self.input_position..];
+            let result = self
+                .intepreter
+                .step(&mut thread, &mut input, &mut self.output);
+            self.input_position = self.input.len() - input.len();
+            self.intepreter.threads[0] = thread;
+            if let Err(e) = result {
+                self.state = State::Failed(e);
+                return;
+            }
+            self.steps += 1;
+        }
+        if self.instruction_ptr() >= self.intepreter.program.len() {
+            self.state = State::Finished;
+        }
+    }
+
+    fn draw(&self, terminal: &mut Terminal) -> Result<()> {
+        let width = terminal.width();
+        let truncate = |line: &str| line.chars().take(width).collect::<String>();
+        // Lines are redrawn in place and cleared behind their end, which
+        // avoids flickering of the whole screen
+        let mut frame = String::from("\x1b[H");
+        let mut line = |text: &str| {
+            frame.push_str(text);
+            frame.push_str("\x1b[K\r\n");
+        };
+
+        let status = match &self.state {
+            State::Running => "running".to_string(),
+            State::Paused => "paused".to_string(),
+            State::Finished => "finished".to_string(),
+            State::Failed(e) => format!("error: {e}"),
+        };
+        line(&truncate(&format!(
+            "rust_brain visualizer | {status} | {} steps/s | step {}",
+            self.speed, self.steps
+        )));
+        line("");
+
+        // Cells around the pointer, as many as fit into the terminal
+        let addr = self.intepreter.addr();
+        let tape_size = self.intepreter.memory.len();
+        let count = (width.saturating_sub(6) / CELL_WIDTH).clamp(1, tape_size);
+        let start = addr.saturating_sub(count / 2).min(tape_size - count);
+        let mut cells = String::from("cell ");
+        let mut values = String::from("value");
+        for cell in start..start + count {
+            let _ = write!(cells, " {cell:>5}");
+            if cell == addr {
+                let _ = write!(values, " \x1b[7m{:>5}\x1b[0m", self.intepreter.cell(cell));
+            } else {
+                let _ = write!(values, " {:>5}", self.intepreter.cell(cell));
+            }
+        }
+        line(&cells);
+        line(&values);
+        line("");
+
+        // Source line of the next instruction, scrolled to keep it in view.
+        // Its commands are underlined up to the end of the line.
+        match self.spans.get(self.instruction_ptr()) {
+            Some(&Span { start, end }) => {
+                let number = start.line;
+                let source = self.lines.get(number - 1).map_or(&[][..], Vec::as_slice);
+                let prefix = format!("{number:>5} | ");
+                let available = width.saturating_sub(prefix.len()).max(1);
+                // Columns count bytes, while the terminal shows characters
+                let characters = |column: usize| {
+                    String::from_utf8_lossy(&source[..(column - 1).min(source.len())])
+                        .chars()
+                        .count()
+                };
+                let caret = characters(start.column);
+                let last = if end.line == number {
+                    characters(end.column)
+                } else {
+                    characters(source.len() + 1)
+                };
+                let underline = last.saturating_sub(caret).max(1);
+                let source = String::from_utf8_lossy(source).replace('\t', " ");
+                let scroll = caret.saturating_sub(available / 2);
+                let visible = source
+                    .chars()
+                    .skip(scroll)
+                    .take(available)
+                    .collect::<String>();
+                line(&format!("{prefix}{visible}"));
+                line(&truncate(&format!(
+                    "{:>width$}{}",
+                    "",
+                    "^".repeat(underline),
+                    width = prefix.len() + caret - scroll
+                )));
+                line(&truncate(&format!(
+                    "next: {:?}",
+                    self.intepreter.program[self.instruction_ptr()]
+                )));
+            }
+            None => {
+                line("");
+                line("");
+                line("");
+            }
+        }
+        line("");
+
+        line("Output:");
+        let output = String::from_utf8_lossy(&self.output)
+            .chars()
+            .map(|char| match char {
+                '\n' => '\n',
+                '\t' => ' ',
+                char if char.is_control() => '.',
+                char => char,
+            })
+            .collect::<String>();
+        let lines = output.split('\n').collect::<Vec<_>>();
+        let shown = &lines[lines.len().saturating_sub(OUTPUT_LINES)..];
+        for index in 0..OUTPUT_LINES {
+            line(&truncate(shown.get(index).copied().unwrap_or_default()));
+        }
+        line("");
+        line(&truncate(
+            "space: pause/continue | s: single step | +: faster | -: slower | q: quit",
+        ));
+        frame.push_str("\x1b[J");
+
+        terminal.tty.write_all(frame.as_bytes())?;
+        terminal.tty.flush().context("flush terminal")
+    }
+
+    /// Show the execution until the user quits. Returns the error the
+    /// program failed with, if any.
+    fn run(&mut self, terminal: &mut Terminal) -> Result<()> {
+        let mut next_frame = Instant::now();
+        loop {
+            self.draw(terminal)?;
+
+            let timeout = match self.state {
+                State::Running => Some(next_frame.saturating_duration_since(Instant::now())),
+                _ => None,
+            };
+            match terminal.wait_key(timeout)? {
+                Some(b' ') => match self.state {
+                    State::Running => self.state = State::Paused,
+                    State::Paused => {
+                        self.state = State::Running;
+                        next_frame = Instant::now();
+                    }
+                    _ => {}
+                },
+                Some(b's') => {
+                    if matches!(self.state, State::Paused) {
+                        self.step(1);
+                    }
+                }
+                Some(b'+') => self.speed = (self.speed * 2).min(MAX_SPEED),
+                Some(b'-') => self.speed = (self.speed / 2).max(1),
+                // Ctrl-C is read as a key as well
+                Some(b'q' | 3) => break,
+                Some(_) => {}
+                None => {
+                    let steps = self.speed.div_ceil(FRAME_RATE);
+                    self.step(steps);
+                    let interval = Duration::from_secs(steps) / self.speed as u32;
+                    next_frame = (next_frame + interval).max(Instant::now());
+                }
+            }
+        }
+
+        match std::mem::replace(&mut self.state, State::Finished) {
+            State::Failed(e) => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Run the program in the interpreter while showing the tape and the source
+/// line executed in the terminal. The output of the program is shown below
+/// and written once the visualization ends.
+///
+/// Keys are read from the terminal, so the input of the program is read
+/// completely in advance, and is empty if it would come from the terminal as
+/// well.
+pub fn run(
+    program: Program,
+    spans: Vec<Span>,
+    code: &[u8],
+    options: &RunOptions,
+    output: &mut dyn Write,
+) -> Result<()> {
+    if program
+        .iter()
+        .any(|instruction| matches!(instruction, Instruction::Fork))
+    {
+        return Err(anyhow!("Programs which fork can not be visualized"));
+    }
+    let mut input = vec![];
+    let from_terminal =
+        matches!(options.input, InputSource::Stdin) && unsafe { libc::isatty(0) } == 1;
+    if !from_terminal {
+        options
+            .input
+            .open()?
+            .read_to_end(&mut input)
+            .context("read program input")?;
+    }
+
+    let mut visualizer = Visualizer {
+        intepreter: Intepreter::new(program, &options.config),
+        spans,
+        lines: code
+            .split(|&byte| byte == b'\n')
+            .map(<[u8]>::to_vec)
+            .collect(),
+        input,
+        input_position: 0,
+        output: vec![],
+        steps: 0,
+        speed: DEFAULT_SPEED,
+        state: State::Running,
+    };
+    let mut terminal = Terminal::open()?;
+    let result = visualizer.run(&mut terminal);
+    drop(terminal);
+
+    output
+        .write_all(&visualizer.output)
+        .and_then(|_| output.flush())
+        .context("writing data to output")?;
+    result
+}
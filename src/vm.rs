@@ -0,0 +1,443 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use crate::backend::{
+    read_byte, CellWidth, CompiledProgram, EofBehavior, ExecutionBackend, ExecutionConfig,
+};
+use crate::{Instruction, Program};
+use anyhow::{anyhow, Result};
+
+/// Executes a single operation, returning the index of the next one.
+type Handler = fn(&mut Vm<'_>, operand: usize, ip: usize) -> usize;
+
+/// An operation of the directly threaded code: the handler executing it,
+/// together with its operand. Jump targets are resolved in advance, so
+/// dispatching is a single indirect call.
+#[derive(Clone, Copy)]
+struct Op {
+    handler: Handler,
+    operand: usize,
+}
+
+/// Translate the program into threaded code. Runs of `+` and `-` become a
+/// single addition modulo the cell width and common loop idioms are replaced
+/// by dedicated operations:
+///
+/// - `[-]` and `[+]` clear the cell
+/// - `[->+<]` and similar loops add the cell to another one and clear it
+///
+/// The code ends with an operation stopping the VM.
+fn encode(program: &Program, mask: u32) -> Vec<Op> {
+    // Index of the operation every instruction starts at
+    let mut starts = Vec::with_capacity(program.len() + 1);
+    let mut code = Vec::with_capacity(program.len() + 1);
+    // Jumps to be patched with the operation of their target instruction
+    let mut jumps = vec![];
+    let mut position = 0;
+    while position < program.len() {
+        let op = |handler: Handler, operand: usize| Op { handler, operand };
+        starts.push(code.len());
+        if let Some((replacement, length)) = match_idiom(&program[position..]) {
+            code.push(replacement);
+            // The skipped instructions are never jumped to from outside
+            for _ in 1..length {
+                starts.push(code.len() - 1);
+            }
+            position += length;
+            continue;
+        }
+        match program[position] {
+            Instruction::AddrRight(count) => code.push(op(right, count)),
+            Instruction::AddrLeft(count) => code.push(op(left, count)),
+            Instruction::Inc(count) => code.push(op(add, (count as u32 & mask) as usize)),
+            Instruction::Dec(count) => {
+                code.push(op(add, ((count as u32).wrapping_neg() & mask) as usize))
+            }
+            Instruction::Output(count) => code.push(op(output, count)),
+            Instruction::Input(count) => code.push(op(input, count)),
+            Instruction::JmpForward(target) => {
+                jumps.push(code.len());
+                code.push(op(jump_if_zero, target));
+            }
+            Instruction::JmpBack(target) => {
+                jumps.push(code.len());
+                code.push(op(jump_if_not_zero, target));
+            }
+            Instruction::ProcedureStart(target) => {
+                jumps.push(code.len());
+                code.push(op(define_procedure, target));
+            }
+            Instruction::ProcedureEnd(_) => code.push(op(return_from_procedure, 0)),
+            Instruction::Call => code.push(op(call, 0)),
+            Instruction::Fork => unreachable!("forking programs are rejected by the check"),
+        }
+        position += 1;
+    }
+    starts.push(code.len());
+    code.push(Op {
+        handler: halt,
+        operand: 0,
+    });
+
+    for jump in jumps {
+        code[jump].operand = starts[code[jump].operand];
+    }
+    code
+}
+
+/// Match a loop at the start of the instructions, which can be replaced by a
+/// single operation. Returns the operation and the number of instructions it
+/// replaces.
+fn match_idiom(instructions: &[Instruction]) -> Option<(Op, usize)> {
+    use Instruction::*;
+    match instructions {
+        [JmpForward(_), Dec(1) | Inc(1), JmpBack(_), ..] => Some((
+            Op {
+                handler: clear,
+                operand: 0,
+            },
+            3,
+        )),
+        [JmpForward(_), Dec(1), AddrRight(a), Inc(1), AddrLeft(b), JmpBack(_), ..] if a == b => {
+            Some((
+                Op {
+                    handler: move_right,
+                    operand: *a,
+                },
+                6,
+            ))
+        }
+        [JmpForward(_), Dec(1), AddrLeft(a), Inc(1), AddrRight(b), JmpBack(_), ..] if a == b => {
+            Some((
+                Op {
+                    handler: move_left,
+                    operand: *a,
+                },
+                6,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Portable interpreter of threaded code, which is considerably faster than
+/// `Intepreter` as every operation is dispatched without decoding it.
+///
+/// The pointer is checked whenever it is moved, so the cells are accessed
+/// without further checks.
+struct Vm<'a> {
+    memory: Vec<u32>,
+    mask: u32,
+    eof: EofBehavior,
+    cell_width: CellWidth,
+    addr: usize,
+    procedures: HashMap<u32, usize>,
+    call_stack: Vec<usize>,
+    input: &'a mut dyn Read,
+    output: &'a mut dyn Write,
+    /// Index of the operation stopping the VM
+    end: usize,
+    error: Option<anyhow::Error>,
+}
+
+impl Vm<'_> {
+    /// Maximal nesting depth of procedure calls, to stop runaway recursion.
+    const MAX_CALL_DEPTH: usize = 1 << 20;
+
+    fn cell(&mut self) -> &mut u32 {
+        // The pointer is kept on the tape by every operation moving it
+        unsafe { self.memory.get_unchecked_mut(self.addr) }
+    }
+
+    /// Stop the VM with the given error.
+    fn fail(&mut self, error: anyhow::Error) -> usize {
+        self.error = Some(error);
+        self.end
+    }
+
+    fn run(&mut self, code: &[Op]) -> Result<()> {
+        let mut ip = 0;
+        while ip != self.end {
+            let op = code[ip];
+            ip = (op.handler)(self, op.operand, ip);
+        }
+        self.error.take().map_or(Ok(()), Err)
+    }
+}
+
+fn right(vm: &mut Vm<'_>, count: usize, ip: usize) -> usize {
+    let addr = vm.addr + count;
+    if addr >= vm.memory.len() {
+        return vm.fail(anyhow!(
+            "Pointer moved beyond the end of the tape to cell {addr}"
+        ));
+    }
+    vm.addr = addr;
+    ip + 1
+}
+
+fn left(vm: &mut Vm<'_>, count: usize, ip: usize) -> usize {
+    match vm.addr.checked_sub(count) {
+        Some(addr) => {
+            vm.addr = addr;
+            ip + 1
+        }
+        None => vm.fail(anyhow!("Pointer moved before the start of the tape")),
+    }
+}
+
+fn add(vm: &mut Vm<'_>, value: usize, ip: usize) -> usize {
+    let mask = vm.mask;
+    let cell = vm.cell();
+    *cell = cell.wrapping_add(value as u32) & mask;
+    ip + 1
+}
+
+fn output(vm: &mut Vm<'_>, count: usize, ip: usize) -> usize {
+    let value = *vm.cell() as u8;
+    let result = (0..count)
+        .try_for_each(|_| vm.output.write_all(&[value]))
+        .and_then(|_| vm.output.flush());
+    match result {
+        Ok(()) => ip + 1,
+        Err(e) => vm.fail(anyhow::Error::new(e).context("writing data to output")),
+    }
+}
+
+fn input(vm: &mut Vm<'_>, count: usize, ip: usize) -> usize {
+    for _ in 0..count {
+        let value = match read_byte(vm.input) {
+            Ok(Some(value)) => value.into(),
+            Ok(None) => {
+                let cell = *vm.cell();
+                vm.eof.apply(cell, vm.cell_width)
+            }
+            Err(e) => return vm.fail(e),
+        };
+        *vm.cell() = value;
+    }
+    ip + 1
+}
+
+fn jump_if_zero(vm: &mut Vm<'_>, target: usize, ip: usize) -> usize {
+    if *vm.cell() == 0 {
+        target
+    } else {
+        ip + 1
+    }
+}
+
+fn jump_if_not_zero(vm: &mut Vm<'_>, target: usize, ip: usize) -> usize {
+    if *vm.cell() != 0 {
+        target
+    } else {
+        ip + 1
+    }
+}
+
+fn clear(vm: &mut Vm<'_>, _: usize, ip: usize) -> usize {
+    *vm.cell() = 0;
+    ip + 1
+}
+
+/// Add the current cell to the given one and clear it.
+fn move_value(vm: &mut Vm<'_>, target: usize, ip: usize) -> usize {
+    let value = *vm.cell();
+    vm.memory[target] = vm.memory[target].wrapping_add(value) & vm.mask;
+    *vm.cell() = 0;
+    ip + 1
+}
+
+fn move_right(vm: &mut Vm<'_>, offset: usize, ip: usize) -> usize {
+    // The loop is not entered for an empty cell, so the pointer never moves
+    if *vm.cell() == 0 {
+        return ip + 1;
+    }
+    let target = vm.addr + offset;
+    if target >= vm.memory.len() {
+        return vm.fail(anyhow!(
+            "Pointer moved beyond the end of the tape to cell {target}"
+        ));
+    }
+    move_value(vm, target, ip)
+}
+
+fn move_left(vm: &mut Vm<'_>, offset: usize, ip: usize) -> usize {
+    if *vm.cell() == 0 {
+        return ip + 1;
+    }
+    match vm.addr.checked_sub(offset) {
+        Some(target) => move_value(vm, target, ip),
+        None => vm.fail(anyhow!("Pointer moved before the start of the tape")),
+    }
+}
+
+fn define_procedure(vm: &mut Vm<'_>, target: usize, ip: usize) -> usize {
+    let number = *vm.cell();
+    vm.procedures.insert(number, ip + 1);
+    target
+}
+
+fn return_from_procedure(vm: &mut Vm<'_>, _: usize, _: usize) -> usize {
+    vm.call_stack
+        .pop()
+        .expect("procedures to be only entered by calls")
+}
+
+fn call(vm: &mut Vm<'_>, _: usize, ip: usize) -> usize {
+    let number = *vm.cell();
+    let Some(&start) = vm.procedures.get(&number) else {
+        return vm.fail(anyhow!("Call of undefined procedure {number}"));
+    };
+    if vm.call_stack.len() >= Vm::MAX_CALL_DEPTH {
+        return vm.fail(anyhow!(
+            "Procedure calls nested deeper than {}",
+            Vm::MAX_CALL_DEPTH
+        ));
+    }
+    vm.call_stack.push(ip + 1);
+    start
+}
+
+fn halt(vm: &mut Vm<'_>, _: usize, _: usize) -> usize {
+    vm.end
+}
+
+/// Portable backend executing threaded code.
+pub struct VmBackend;
+
+impl ExecutionBackend for VmBackend {
+    fn name(&self) -> &'static str {
+        "vm"
+    }
+
+    fn check(&self, program: &Program, config: &ExecutionConfig) -> Result<()> {
+        if config.snapshots {
+            return Err(anyhow!("snapshots are not supported"));
+        }
+        if program
+            .iter()
+            .any(|instruction| matches!(instruction, Instruction::Fork))
+        {
+            return Err(anyhow!("forking threads is not supported"));
+        }
+        Ok(())
+    }
+
+    fn compile(
+        &self,
+        program: Program,
+        config: &ExecutionConfig,
+    ) -> Result<Box<dyn CompiledProgram>> {
+        if config.tape_size == 0 {
+            return Err(anyhow!("The tape needs at least a single cell"));
+        }
+        let mask = config.cell_width.mask();
+        Ok(Box::new(VmProgram {
+            code: encode(&program, mask),
+            config: config.clone(),
+        }))
+    }
+}
+
+/// A program translated into threaded code.
+struct VmProgram {
+    code: Vec<Op>,
+    config: ExecutionConfig,
+}
+
+impl CompiledProgram for VmProgram {
+    fn run(&mut self, input: &mut dyn Read, output: &mut dyn Write) -> Result<()> {
+        let mut vm = Vm {
+            memory: vec![0; self.config.tape_size],
+            mask: self.config.cell_width.mask(),
+            eof: self.config.eof,
+            cell_width: self.config.cell_width,
+            addr: 0,
+            procedures: HashMap::new(),
+            call_stack: vec![],
+            input,
+            output,
+            end: self.code.len() - 1,
+            error: None,
+        };
+        vm.run(&self.code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::Dialect;
+    use crate::{Lexer, Parser};
+    use std::rc::Rc;
+
+    fn parse(code: &str) -> Program {
+        let dialect = Rc::new(Dialect::load("brainfuck").unwrap());
+        Parser::default()
+            .parse_program(&mut Lexer::new(code.as_bytes(), dialect))
+            .unwrap()
+    }
+
+    /// Run the program on a tape of four cells, returning its output.
+    fn run(code: &str) -> Result<Vec<u8>> {
+        let config = ExecutionConfig {
+            tape_size: 4,
+            ..ExecutionConfig::default()
+        };
+        let mut compiled = VmBackend.compile(parse(code), &config)?;
+        let mut output = vec![];
+        compiled.run(&mut std::io::empty(), &mut output)?;
+        Ok(output)
+    }
+
+    #[test]
+    fn replaces_idioms() {
+        // Each loop becomes a single operation, followed by the halt
+        for code in ["[-]", "[+]", "[->+<]", "[->>>+<<<]", "[-<+>]"] {
+            assert_eq!(encode(&parse(code), 0xFF).len(), 2, "{code}");
+        }
+        assert_eq!(encode(&parse("[->+<<]"), 0xFF).len(), 7);
+    }
+
+    #[test]
+    fn clears_cells_at_the_edges() {
+        assert_eq!(run("+++[-].").unwrap(), [0]);
+        assert_eq!(run(">>>---[+].").unwrap(), [0]);
+    }
+
+    #[test]
+    fn moves_right_to_the_last_cell() {
+        assert_eq!(run("+++[->>>+<<<]>>>.").unwrap(), [3]);
+        assert_eq!(run(">>+>++<[->+<]>.").unwrap(), [3]);
+        // An empty cell is not moved, so the pointer stays on the tape
+        assert_eq!(run(">>>[->+<].").unwrap(), [0]);
+        let error = run(">>>+[->+<]").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Pointer moved beyond the end of the tape to cell 4"
+        );
+        let error = run(">+[->>>+<<<]").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Pointer moved beyond the end of the tape to cell 4"
+        );
+    }
+
+    #[test]
+    fn moves_left_to_the_first_cell() {
+        assert_eq!(run(">>>++[-<<<+>>>]<<<.").unwrap(), [2]);
+        assert_eq!(run("+>-[-<+>]<.").unwrap(), [0]);
+        assert_eq!(run("[-<+>].").unwrap(), [0]);
+        let error = run("+[-<+>]").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Pointer moved before the start of the tape"
+        );
+        let error = run(">>+[-<<<+>>>]").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Pointer moved before the start of the tape"
+        );
+    }
+}
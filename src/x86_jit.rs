@@ -1,8 +1,8 @@
-use std::fs::File;
-use std::io::Write;
+use std::collections::HashMap;
 
+use crate::backend::JitBackend;
 use crate::{Instruction, Program};
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Result};
 
 pub enum Operand {
     Register(u8),
@@ -10,6 +10,9 @@ pub enum Operand {
     Immediate8(u8),
     MemoryByRegister(u8),
     MemoryByRegisterAndOffset(u8, u8),
+    MemoryByRegisterAndDisplacement8(u8, u8),
+    /// Position in the generated code, addressed relative to RIP
+    RipRelative(usize),
 }
 
 #[derive(Default)]
@@ -19,6 +22,8 @@ pub struct X86Assembler {
 
 impl X86Assembler {
     const RAX: u8 = 0x00;
+    const RCX: u8 = 0x01;
+    const RSP: u8 = 0x04;
     const RDI: u8 = 0x07;
     const RSI: u8 = 0x06;
     const RDX: u8 = 0x02;
@@ -41,12 +46,7 @@ impl X86Assembler {
                 Operand::MemoryByRegisterAndOffset(src_reg, offset_reg),
             ) => {
                 // movzx dst, byte [src_reg + offset_reg*1]
-                self.emit(&[
-                    0x0F,
-                    0xB6,
-                    0x04,
-                    0x00 | (dst_reg << 3) | src_reg | offset_reg,
-                ]);
+                self.emit(&[0x0F, 0xB6, 0x04, (dst_reg << 3) | src_reg | offset_reg]);
             }
             _ => todo!("not implemented"),
         }
@@ -56,7 +56,7 @@ impl X86Assembler {
         match (dst, src) {
             (Operand::Register(dst_reg), Operand::MemoryByRegister(src_reg)) => {
                 // mov dst, [src]
-                self.emit(&[0x48, 0x8B, 0x00 | (dst_reg << 3) | src_reg]);
+                self.emit(&[0x48, 0x8B, (dst_reg << 3) | src_reg]);
             }
             (Operand::Register(dst), Operand::Immediate(src)) => {
                 // mov dst, src
@@ -72,7 +72,7 @@ impl X86Assembler {
             }
             (Operand::MemoryByRegister(dst), Operand::Register(src)) => {
                 // mov [dst], src
-                self.emit(&[0x48, 0x89, 0x00 | (src << 3) | dst]);
+                self.emit(&[0x48, 0x89, (src << 3) | dst]);
             }
             (
                 Operand::Register(dst_reg),
@@ -83,13 +83,57 @@ impl X86Assembler {
                     0x48,
                     0x8B,
                     0x04 | (dst_reg << 3),
-                    0x00 | (offset_reg << 3) | src_reg,
+                    (offset_reg << 3) | src_reg,
                 ]);
             }
             (Operand::Register(dst), Operand::Register(src)) => {
                 // mov dst, src
                 self.emit(&[0x48, 0x89, 0xC0 | (src << 3) | dst]);
             }
+            (
+                Operand::Register(dst),
+                Operand::MemoryByRegisterAndDisplacement8(src, displacement),
+            ) => {
+                // mov dst, [src + displacement]
+                self.emit(&[0x48, 0x8B, 0x40 | (dst << 3) | src, displacement]);
+            }
+            (
+                Operand::MemoryByRegisterAndDisplacement8(dst, displacement),
+                Operand::Register(src),
+            ) => {
+                // mov [dst + displacement], src
+                self.emit(&[0x48, 0x89, 0x40 | (src << 3) | dst, displacement]);
+            }
+            (
+                Operand::MemoryByRegisterAndDisplacement8(dst, displacement),
+                Operand::Immediate(value),
+            ) => {
+                // mov qword [dst + displacement], value
+                self.emit(&[0x48, 0xC7, 0x40 | dst, displacement]);
+                self.emit(&(value as u32).to_le_bytes());
+            }
+            _ => todo!("not implemented"),
+        }
+    }
+
+    fn emit_lea(&mut self, dst: Operand, src: Operand) {
+        match (dst, src) {
+            (Operand::Register(dst), Operand::RipRelative(target)) => {
+                // lea dst, [rip + relative_target]
+                let relative_target = target as i32 - (self.position() + 7) as i32;
+                self.emit(&[0x48, 0x8D, 0x05 | (dst << 3)]);
+                self.emit(&relative_target.to_le_bytes());
+            }
+            _ => todo!("not implemented"),
+        }
+    }
+
+    fn emit_shift_left(&mut self, dst: Operand, src: Operand) {
+        match (dst, src) {
+            (Operand::Register(dst), Operand::Immediate8(value)) => {
+                // shl dst, value
+                self.emit(&[0x48, 0xC1, 0xE0 | dst, value]);
+            }
             _ => todo!("not implemented"),
         }
     }
@@ -117,7 +161,7 @@ impl X86Assembler {
                 Operand::Immediate8(value),
             ) => {
                 // add [dst_reg + offset_reg*1] byte value
-                self.emit(&[0x80, 0x04, 0x00 | (dst_reg << 3) | offset_reg, value]);
+                self.emit(&[0x80, 0x04, (dst_reg << 3) | offset_reg, value]);
             }
             (Operand::Register(dst), Operand::Register(src)) => {
                 // add dst, src
@@ -150,7 +194,14 @@ impl X86Assembler {
                 Operand::Immediate8(value),
             ) => {
                 // sub byte [dst_reg + offset_reg], value
-                self.emit(&[0x80, 0x2C, 0x00 | (dst_reg << 3) | offset_reg, value]);
+                self.emit(&[0x80, 0x2C, (dst_reg << 3) | offset_reg, value]);
+            }
+            (
+                Operand::MemoryByRegisterAndDisplacement8(dst, displacement),
+                Operand::Immediate8(value),
+            ) => {
+                // sub qword [dst + displacement], value
+                self.emit(&[0x48, 0x83, 0x68 | dst, displacement, value]);
             }
             _ => todo!("not implemented"),
         }
@@ -182,6 +233,11 @@ impl X86Assembler {
                 // cmp dst, byte value
                 self.emit(&[0x48, 0x83, 0xF8 | dst, value]);
             }
+            (Operand::Register(dst), Operand::Immediate(value)) => {
+                // cmp dst, value
+                self.emit(&[0x48, 0x81, 0xF8 | dst]);
+                self.emit(&(value as u32).to_le_bytes());
+            }
             _ => todo!("not implemented"),
         }
     }
@@ -208,205 +264,297 @@ impl X86Assembler {
         self.emit(&relative_target.to_le_bytes());
     }
 
+    fn emit_jump_if_above_or_equal(&mut self, target: usize) {
+        let src_pos = (self.position() + 6) as i32;
+        let relative_target = target as i32 - src_pos;
+
+        // jae relative_target
+        self.emit(&[0x0F, 0x83]);
+        self.emit(&relative_target.to_le_bytes());
+    }
+
+    fn emit_jump(&mut self, target: usize) {
+        let src_pos = (self.position() + 5) as i32;
+        let relative_target = target as i32 - src_pos;
+
+        // jmp relative_target
+        self.emit(&[0xE9]);
+        self.emit(&relative_target.to_le_bytes());
+    }
+
     fn patch_jump_target(&mut self, patch_target_pos: usize, new_target: usize) {
         let relative_target = new_target as i32 - patch_target_pos as i32;
         self.code[patch_target_pos - 4..patch_target_pos]
             .copy_from_slice(&relative_target.to_le_bytes());
     }
 
-    fn emit_syscall(&mut self) {
-        self.emit(&[0x0F, 0x05]);
+    fn emit_call(&mut self, target: Operand) {
+        match target {
+            Operand::MemoryByRegister(target) => {
+                // call [target]
+                self.emit(&[0xFF, 0x10 | target]);
+            }
+            Operand::MemoryByRegisterAndDisplacement8(target, displacement) => {
+                // call [target + displacement]
+                self.emit(&[0xFF, 0x50 | target, displacement]);
+            }
+            Operand::Register(target) => {
+                // call target
+                self.emit(&[0xFF, 0xD0 | target]);
+            }
+            _ => todo!("not implemented"),
+        }
+    }
+
+    fn emit_io_call(&mut self, function: Operand) {
+        use Operand::*;
+        self.emit_push(Register(Self::RDI));
+        self.emit_push(Register(Self::RSI));
+        self.emit_push(Register(Self::RDX));
+
+        // Load offset into "memory" into RAX
+        self.emit_mov(Register(Self::RAX), MemoryByRegister(Self::RSI));
+
+        // Add RDI to RAX to get the memory location
+        self.emit_add(Register(Self::RAX), Register(Self::RDI));
+
+        // Put the memory location into RSI (second argument)
+        self.emit_mov(Register(Self::RSI), Register(Self::RAX));
+
+        // Put the context into RDI (first argument)
+        self.emit_mov(Register(Self::RDI), Register(Self::RDX));
+
+        // Call the I/O function stored in the context. The three pushes above
+        // keep the stack 16 byte aligned.
+        self.emit_call(function);
+
+        self.emit_pop(Register(Self::RDX));
+        self.emit_pop(Register(Self::RSI));
+        self.emit_pop(Register(Self::RDI));
+    }
+
+    /// Load the address of the entry for the procedure numbered by the
+    /// current cell into RCX.
+    fn emit_procedure_entry(&mut self) {
+        use Operand::*;
+        self.emit_mov(Register(Self::RAX), MemoryByRegister(Self::RSI));
+        self.emit_movzx(
+            Register(Self::RAX),
+            MemoryByRegisterAndOffset(Self::RDI, Self::RAX),
+        );
+        self.emit_mov(
+            Register(Self::RCX),
+            MemoryByRegisterAndDisplacement8(Self::RDX, 16),
+        );
+        self.emit_shift_left(Register(Self::RAX), Immediate8(3));
+        self.emit_add(Register(Self::RCX), Register(Self::RAX));
     }
 
     fn emit_return(&mut self) {
         self.emit(&[0xC3])
     }
-}
 
-pub struct JitCompiler {
-    assembler: X86Assembler,
-    program: Program,
-    memory: Vec<u8>,
-    addr: usize,
+    /// Continue at the `]` stored in the context, if any. Returns the
+    /// positions of the jumps to backpatch by the index of their `]`.
+    fn emit_resume_dispatch(&mut self, program: &Program) -> HashMap<usize, usize> {
+        use Operand::*;
+        self.emit_mov(
+            Register(Self::RAX),
+            MemoryByRegisterAndDisplacement8(Self::RDX, 48),
+        );
+        self.emit_mov(
+            MemoryByRegisterAndDisplacement8(Self::RDX, 48),
+            Immediate(0),
+        );
+        let mut resume_jumps = HashMap::new();
+        for (index, instruction) in program.iter().enumerate() {
+            if let Instruction::JmpBack(_) = instruction {
+                self.emit_compare(Register(Self::RAX), Immediate(index));
+                self.emit_jump_if_zero(0x00c0ffee);
+                resume_jumps.insert(index, self.position());
+            }
+        }
+        resume_jumps
+    }
+
+    /// Count down the loop iterations left and return, once there are none,
+    /// telling where to continue.
+    fn emit_loop_budget(&mut self, index: usize) {
+        use Operand::*;
+        self.emit_sub(
+            MemoryByRegisterAndDisplacement8(Self::RDX, 40),
+            Immediate8(1),
+        );
+        self.emit_jump_if_above_or_equal(0x00c0ffee);
+        let budget_left = self.position();
+        self.emit_mov(
+            MemoryByRegisterAndDisplacement8(Self::RDX, 48),
+            Immediate(index),
+        );
+        self.emit_mov(
+            Register(Self::RSP),
+            MemoryByRegisterAndDisplacement8(Self::RDX, 24),
+        );
+        self.emit_return();
+        let patch_target = self.position();
+        self.patch_jump_target(budget_left, patch_target);
+    }
 }
 
-impl JitCompiler {
-    pub fn new(program: Program, assembler: X86Assembler) -> Self {
-        Self {
-            assembler,
-            program,
-            memory: vec![0; 640000],
-            addr: 0,
-        }
+impl JitBackend for X86Assembler {
+    fn architecture(&self) -> &'static str {
+        "x86_64"
     }
 
-    pub fn compile(&mut self) -> Result<()> {
+    fn compile(&mut self, program: &Program, snapshots: bool) -> Result<()> {
         let mut forward_jumps = vec![];
+        let mut procedure_starts = vec![];
+        let mut undefined_procedure_jumps = vec![];
 
-        self.assembler.clear();
+        self.clear();
         // RDI will be the pointer to the memory array
         // RSI will be the offset into the memory array
-        for i in 0..self.program.len() {
+        // RDX will be the pointer to the JitContext
+        //
+        // Remember the stack pointer to be able to abort from within
+        // procedures.
+        self.emit_mov(
+            Operand::MemoryByRegisterAndDisplacement8(Self::RDX, 24),
+            Operand::Register(Self::RSP),
+        );
+        let mut resume_jumps = HashMap::new();
+        if snapshots {
+            resume_jumps = self.emit_resume_dispatch(program);
+        }
+        for (index, instruction) in program.iter().enumerate() {
             use Operand::*;
-            match self.program[i] {
+            match *instruction {
                 Instruction::AddrRight(value) => {
-                    self.assembler.emit_mov(
-                        Register(X86Assembler::RAX),
-                        MemoryByRegister(X86Assembler::RSI),
-                    );
-                    self.assembler
-                        .emit_add(Register(X86Assembler::RAX), Immediate(value));
-                    self.assembler.emit_mov(
-                        MemoryByRegister(X86Assembler::RSI),
-                        Register(X86Assembler::RAX),
-                    );
+                    self.emit_mov(Register(Self::RAX), MemoryByRegister(Self::RSI));
+                    self.emit_add(Register(Self::RAX), Immediate(value));
+                    self.emit_mov(MemoryByRegister(Self::RSI), Register(Self::RAX));
                 }
                 Instruction::AddrLeft(value) => {
-                    self.assembler.emit_mov(
-                        Register(X86Assembler::RAX),
-                        MemoryByRegister(X86Assembler::RSI),
-                    );
-                    self.assembler
-                        .emit_sub(Register(X86Assembler::RAX), Immediate(value));
-                    self.assembler.emit_mov(
-                        MemoryByRegister(X86Assembler::RSI),
-                        Register(X86Assembler::RAX),
-                    );
+                    self.emit_mov(Register(Self::RAX), MemoryByRegister(Self::RSI));
+                    self.emit_sub(Register(Self::RAX), Immediate(value));
+                    self.emit_mov(MemoryByRegister(Self::RSI), Register(Self::RAX));
                 }
                 Instruction::Inc(value) => {
-                    self.assembler.emit_mov(
-                        Register(X86Assembler::RAX),
-                        MemoryByRegister(X86Assembler::RSI),
-                    );
-                    self.assembler.emit_add(
-                        MemoryByRegisterAndOffset(X86Assembler::RDI, X86Assembler::RAX),
+                    self.emit_mov(Register(Self::RAX), MemoryByRegister(Self::RSI));
+                    self.emit_add(
+                        MemoryByRegisterAndOffset(Self::RDI, Self::RAX),
                         Immediate8(value),
                     );
                 }
                 Instruction::Dec(value) => {
-                    self.assembler.emit_mov(
-                        Register(X86Assembler::RAX),
-                        MemoryByRegister(X86Assembler::RSI),
-                    );
-                    self.assembler.emit_sub(
-                        MemoryByRegisterAndOffset(X86Assembler::RDI, X86Assembler::RAX),
+                    self.emit_mov(Register(Self::RAX), MemoryByRegister(Self::RSI));
+                    self.emit_sub(
+                        MemoryByRegisterAndOffset(Self::RDI, Self::RAX),
                         Immediate8(value),
                     );
                 }
                 Instruction::Output(value) => {
                     for _ in 0..value {
-                        self.assembler.emit_push(Register(X86Assembler::RDI));
-                        self.assembler.emit_push(Register(X86Assembler::RSI));
-
-                        // Load offset into "memory" into RAX
-                        self.assembler.emit_mov(
-                            Register(X86Assembler::RAX),
-                            MemoryByRegister(X86Assembler::RSI),
-                        );
-
-                        // Add RDI to RAX to get the memory location
-                        self.assembler
-                            .emit_add(Register(X86Assembler::RAX), Register(X86Assembler::RDI));
-
-                        // Put the memory location into RSI
-                        self.assembler
-                            .emit_mov(Register(X86Assembler::RSI), Register(X86Assembler::RAX));
-
-                        // Load syscall number into RAX (write)
-                        self.assembler
-                            .emit_mov(Register(X86Assembler::RAX), Immediate(1));
-                        // Load stdout file descriptor into RDI
-                        self.assembler
-                            .emit_mov(Register(X86Assembler::RDI), Immediate(1));
-                        // Load size into RDX
-                        self.assembler
-                            .emit_mov(Register(X86Assembler::RDX), Immediate(1));
-                        // Perform syscall
-                        self.assembler.emit_syscall();
-
-                        self.assembler.emit_pop(Register(X86Assembler::RSI));
-                        self.assembler.emit_pop(Register(X86Assembler::RDI));
+                        self.emit_io_call(MemoryByRegister(Self::RDI));
+                    }
+                }
+                Instruction::Input(value) => {
+                    for _ in 0..value {
+                        self.emit_io_call(MemoryByRegisterAndDisplacement8(Self::RDI, 8));
                     }
                 }
-                Instruction::Input(_) => todo!(),
                 Instruction::JmpForward(_) => {
-                    self.assembler.emit_mov(
-                        Register(X86Assembler::RAX),
-                        MemoryByRegister(X86Assembler::RSI),
-                    );
-                    self.assembler.emit_movzx(
-                        Register(X86Assembler::RAX),
-                        MemoryByRegisterAndOffset(X86Assembler::RDI, X86Assembler::RAX),
+                    self.emit_mov(Register(Self::RAX), MemoryByRegister(Self::RSI));
+                    self.emit_movzx(
+                        Register(Self::RAX),
+                        MemoryByRegisterAndOffset(Self::RDI, Self::RAX),
                     );
-                    self.assembler
-                        .emit_compare(Register(X86Assembler::RAX), Immediate8(0));
+                    self.emit_compare(Register(Self::RAX), Immediate8(0));
                     //                     // Backpatch the jump target once we know it.
-                    self.assembler.emit_jump_if_zero(0x00c0ffee);
-                    forward_jumps.push(self.assembler.position());
+                    self.emit_jump_if_zero(0x00c0ffee);
+                    forward_jumps.push(self.position());
                 }
                 Instruction::JmpBack(_) => {
-                    self.assembler.emit_mov(
-                        Register(X86Assembler::RAX),
-                        MemoryByRegister(X86Assembler::RSI),
-                    );
-                    self.assembler.emit_movzx(
-                        Register(X86Assembler::RAX),
-                        MemoryByRegisterAndOffset(X86Assembler::RDI, X86Assembler::RAX),
+                    if snapshots {
+                        let resume_target = self.position();
+                        if let Some(jump) = resume_jumps.remove(&index) {
+                            self.patch_jump_target(jump, resume_target);
+                        }
+                        self.emit_loop_budget(index);
+                    }
+                    self.emit_mov(Register(Self::RAX), MemoryByRegister(Self::RSI));
+                    self.emit_movzx(
+                        Register(Self::RAX),
+                        MemoryByRegisterAndOffset(Self::RDI, Self::RAX),
                     );
-                    self.assembler
-                        .emit_compare(Register(X86Assembler::RAX), Immediate8(0));
+                    self.emit_compare(Register(Self::RAX), Immediate8(0));
                     let target = forward_jumps.pop().expect("expected forward jump target");
-                    self.assembler.emit_jump_if_non_zero(target);
+                    self.emit_jump_if_non_zero(target);
 
                     // Backpatch the forward jump target
-                    let patch_target = self.assembler.position();
-                    self.assembler.patch_jump_target(target, patch_target);
+                    let patch_target = self.position();
+                    self.patch_jump_target(target, patch_target);
+                }
+                Instruction::ProcedureStart(_) if snapshots => {
+                    return Err(anyhow!(
+                        "Snapshots of programs with procedures are not supported by the JIT"
+                    ));
+                }
+                Instruction::ProcedureStart(_) => {
+                    // Register the body following this definition and jump
+                    // over it, both targets are backpatched.
+                    self.emit_procedure_entry();
+                    self.emit_lea(Register(Self::RAX), RipRelative(0));
+                    let body_address = self.position();
+                    self.emit_mov(MemoryByRegister(Self::RCX), Register(Self::RAX));
+                    self.emit_jump(0x00c0ffee);
+                    let body = self.position();
+                    self.patch_jump_target(body_address, body);
+                    procedure_starts.push(body);
+                }
+                Instruction::ProcedureEnd(_) => {
+                    self.emit_return();
+                    let target = procedure_starts.pop().expect("expected procedure start");
+                    let patch_target = self.position();
+                    self.patch_jump_target(target, patch_target);
+                }
+                Instruction::Call => {
+                    self.emit_procedure_entry();
+                    self.emit_mov(Register(Self::RAX), MemoryByRegister(Self::RCX));
+                    self.emit_compare(Register(Self::RAX), Immediate8(0));
+                    self.emit_jump_if_zero(0x00c0ffee);
+                    undefined_procedure_jumps.push(self.position());
+                    // The padding keeps the stack 16 byte aligned within the
+                    // procedure, just like on the top level.
+                    self.emit_push(Register(Self::RCX));
+                    self.emit_call(Register(Self::RAX));
+                    self.emit_pop(Register(Self::RCX));
+                }
+                Instruction::Fork => {
+                    return Err(anyhow!("Forking threads is not supported by the JIT"));
                 }
             }
         }
-        self.assembler.emit_return();
+        self.emit_return();
 
-        // write the code to a file
-        let mut file = File::create("output.bin").context("creating output file")?;
-        file.write_all(&self.assembler.code)
-            .context("writing output file")?;
-        drop(file);
+        if !undefined_procedure_jumps.is_empty() {
+            let undefined_procedure = self.position();
+            for jump in undefined_procedure_jumps {
+                self.patch_jump_target(jump, undefined_procedure);
+            }
+            self.emit_io_call(Operand::MemoryByRegisterAndDisplacement8(Self::RDI, 32));
+            // Unwind all procedure calls at once
+            self.emit_mov(
+                Operand::Register(Self::RSP),
+                Operand::MemoryByRegisterAndDisplacement8(Self::RDX, 24),
+            );
+            self.emit_return();
+        }
 
         Ok(())
     }
 
-    pub fn run(&mut self) {
-        let jit_fn = memory_map_executable_code(&self.assembler.code).unwrap();
-        jit_fn(self.memory.as_ptr(), &mut self.addr as *mut usize);
+    fn code(&self) -> &[u8] {
+        &self.code
     }
 }
-
-fn memory_map_executable_code(code: &Vec<u8>) -> Result<extern "C" fn(*const u8, *mut usize)> {
-    let func: extern "C" fn(*const u8, *mut usize) = unsafe {
-        // 1. mmap to map read/write anonymous memory of size code
-        let ptr = libc::mmap(
-            0 as *mut libc::c_void,
-            code.len(),
-            libc::PROT_READ | libc::PROT_WRITE,
-            libc::MAP_ANON | libc::MAP_PRIVATE,
-            -1,
-            0,
-        );
-        if ptr == libc::MAP_FAILED {
-            return Err(std::io::Error::last_os_error())
-                .context("memory mapping region for executable code");
-        }
-        // 2. copy code to memory
-        std::ptr::copy_nonoverlapping(code.as_ptr(), ptr as *mut u8, code.len());
-        // 3. mprotect the memory to read/exec
-        let result = libc::mprotect(ptr, code.len(), libc::PROT_EXEC | libc::PROT_READ);
-        if result == -1 {
-            return Err(std::io::Error::last_os_error())
-                .context("making memory mapped region executable");
-        }
-        // 4. reinterpret_cast memory pointer to function signature
-        std::mem::transmute(ptr)
-    };
-
-    Ok(func)
-}
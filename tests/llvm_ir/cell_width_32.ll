@@ -0,0 +1,418 @@
+; Generated by rust_brain from brainfuck file conformance/cell_width.b, do not edit.
+
+@tape = internal global [640000 x i32] zeroinitializer
+@ptr = internal global i64 0
+
+declare i32 @getchar()
+declare i32 @putchar(i32)
+
+define i32 @main() {
+entry:
+  %t1 = load i64, ptr @ptr
+  %t2 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t1
+  %t3 = load i32, ptr %t2
+  %t4 = add i32 %t3, 16
+  store i32 %t4, ptr %t2
+  br label %loop_1
+loop_1:
+  %t5 = load i64, ptr @ptr
+  %t6 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t5
+  %t7 = load i32, ptr %t6
+  %t8 = icmp ne i32 %t7, 0
+  br i1 %t8, label %body_1, label %end_1
+body_1:
+  %t9 = load i64, ptr @ptr
+  %t10 = add i64 %t9, 1
+  store i64 %t10, ptr @ptr
+  %t11 = load i64, ptr @ptr
+  %t12 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t11
+  %t13 = load i32, ptr %t12
+  %t14 = add i32 %t13, 16
+  store i32 %t14, ptr %t12
+  %t15 = load i64, ptr @ptr
+  %t16 = sub i64 %t15, 1
+  store i64 %t16, ptr @ptr
+  %t17 = load i64, ptr @ptr
+  %t18 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t17
+  %t19 = load i32, ptr %t18
+  %t20 = sub i32 %t19, 1
+  store i32 %t20, ptr %t18
+  br label %loop_1
+end_1:
+  %t21 = load i64, ptr @ptr
+  %t22 = add i64 %t21, 3
+  store i64 %t22, ptr @ptr
+  %t23 = load i64, ptr @ptr
+  %t24 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t23
+  %t25 = load i32, ptr %t24
+  %t26 = add i32 %t25, 1
+  store i32 %t26, ptr %t24
+  %t27 = load i64, ptr @ptr
+  %t28 = sub i64 %t27, 2
+  store i64 %t28, ptr @ptr
+  br label %loop_10
+loop_10:
+  %t29 = load i64, ptr @ptr
+  %t30 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t29
+  %t31 = load i32, ptr %t30
+  %t32 = icmp ne i32 %t31, 0
+  br i1 %t32, label %body_10, label %end_10
+body_10:
+  %t33 = load i64, ptr @ptr
+  %t34 = add i64 %t33, 2
+  store i64 %t34, ptr @ptr
+  %t35 = load i64, ptr @ptr
+  %t36 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t35
+  %t37 = load i32, ptr %t36
+  %t38 = sub i32 %t37, 1
+  store i32 %t38, ptr %t36
+  %t39 = load i64, ptr @ptr
+  %t40 = sub i64 %t39, 2
+  store i64 %t40, ptr @ptr
+  br label %loop_14
+loop_14:
+  %t41 = load i64, ptr @ptr
+  %t42 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t41
+  %t43 = load i32, ptr %t42
+  %t44 = icmp ne i32 %t43, 0
+  br i1 %t44, label %body_14, label %end_14
+body_14:
+  %t45 = load i64, ptr @ptr
+  %t46 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t45
+  %t47 = load i32, ptr %t46
+  %t48 = sub i32 %t47, 1
+  store i32 %t48, ptr %t46
+  br label %loop_14
+end_14:
+  br label %loop_10
+end_10:
+  %t49 = load i64, ptr @ptr
+  %t50 = add i64 %t49, 3
+  store i64 %t50, ptr @ptr
+  %t51 = load i64, ptr @ptr
+  %t52 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t51
+  %t53 = load i32, ptr %t52
+  %t54 = add i32 %t53, 8
+  store i32 %t54, ptr %t52
+  br label %loop_20
+loop_20:
+  %t55 = load i64, ptr @ptr
+  %t56 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t55
+  %t57 = load i32, ptr %t56
+  %t58 = icmp ne i32 %t57, 0
+  br i1 %t58, label %body_20, label %end_20
+body_20:
+  %t59 = load i64, ptr @ptr
+  %t60 = sub i64 %t59, 2
+  store i64 %t60, ptr @ptr
+  %t61 = load i64, ptr @ptr
+  %t62 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t61
+  %t63 = load i32, ptr %t62
+  %t64 = add i32 %t63, 10
+  store i32 %t64, ptr %t62
+  %t65 = load i64, ptr @ptr
+  %t66 = add i64 %t65, 2
+  store i64 %t66, ptr @ptr
+  %t67 = load i64, ptr @ptr
+  %t68 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t67
+  %t69 = load i32, ptr %t68
+  %t70 = sub i32 %t69, 1
+  store i32 %t70, ptr %t68
+  br label %loop_20
+end_20:
+  %t71 = load i64, ptr @ptr
+  %t72 = sub i64 %t71, 2
+  store i64 %t72, ptr @ptr
+  %t73 = load i64, ptr @ptr
+  %t74 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t73
+  %t75 = load i32, ptr %t74
+  %t76 = sub i32 %t75, 2
+  store i32 %t76, ptr %t74
+  %t77 = load i64, ptr @ptr
+  %t78 = add i64 %t77, 1
+  store i64 %t78, ptr @ptr
+  br label %loop_29
+loop_29:
+  %t79 = load i64, ptr @ptr
+  %t80 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t79
+  %t81 = load i32, ptr %t80
+  %t82 = icmp ne i32 %t81, 0
+  br i1 %t82, label %body_29, label %end_29
+body_29:
+  %t83 = load i64, ptr @ptr
+  %t84 = sub i64 %t83, 1
+  store i64 %t84, ptr @ptr
+  %t85 = load i64, ptr @ptr
+  %t86 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t85
+  %t87 = load i32, ptr %t86
+  %t88 = add i32 %t87, 11
+  store i32 %t88, ptr %t86
+  %t89 = load i64, ptr @ptr
+  %t90 = add i64 %t89, 1
+  store i64 %t90, ptr @ptr
+  %t91 = load i64, ptr @ptr
+  %t92 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t91
+  %t93 = load i32, ptr %t92
+  %t94 = sub i32 %t93, 1
+  store i32 %t94, ptr %t92
+  br label %loop_29
+end_29:
+  %t95 = load i64, ptr @ptr
+  %t96 = sub i64 %t95, 1
+  store i64 %t96, ptr @ptr
+  %t97 = load i64, ptr @ptr
+  %t98 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t97
+  %t99 = load i32, ptr %t98
+  %t100 = call i32 @putchar(i32 %t99)
+  br label %loop_37
+loop_37:
+  %t101 = load i64, ptr @ptr
+  %t102 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t101
+  %t103 = load i32, ptr %t102
+  %t104 = icmp ne i32 %t103, 0
+  br i1 %t104, label %body_37, label %end_37
+body_37:
+  %t105 = load i64, ptr @ptr
+  %t106 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t105
+  %t107 = load i32, ptr %t106
+  %t108 = sub i32 %t107, 1
+  store i32 %t108, ptr %t106
+  br label %loop_37
+end_37:
+  %t109 = load i64, ptr @ptr
+  %t110 = sub i64 %t109, 2
+  store i64 %t110, ptr @ptr
+  %t111 = load i64, ptr @ptr
+  %t112 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t111
+  %t113 = load i32, ptr %t112
+  %t114 = add i32 %t113, 16
+  store i32 %t114, ptr %t112
+  br label %loop_42
+loop_42:
+  %t115 = load i64, ptr @ptr
+  %t116 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t115
+  %t117 = load i32, ptr %t116
+  %t118 = icmp ne i32 %t117, 0
+  br i1 %t118, label %body_42, label %end_42
+body_42:
+  %t119 = load i64, ptr @ptr
+  %t120 = add i64 %t119, 1
+  store i64 %t120, ptr @ptr
+  %t121 = load i64, ptr @ptr
+  %t122 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t121
+  %t123 = load i32, ptr %t122
+  %t124 = add i32 %t123, 16
+  store i32 %t124, ptr %t122
+  br label %loop_45
+loop_45:
+  %t125 = load i64, ptr @ptr
+  %t126 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t125
+  %t127 = load i32, ptr %t126
+  %t128 = icmp ne i32 %t127, 0
+  br i1 %t128, label %body_45, label %end_45
+body_45:
+  %t129 = load i64, ptr @ptr
+  %t130 = add i64 %t129, 2
+  store i64 %t130, ptr @ptr
+  %t131 = load i64, ptr @ptr
+  %t132 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t131
+  %t133 = load i32, ptr %t132
+  %t134 = add i32 %t133, 16
+  store i32 %t134, ptr %t132
+  br label %loop_48
+loop_48:
+  %t135 = load i64, ptr @ptr
+  %t136 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t135
+  %t137 = load i32, ptr %t136
+  %t138 = icmp ne i32 %t137, 0
+  br i1 %t138, label %body_48, label %end_48
+body_48:
+  %t139 = load i64, ptr @ptr
+  %t140 = sub i64 %t139, 1
+  store i64 %t140, ptr @ptr
+  %t141 = load i64, ptr @ptr
+  %t142 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t141
+  %t143 = load i32, ptr %t142
+  %t144 = add i32 %t143, 16
+  store i32 %t144, ptr %t142
+  %t145 = load i64, ptr @ptr
+  %t146 = add i64 %t145, 1
+  store i64 %t146, ptr @ptr
+  %t147 = load i64, ptr @ptr
+  %t148 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t147
+  %t149 = load i32, ptr %t148
+  %t150 = sub i32 %t149, 1
+  store i32 %t150, ptr %t148
+  br label %loop_48
+end_48:
+  %t151 = load i64, ptr @ptr
+  %t152 = sub i64 %t151, 2
+  store i64 %t152, ptr @ptr
+  %t153 = load i64, ptr @ptr
+  %t154 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t153
+  %t155 = load i32, ptr %t154
+  %t156 = sub i32 %t155, 1
+  store i32 %t156, ptr %t154
+  br label %loop_45
+end_45:
+  %t157 = load i64, ptr @ptr
+  %t158 = sub i64 %t157, 1
+  store i64 %t158, ptr @ptr
+  %t159 = load i64, ptr @ptr
+  %t160 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t159
+  %t161 = load i32, ptr %t160
+  %t162 = sub i32 %t161, 1
+  store i32 %t162, ptr %t160
+  br label %loop_42
+end_42:
+  %t163 = load i64, ptr @ptr
+  %t164 = add i64 %t163, 4
+  store i64 %t164, ptr @ptr
+  %t165 = load i64, ptr @ptr
+  %t166 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t165
+  %t167 = load i32, ptr %t166
+  %t168 = add i32 %t167, 1
+  store i32 %t168, ptr %t166
+  %t169 = load i64, ptr @ptr
+  %t170 = sub i64 %t169, 2
+  store i64 %t170, ptr @ptr
+  br label %loop_63
+loop_63:
+  %t171 = load i64, ptr @ptr
+  %t172 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t171
+  %t173 = load i32, ptr %t172
+  %t174 = icmp ne i32 %t173, 0
+  br i1 %t174, label %body_63, label %end_63
+body_63:
+  %t175 = load i64, ptr @ptr
+  %t176 = add i64 %t175, 2
+  store i64 %t176, ptr @ptr
+  %t177 = load i64, ptr @ptr
+  %t178 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t177
+  %t179 = load i32, ptr %t178
+  %t180 = sub i32 %t179, 1
+  store i32 %t180, ptr %t178
+  %t181 = load i64, ptr @ptr
+  %t182 = sub i64 %t181, 2
+  store i64 %t182, ptr @ptr
+  br label %loop_67
+loop_67:
+  %t183 = load i64, ptr @ptr
+  %t184 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t183
+  %t185 = load i32, ptr %t184
+  %t186 = icmp ne i32 %t185, 0
+  br i1 %t186, label %body_67, label %end_67
+body_67:
+  %t187 = load i64, ptr @ptr
+  %t188 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t187
+  %t189 = load i32, ptr %t188
+  %t190 = sub i32 %t189, 1
+  store i32 %t190, ptr %t188
+  br label %loop_67
+end_67:
+  br label %loop_63
+end_63:
+  %t191 = load i64, ptr @ptr
+  %t192 = add i64 %t191, 3
+  store i64 %t192, ptr @ptr
+  %t193 = load i64, ptr @ptr
+  %t194 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t193
+  %t195 = load i32, ptr %t194
+  %t196 = add i32 %t195, 8
+  store i32 %t196, ptr %t194
+  br label %loop_73
+loop_73:
+  %t197 = load i64, ptr @ptr
+  %t198 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t197
+  %t199 = load i32, ptr %t198
+  %t200 = icmp ne i32 %t199, 0
+  br i1 %t200, label %body_73, label %end_73
+body_73:
+  %t201 = load i64, ptr @ptr
+  %t202 = sub i64 %t201, 2
+  store i64 %t202, ptr @ptr
+  %t203 = load i64, ptr @ptr
+  %t204 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t203
+  %t205 = load i32, ptr %t204
+  %t206 = add i32 %t205, 10
+  store i32 %t206, ptr %t204
+  %t207 = load i64, ptr @ptr
+  %t208 = add i64 %t207, 2
+  store i64 %t208, ptr @ptr
+  %t209 = load i64, ptr @ptr
+  %t210 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t209
+  %t211 = load i32, ptr %t210
+  %t212 = sub i32 %t211, 1
+  store i32 %t212, ptr %t210
+  br label %loop_73
+end_73:
+  %t213 = load i64, ptr @ptr
+  %t214 = sub i64 %t213, 2
+  store i64 %t214, ptr @ptr
+  %t215 = load i64, ptr @ptr
+  %t216 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t215
+  %t217 = load i32, ptr %t216
+  %t218 = sub i32 %t217, 2
+  store i32 %t218, ptr %t216
+  %t219 = load i64, ptr @ptr
+  %t220 = add i64 %t219, 1
+  store i64 %t220, ptr @ptr
+  br label %loop_82
+loop_82:
+  %t221 = load i64, ptr @ptr
+  %t222 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t221
+  %t223 = load i32, ptr %t222
+  %t224 = icmp ne i32 %t223, 0
+  br i1 %t224, label %body_82, label %end_82
+body_82:
+  %t225 = load i64, ptr @ptr
+  %t226 = sub i64 %t225, 1
+  store i64 %t226, ptr @ptr
+  %t227 = load i64, ptr @ptr
+  %t228 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t227
+  %t229 = load i32, ptr %t228
+  %t230 = add i32 %t229, 11
+  store i32 %t230, ptr %t228
+  %t231 = load i64, ptr @ptr
+  %t232 = add i64 %t231, 1
+  store i64 %t232, ptr @ptr
+  %t233 = load i64, ptr @ptr
+  %t234 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t233
+  %t235 = load i32, ptr %t234
+  %t236 = sub i32 %t235, 1
+  store i32 %t236, ptr %t234
+  br label %loop_82
+end_82:
+  %t237 = load i64, ptr @ptr
+  %t238 = sub i64 %t237, 1
+  store i64 %t238, ptr @ptr
+  %t239 = load i64, ptr @ptr
+  %t240 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t239
+  %t241 = load i32, ptr %t240
+  %t242 = call i32 @putchar(i32 %t241)
+  br label %loop_90
+loop_90:
+  %t243 = load i64, ptr @ptr
+  %t244 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t243
+  %t245 = load i32, ptr %t244
+  %t246 = icmp ne i32 %t245, 0
+  br i1 %t246, label %body_90, label %end_90
+body_90:
+  %t247 = load i64, ptr @ptr
+  %t248 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t247
+  %t249 = load i32, ptr %t248
+  %t250 = sub i32 %t249, 1
+  store i32 %t250, ptr %t248
+  br label %loop_90
+end_90:
+  %t251 = load i64, ptr @ptr
+  %t252 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t251
+  %t253 = load i32, ptr %t252
+  %t254 = add i32 %t253, 10
+  store i32 %t254, ptr %t252
+  %t255 = load i64, ptr @ptr
+  %t256 = getelementptr inbounds [640000 x i32], ptr @tape, i64 0, i64 %t255
+  %t257 = load i32, ptr %t256
+  %t258 = call i32 @putchar(i32 %t257)
+  ret i32 0
+}
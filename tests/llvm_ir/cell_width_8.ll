@@ -0,0 +1,421 @@
+; Generated by rust_brain from brainfuck file conformance/cell_width.b, do not edit.
+
+@tape = internal global [640000 x i8] zeroinitializer
+@ptr = internal global i64 0
+
+declare i32 @getchar()
+declare i32 @putchar(i32)
+
+define i32 @main() {
+entry:
+  %t1 = load i64, ptr @ptr
+  %t2 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t1
+  %t3 = load i8, ptr %t2
+  %t4 = add i8 %t3, 16
+  store i8 %t4, ptr %t2
+  br label %loop_1
+loop_1:
+  %t5 = load i64, ptr @ptr
+  %t6 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t5
+  %t7 = load i8, ptr %t6
+  %t8 = icmp ne i8 %t7, 0
+  br i1 %t8, label %body_1, label %end_1
+body_1:
+  %t9 = load i64, ptr @ptr
+  %t10 = add i64 %t9, 1
+  store i64 %t10, ptr @ptr
+  %t11 = load i64, ptr @ptr
+  %t12 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t11
+  %t13 = load i8, ptr %t12
+  %t14 = add i8 %t13, 16
+  store i8 %t14, ptr %t12
+  %t15 = load i64, ptr @ptr
+  %t16 = sub i64 %t15, 1
+  store i64 %t16, ptr @ptr
+  %t17 = load i64, ptr @ptr
+  %t18 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t17
+  %t19 = load i8, ptr %t18
+  %t20 = sub i8 %t19, 1
+  store i8 %t20, ptr %t18
+  br label %loop_1
+end_1:
+  %t21 = load i64, ptr @ptr
+  %t22 = add i64 %t21, 3
+  store i64 %t22, ptr @ptr
+  %t23 = load i64, ptr @ptr
+  %t24 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t23
+  %t25 = load i8, ptr %t24
+  %t26 = add i8 %t25, 1
+  store i8 %t26, ptr %t24
+  %t27 = load i64, ptr @ptr
+  %t28 = sub i64 %t27, 2
+  store i64 %t28, ptr @ptr
+  br label %loop_10
+loop_10:
+  %t29 = load i64, ptr @ptr
+  %t30 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t29
+  %t31 = load i8, ptr %t30
+  %t32 = icmp ne i8 %t31, 0
+  br i1 %t32, label %body_10, label %end_10
+body_10:
+  %t33 = load i64, ptr @ptr
+  %t34 = add i64 %t33, 2
+  store i64 %t34, ptr @ptr
+  %t35 = load i64, ptr @ptr
+  %t36 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t35
+  %t37 = load i8, ptr %t36
+  %t38 = sub i8 %t37, 1
+  store i8 %t38, ptr %t36
+  %t39 = load i64, ptr @ptr
+  %t40 = sub i64 %t39, 2
+  store i64 %t40, ptr @ptr
+  br label %loop_14
+loop_14:
+  %t41 = load i64, ptr @ptr
+  %t42 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t41
+  %t43 = load i8, ptr %t42
+  %t44 = icmp ne i8 %t43, 0
+  br i1 %t44, label %body_14, label %end_14
+body_14:
+  %t45 = load i64, ptr @ptr
+  %t46 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t45
+  %t47 = load i8, ptr %t46
+  %t48 = sub i8 %t47, 1
+  store i8 %t48, ptr %t46
+  br label %loop_14
+end_14:
+  br label %loop_10
+end_10:
+  %t49 = load i64, ptr @ptr
+  %t50 = add i64 %t49, 3
+  store i64 %t50, ptr @ptr
+  %t51 = load i64, ptr @ptr
+  %t52 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t51
+  %t53 = load i8, ptr %t52
+  %t54 = add i8 %t53, 8
+  store i8 %t54, ptr %t52
+  br label %loop_20
+loop_20:
+  %t55 = load i64, ptr @ptr
+  %t56 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t55
+  %t57 = load i8, ptr %t56
+  %t58 = icmp ne i8 %t57, 0
+  br i1 %t58, label %body_20, label %end_20
+body_20:
+  %t59 = load i64, ptr @ptr
+  %t60 = sub i64 %t59, 2
+  store i64 %t60, ptr @ptr
+  %t61 = load i64, ptr @ptr
+  %t62 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t61
+  %t63 = load i8, ptr %t62
+  %t64 = add i8 %t63, 10
+  store i8 %t64, ptr %t62
+  %t65 = load i64, ptr @ptr
+  %t66 = add i64 %t65, 2
+  store i64 %t66, ptr @ptr
+  %t67 = load i64, ptr @ptr
+  %t68 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t67
+  %t69 = load i8, ptr %t68
+  %t70 = sub i8 %t69, 1
+  store i8 %t70, ptr %t68
+  br label %loop_20
+end_20:
+  %t71 = load i64, ptr @ptr
+  %t72 = sub i64 %t71, 2
+  store i64 %t72, ptr @ptr
+  %t73 = load i64, ptr @ptr
+  %t74 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t73
+  %t75 = load i8, ptr %t74
+  %t76 = sub i8 %t75, 2
+  store i8 %t76, ptr %t74
+  %t77 = load i64, ptr @ptr
+  %t78 = add i64 %t77, 1
+  store i64 %t78, ptr @ptr
+  br label %loop_29
+loop_29:
+  %t79 = load i64, ptr @ptr
+  %t80 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t79
+  %t81 = load i8, ptr %t80
+  %t82 = icmp ne i8 %t81, 0
+  br i1 %t82, label %body_29, label %end_29
+body_29:
+  %t83 = load i64, ptr @ptr
+  %t84 = sub i64 %t83, 1
+  store i64 %t84, ptr @ptr
+  %t85 = load i64, ptr @ptr
+  %t86 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t85
+  %t87 = load i8, ptr %t86
+  %t88 = add i8 %t87, 11
+  store i8 %t88, ptr %t86
+  %t89 = load i64, ptr @ptr
+  %t90 = add i64 %t89, 1
+  store i64 %t90, ptr @ptr
+  %t91 = load i64, ptr @ptr
+  %t92 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t91
+  %t93 = load i8, ptr %t92
+  %t94 = sub i8 %t93, 1
+  store i8 %t94, ptr %t92
+  br label %loop_29
+end_29:
+  %t95 = load i64, ptr @ptr
+  %t96 = sub i64 %t95, 1
+  store i64 %t96, ptr @ptr
+  %t97 = load i64, ptr @ptr
+  %t98 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t97
+  %t99 = load i8, ptr %t98
+  %t100 = zext i8 %t99 to i32
+  %t101 = call i32 @putchar(i32 %t100)
+  br label %loop_37
+loop_37:
+  %t102 = load i64, ptr @ptr
+  %t103 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t102
+  %t104 = load i8, ptr %t103
+  %t105 = icmp ne i8 %t104, 0
+  br i1 %t105, label %body_37, label %end_37
+body_37:
+  %t106 = load i64, ptr @ptr
+  %t107 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t106
+  %t108 = load i8, ptr %t107
+  %t109 = sub i8 %t108, 1
+  store i8 %t109, ptr %t107
+  br label %loop_37
+end_37:
+  %t110 = load i64, ptr @ptr
+  %t111 = sub i64 %t110, 2
+  store i64 %t111, ptr @ptr
+  %t112 = load i64, ptr @ptr
+  %t113 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t112
+  %t114 = load i8, ptr %t113
+  %t115 = add i8 %t114, 16
+  store i8 %t115, ptr %t113
+  br label %loop_42
+loop_42:
+  %t116 = load i64, ptr @ptr
+  %t117 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t116
+  %t118 = load i8, ptr %t117
+  %t119 = icmp ne i8 %t118, 0
+  br i1 %t119, label %body_42, label %end_42
+body_42:
+  %t120 = load i64, ptr @ptr
+  %t121 = add i64 %t120, 1
+  store i64 %t121, ptr @ptr
+  %t122 = load i64, ptr @ptr
+  %t123 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t122
+  %t124 = load i8, ptr %t123
+  %t125 = add i8 %t124, 16
+  store i8 %t125, ptr %t123
+  br label %loop_45
+loop_45:
+  %t126 = load i64, ptr @ptr
+  %t127 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t126
+  %t128 = load i8, ptr %t127
+  %t129 = icmp ne i8 %t128, 0
+  br i1 %t129, label %body_45, label %end_45
+body_45:
+  %t130 = load i64, ptr @ptr
+  %t131 = add i64 %t130, 2
+  store i64 %t131, ptr @ptr
+  %t132 = load i64, ptr @ptr
+  %t133 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t132
+  %t134 = load i8, ptr %t133
+  %t135 = add i8 %t134, 16
+  store i8 %t135, ptr %t133
+  br label %loop_48
+loop_48:
+  %t136 = load i64, ptr @ptr
+  %t137 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t136
+  %t138 = load i8, ptr %t137
+  %t139 = icmp ne i8 %t138, 0
+  br i1 %t139, label %body_48, label %end_48
+body_48:
+  %t140 = load i64, ptr @ptr
+  %t141 = sub i64 %t140, 1
+  store i64 %t141, ptr @ptr
+  %t142 = load i64, ptr @ptr
+  %t143 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t142
+  %t144 = load i8, ptr %t143
+  %t145 = add i8 %t144, 16
+  store i8 %t145, ptr %t143
+  %t146 = load i64, ptr @ptr
+  %t147 = add i64 %t146, 1
+  store i64 %t147, ptr @ptr
+  %t148 = load i64, ptr @ptr
+  %t149 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t148
+  %t150 = load i8, ptr %t149
+  %t151 = sub i8 %t150, 1
+  store i8 %t151, ptr %t149
+  br label %loop_48
+end_48:
+  %t152 = load i64, ptr @ptr
+  %t153 = sub i64 %t152, 2
+  store i64 %t153, ptr @ptr
+  %t154 = load i64, ptr @ptr
+  %t155 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t154
+  %t156 = load i8, ptr %t155
+  %t157 = sub i8 %t156, 1
+  store i8 %t157, ptr %t155
+  br label %loop_45
+end_45:
+  %t158 = load i64, ptr @ptr
+  %t159 = sub i64 %t158, 1
+  store i64 %t159, ptr @ptr
+  %t160 = load i64, ptr @ptr
+  %t161 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t160
+  %t162 = load i8, ptr %t161
+  %t163 = sub i8 %t162, 1
+  store i8 %t163, ptr %t161
+  br label %loop_42
+end_42:
+  %t164 = load i64, ptr @ptr
+  %t165 = add i64 %t164, 4
+  store i64 %t165, ptr @ptr
+  %t166 = load i64, ptr @ptr
+  %t167 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t166
+  %t168 = load i8, ptr %t167
+  %t169 = add i8 %t168, 1
+  store i8 %t169, ptr %t167
+  %t170 = load i64, ptr @ptr
+  %t171 = sub i64 %t170, 2
+  store i64 %t171, ptr @ptr
+  br label %loop_63
+loop_63:
+  %t172 = load i64, ptr @ptr
+  %t173 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t172
+  %t174 = load i8, ptr %t173
+  %t175 = icmp ne i8 %t174, 0
+  br i1 %t175, label %body_63, label %end_63
+body_63:
+  %t176 = load i64, ptr @ptr
+  %t177 = add i64 %t176, 2
+  store i64 %t177, ptr @ptr
+  %t178 = load i64, ptr @ptr
+  %t179 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t178
+  %t180 = load i8, ptr %t179
+  %t181 = sub i8 %t180, 1
+  store i8 %t181, ptr %t179
+  %t182 = load i64, ptr @ptr
+  %t183 = sub i64 %t182, 2
+  store i64 %t183, ptr @ptr
+  br label %loop_67
+loop_67:
+  %t184 = load i64, ptr @ptr
+  %t185 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t184
+  %t186 = load i8, ptr %t185
+  %t187 = icmp ne i8 %t186, 0
+  br i1 %t187, label %body_67, label %end_67
+body_67:
+  %t188 = load i64, ptr @ptr
+  %t189 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t188
+  %t190 = load i8, ptr %t189
+  %t191 = sub i8 %t190, 1
+  store i8 %t191, ptr %t189
+  br label %loop_67
+end_67:
+  br label %loop_63
+end_63:
+  %t192 = load i64, ptr @ptr
+  %t193 = add i64 %t192, 3
+  store i64 %t193, ptr @ptr
+  %t194 = load i64, ptr @ptr
+  %t195 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t194
+  %t196 = load i8, ptr %t195
+  %t197 = add i8 %t196, 8
+  store i8 %t197, ptr %t195
+  br label %loop_73
+loop_73:
+  %t198 = load i64, ptr @ptr
+  %t199 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t198
+  %t200 = load i8, ptr %t199
+  %t201 = icmp ne i8 %t200, 0
+  br i1 %t201, label %body_73, label %end_73
+body_73:
+  %t202 = load i64, ptr @ptr
+  %t203 = sub i64 %t202, 2
+  store i64 %t203, ptr @ptr
+  %t204 = load i64, ptr @ptr
+  %t205 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t204
+  %t206 = load i8, ptr %t205
+  %t207 = add i8 %t206, 10
+  store i8 %t207, ptr %t205
+  %t208 = load i64, ptr @ptr
+  %t209 = add i64 %t208, 2
+  store i64 %t209, ptr @ptr
+  %t210 = load i64, ptr @ptr
+  %t211 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t210
+  %t212 = load i8, ptr %t211
+  %t213 = sub i8 %t212, 1
+  store i8 %t213, ptr %t211
+  br label %loop_73
+end_73:
+  %t214 = load i64, ptr @ptr
+  %t215 = sub i64 %t214, 2
+  store i64 %t215, ptr @ptr
+  %t216 = load i64, ptr @ptr
+  %t217 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t216
+  %t218 = load i8, ptr %t217
+  %t219 = sub i8 %t218, 2
+  store i8 %t219, ptr %t217
+  %t220 = load i64, ptr @ptr
+  %t221 = add i64 %t220, 1
+  store i64 %t221, ptr @ptr
+  br label %loop_82
+loop_82:
+  %t222 = load i64, ptr @ptr
+  %t223 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t222
+  %t224 = load i8, ptr %t223
+  %t225 = icmp ne i8 %t224, 0
+  br i1 %t225, label %body_82, label %end_82
+body_82:
+  %t226 = load i64, ptr @ptr
+  %t227 = sub i64 %t226, 1
+  store i64 %t227, ptr @ptr
+  %t228 = load i64, ptr @ptr
+  %t229 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t228
+  %t230 = load i8, ptr %t229
+  %t231 = add i8 %t230, 11
+  store i8 %t231, ptr %t229
+  %t232 = load i64, ptr @ptr
+  %t233 = add i64 %t232, 1
+  store i64 %t233, ptr @ptr
+  %t234 = load i64, ptr @ptr
+  %t235 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t234
+  %t236 = load i8, ptr %t235
+  %t237 = sub i8 %t236, 1
+  store i8 %t237, ptr %t235
+  br label %loop_82
+end_82:
+  %t238 = load i64, ptr @ptr
+  %t239 = sub i64 %t238, 1
+  store i64 %t239, ptr @ptr
+  %t240 = load i64, ptr @ptr
+  %t241 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t240
+  %t242 = load i8, ptr %t241
+  %t243 = zext i8 %t242 to i32
+  %t244 = call i32 @putchar(i32 %t243)
+  br label %loop_90
+loop_90:
+  %t245 = load i64, ptr @ptr
+  %t246 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t245
+  %t247 = load i8, ptr %t246
+  %t248 = icmp ne i8 %t247, 0
+  br i1 %t248, label %body_90, label %end_90
+body_90:
+  %t249 = load i64, ptr @ptr
+  %t250 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t249
+  %t251 = load i8, ptr %t250
+  %t252 = sub i8 %t251, 1
+  store i8 %t252, ptr %t250
+  br label %loop_90
+end_90:
+  %t253 = load i64, ptr @ptr
+  %t254 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t253
+  %t255 = load i8, ptr %t254
+  %t256 = add i8 %t255, 10
+  store i8 %t256, ptr %t254
+  %t257 = load i64, ptr @ptr
+  %t258 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t257
+  %t259 = load i8, ptr %t258
+  %t260 = zext i8 %t259 to i32
+  %t261 = call i32 @putchar(i32 %t260)
+  ret i32 0
+}
@@ -0,0 +1,265 @@
+; Generated by rust_brain from brainfuck file examples/hello_world.brainfuck, do not edit.
+
+@tape = internal global [640000 x i8] zeroinitializer
+@ptr = internal global i64 0
+
+declare i32 @getchar()
+declare i32 @putchar(i32)
+
+define i32 @main() {
+entry:
+  %t1 = load i64, ptr @ptr
+  %t2 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t1
+  %t3 = load i8, ptr %t2
+  %t4 = add i8 %t3, 8
+  store i8 %t4, ptr %t2
+  br label %loop_1
+loop_1:
+  %t5 = load i64, ptr @ptr
+  %t6 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t5
+  %t7 = load i8, ptr %t6
+  %t8 = icmp ne i8 %t7, 0
+  br i1 %t8, label %body_1, label %end_1
+body_1:
+  %t9 = load i64, ptr @ptr
+  %t10 = add i64 %t9, 1
+  store i64 %t10, ptr @ptr
+  %t11 = load i64, ptr @ptr
+  %t12 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t11
+  %t13 = load i8, ptr %t12
+  %t14 = add i8 %t13, 4
+  store i8 %t14, ptr %t12
+  br label %loop_4
+loop_4:
+  %t15 = load i64, ptr @ptr
+  %t16 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t15
+  %t17 = load i8, ptr %t16
+  %t18 = icmp ne i8 %t17, 0
+  br i1 %t18, label %body_4, label %end_4
+body_4:
+  %t19 = load i64, ptr @ptr
+  %t20 = add i64 %t19, 1
+  store i64 %t20, ptr @ptr
+  %t21 = load i64, ptr @ptr
+  %t22 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t21
+  %t23 = load i8, ptr %t22
+  %t24 = add i8 %t23, 2
+  store i8 %t24, ptr %t22
+  %t25 = load i64, ptr @ptr
+  %t26 = add i64 %t25, 1
+  store i64 %t26, ptr @ptr
+  %t27 = load i64, ptr @ptr
+  %t28 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t27
+  %t29 = load i8, ptr %t28
+  %t30 = add i8 %t29, 3
+  store i8 %t30, ptr %t28
+  %t31 = load i64, ptr @ptr
+  %t32 = add i64 %t31, 1
+  store i64 %t32, ptr @ptr
+  %t33 = load i64, ptr @ptr
+  %t34 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t33
+  %t35 = load i8, ptr %t34
+  %t36 = add i8 %t35, 3
+  store i8 %t36, ptr %t34
+  %t37 = load i64, ptr @ptr
+  %t38 = add i64 %t37, 1
+  store i64 %t38, ptr @ptr
+  %t39 = load i64, ptr @ptr
+  %t40 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t39
+  %t41 = load i8, ptr %t40
+  %t42 = add i8 %t41, 1
+  store i8 %t42, ptr %t40
+  %t43 = load i64, ptr @ptr
+  %t44 = sub i64 %t43, 4
+  store i64 %t44, ptr @ptr
+  %t45 = load i64, ptr @ptr
+  %t46 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t45
+  %t47 = load i8, ptr %t46
+  %t48 = sub i8 %t47, 1
+  store i8 %t48, ptr %t46
+  br label %loop_4
+end_4:
+  %t49 = load i64, ptr @ptr
+  %t50 = add i64 %t49, 1
+  store i64 %t50, ptr @ptr
+  %t51 = load i64, ptr @ptr
+  %t52 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t51
+  %t53 = load i8, ptr %t52
+  %t54 = add i8 %t53, 1
+  store i8 %t54, ptr %t52
+  %t55 = load i64, ptr @ptr
+  %t56 = add i64 %t55, 1
+  store i64 %t56, ptr @ptr
+  %t57 = load i64, ptr @ptr
+  %t58 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t57
+  %t59 = load i8, ptr %t58
+  %t60 = add i8 %t59, 1
+  store i8 %t60, ptr %t58
+  %t61 = load i64, ptr @ptr
+  %t62 = add i64 %t61, 1
+  store i64 %t62, ptr @ptr
+  %t63 = load i64, ptr @ptr
+  %t64 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t63
+  %t65 = load i8, ptr %t64
+  %t66 = sub i8 %t65, 1
+  store i8 %t66, ptr %t64
+  %t67 = load i64, ptr @ptr
+  %t68 = add i64 %t67, 2
+  store i64 %t68, ptr @ptr
+  %t69 = load i64, ptr @ptr
+  %t70 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t69
+  %t71 = load i8, ptr %t70
+  %t72 = add i8 %t71, 1
+  store i8 %t72, ptr %t70
+  br label %loop_24
+loop_24:
+  %t73 = load i64, ptr @ptr
+  %t74 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t73
+  %t75 = load i8, ptr %t74
+  %t76 = icmp ne i8 %t75, 0
+  br i1 %t76, label %body_24, label %end_24
+body_24:
+  %t77 = load i64, ptr @ptr
+  %t78 = sub i64 %t77, 1
+  store i64 %t78, ptr @ptr
+  br label %loop_24
+end_24:
+  %t79 = load i64, ptr @ptr
+  %t80 = sub i64 %t79, 1
+  store i64 %t80, ptr @ptr
+  %t81 = load i64, ptr @ptr
+  %t82 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t81
+  %t83 = load i8, ptr %t82
+  %t84 = sub i8 %t83, 1
+  store i8 %t84, ptr %t82
+  br label %loop_1
+end_1:
+  %t85 = load i64, ptr @ptr
+  %t86 = add i64 %t85, 2
+  store i64 %t86, ptr @ptr
+  %t87 = load i64, ptr @ptr
+  %t88 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t87
+  %t89 = load i8, ptr %t88
+  %t90 = zext i8 %t89 to i32
+  %t91 = call i32 @putchar(i32 %t90)
+  %t92 = load i64, ptr @ptr
+  %t93 = add i64 %t92, 1
+  store i64 %t93, ptr @ptr
+  %t94 = load i64, ptr @ptr
+  %t95 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t94
+  %t96 = load i8, ptr %t95
+  %t97 = sub i8 %t96, 3
+  store i8 %t97, ptr %t95
+  %t98 = load i64, ptr @ptr
+  %t99 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t98
+  %t100 = load i8, ptr %t99
+  %t101 = zext i8 %t100 to i32
+  %t102 = call i32 @putchar(i32 %t101)
+  %t103 = load i64, ptr @ptr
+  %t104 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t103
+  %t105 = load i8, ptr %t104
+  %t106 = add i8 %t105, 7
+  store i8 %t106, ptr %t104
+  %t107 = load i64, ptr @ptr
+  %t108 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t107
+  %t109 = load i8, ptr %t108
+  %t110 = zext i8 %t109 to i32
+  %t111 = call i32 @putchar(i32 %t110)
+  %t112 = call i32 @putchar(i32 %t110)
+  %t113 = load i64, ptr @ptr
+  %t114 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t113
+  %t115 = load i8, ptr %t114
+  %t116 = add i8 %t115, 3
+  store i8 %t116, ptr %t114
+  %t117 = load i64, ptr @ptr
+  %t118 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t117
+  %t119 = load i8, ptr %t118
+  %t120 = zext i8 %t119 to i32
+  %t121 = call i32 @putchar(i32 %t120)
+  %t122 = load i64, ptr @ptr
+  %t123 = add i64 %t122, 2
+  store i64 %t123, ptr @ptr
+  %t124 = load i64, ptr @ptr
+  %t125 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t124
+  %t126 = load i8, ptr %t125
+  %t127 = zext i8 %t126 to i32
+  %t128 = call i32 @putchar(i32 %t127)
+  %t129 = load i64, ptr @ptr
+  %t130 = sub i64 %t129, 1
+  store i64 %t130, ptr @ptr
+  %t131 = load i64, ptr @ptr
+  %t132 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t131
+  %t133 = load i8, ptr %t132
+  %t134 = sub i8 %t133, 1
+  store i8 %t134, ptr %t132
+  %t135 = load i64, ptr @ptr
+  %t136 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t135
+  %t137 = load i8, ptr %t136
+  %t138 = zext i8 %t137 to i32
+  %t139 = call i32 @putchar(i32 %t138)
+  %t140 = load i64, ptr @ptr
+  %t141 = sub i64 %t140, 1
+  store i64 %t141, ptr @ptr
+  %t142 = load i64, ptr @ptr
+  %t143 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t142
+  %t144 = load i8, ptr %t143
+  %t145 = zext i8 %t144 to i32
+  %t146 = call i32 @putchar(i32 %t145)
+  %t147 = load i64, ptr @ptr
+  %t148 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t147
+  %t149 = load i8, ptr %t148
+  %t150 = add i8 %t149, 3
+  store i8 %t150, ptr %t148
+  %t151 = load i64, ptr @ptr
+  %t152 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t151
+  %t153 = load i8, ptr %t152
+  %t154 = zext i8 %t153 to i32
+  %t155 = call i32 @putchar(i32 %t154)
+  %t156 = load i64, ptr @ptr
+  %t157 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t156
+  %t158 = load i8, ptr %t157
+  %t159 = sub i8 %t158, 6
+  store i8 %t159, ptr %t157
+  %t160 = load i64, ptr @ptr
+  %t161 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t160
+  %t162 = load i8, ptr %t161
+  %t163 = zext i8 %t162 to i32
+  %t164 = call i32 @putchar(i32 %t163)
+  %t165 = load i64, ptr @ptr
+  %t166 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t165
+  %t167 = load i8, ptr %t166
+  %t168 = sub i8 %t167, 8
+  store i8 %t168, ptr %t166
+  %t169 = load i64, ptr @ptr
+  %t170 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t169
+  %t171 = load i8, ptr %t170
+  %t172 = zext i8 %t171 to i32
+  %t173 = call i32 @putchar(i32 %t172)
+  %t174 = load i64, ptr @ptr
+  %t175 = add i64 %t174, 2
+  store i64 %t175, ptr @ptr
+  %t176 = load i64, ptr @ptr
+  %t177 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t176
+  %t178 = load i8, ptr %t177
+  %t179 = add i8 %t178, 1
+  store i8 %t179, ptr %t177
+  %t180 = load i64, ptr @ptr
+  %t181 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t180
+  %t182 = load i8, ptr %t181
+  %t183 = zext i8 %t182 to i32
+  %t184 = call i32 @putchar(i32 %t183)
+  %t185 = load i64, ptr @ptr
+  %t186 = add i64 %t185, 1
+  store i64 %t186, ptr @ptr
+  %t187 = load i64, ptr @ptr
+  %t188 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t187
+  %t189 = load i8, ptr %t188
+  %t190 = add i8 %t189, 2
+  store i8 %t190, ptr %t188
+  %t191 = load i64, ptr @ptr
+  %t192 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t191
+  %t193 = load i8, ptr %t192
+  %t194 = zext i8 %t193 to i32
+  %t195 = call i32 @putchar(i32 %t194)
+  ret i32 0
+}
@@ -0,0 +1,135 @@
+; Generated by rust_brain from brainfuck file tests/llvm_ir/procedures.pbrain, do not edit.
+
+@tape = internal global [640000 x i8] zeroinitializer
+@ptr = internal global i64 0
+@procedures = internal global [256 x ptr] zeroinitializer
+@undefined_procedure = private unnamed_addr constant [39 x i8] c"Error: Call of undefined procedure %u\0A\00"
+
+declare i32 @getchar()
+declare i32 @putchar(i32)
+declare i32 @dprintf(i32, ptr, ...)
+declare void @exit(i32) noreturn
+
+define i32 @main() {
+entry:
+  %t42 = load i64, ptr @ptr
+  %t43 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t42
+  %t44 = load i8, ptr %t43
+  %t45 = zext i8 %t44 to i64
+  %t46 = getelementptr inbounds [256 x ptr], ptr @procedures, i64 0, i64 %t45
+  store ptr @procedure_0, ptr %t46
+  %t47 = load i64, ptr @ptr
+  %t48 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t47
+  %t49 = load i8, ptr %t48
+  %t50 = load i64, ptr @ptr
+  %t51 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t50
+  %t52 = load i8, ptr %t51
+  %t53 = zext i8 %t52 to i64
+  %t54 = getelementptr inbounds [256 x ptr], ptr @procedures, i64 0, i64 %t53
+  %t55 = load ptr, ptr %t54
+  %t56 = icmp eq ptr %t55, null
+  br i1 %t56, label %undefined_16, label %call_16
+undefined_16:
+  %t57 = zext i8 %t49 to i32
+  %t58 = call i32 (i32, ptr, ...) @dprintf(i32 2, ptr @undefined_procedure, i32 %t57)
+  call void @exit(i32 1)
+  unreachable
+call_16:
+  call void %t55()
+  %t59 = load i64, ptr @ptr
+  %t60 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t59
+  %t61 = load i8, ptr %t60
+  %t62 = load i64, ptr @ptr
+  %t63 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t62
+  %t64 = load i8, ptr %t63
+  %t65 = zext i8 %t64 to i64
+  %t66 = getelementptr inbounds [256 x ptr], ptr @procedures, i64 0, i64 %t65
+  %t67 = load ptr, ptr %t66
+  %t68 = icmp eq ptr %t67, null
+  br i1 %t68, label %undefined_17, label %call_17
+undefined_17:
+  %t69 = zext i8 %t61 to i32
+  %t70 = call i32 (i32, ptr, ...) @dprintf(i32 2, ptr @undefined_procedure, i32 %t69)
+  call void @exit(i32 1)
+  unreachable
+call_17:
+  call void %t67()
+  %t71 = load i64, ptr @ptr
+  %t72 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t71
+  %t73 = load i8, ptr %t72
+  %t74 = add i8 %t73, 10
+  store i8 %t74, ptr %t72
+  %t75 = load i64, ptr @ptr
+  %t76 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t75
+  %t77 = load i8, ptr %t76
+  %t78 = zext i8 %t77 to i32
+  %t79 = call i32 @putchar(i32 %t78)
+  ret i32 0
+}
+
+define internal void @procedure_0() {
+entry:
+  %t1 = load i64, ptr @ptr
+  %t2 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t1
+  %t3 = load i8, ptr %t2
+  %t4 = add i8 %t3, 8
+  store i8 %t4, ptr %t2
+  br label %loop_2
+loop_2:
+  %t5 = load i64, ptr @ptr
+  %t6 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t5
+  %t7 = load i8, ptr %t6
+  %t8 = icmp ne i8 %t7, 0
+  br i1 %t8, label %body_2, label %end_2
+body_2:
+  %t9 = load i64, ptr @ptr
+  %t10 = add i64 %t9, 1
+  store i64 %t10, ptr @ptr
+  %t11 = load i64, ptr @ptr
+  %t12 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t11
+  %t13 = load i8, ptr %t12
+  %t14 = add i8 %t13, 8
+  store i8 %t14, ptr %t12
+  %t15 = load i64, ptr @ptr
+  %t16 = sub i64 %t15, 1
+  store i64 %t16, ptr @ptr
+  %t17 = load i64, ptr @ptr
+  %t18 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t17
+  %t19 = load i8, ptr %t18
+  %t20 = sub i8 %t19, 1
+  store i8 %t20, ptr %t18
+  br label %loop_2
+end_2:
+  %t21 = load i64, ptr @ptr
+  %t22 = add i64 %t21, 1
+  store i64 %t22, ptr @ptr
+  %t23 = load i64, ptr @ptr
+  %t24 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t23
+  %t25 = load i8, ptr %t24
+  %t26 = add i8 %t25, 1
+  store i8 %t26, ptr %t24
+  %t27 = load i64, ptr @ptr
+  %t28 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t27
+  %t29 = load i8, ptr %t28
+  %t30 = zext i8 %t29 to i32
+  %t31 = call i32 @putchar(i32 %t30)
+  br label %loop_11
+loop_11:
+  %t32 = load i64, ptr @ptr
+  %t33 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t32
+  %t34 = load i8, ptr %t33
+  %t35 = icmp ne i8 %t34, 0
+  br i1 %t35, label %body_11, label %end_11
+body_11:
+  %t36 = load i64, ptr @ptr
+  %t37 = getelementptr inbounds [640000 x i8], ptr @tape, i64 0, i64 %t36
+  %t38 = load i8, ptr %t37
+  %t39 = sub i8 %t38, 1
+  store i8 %t39, ptr %t37
+  br label %loop_11
+end_11:
+  %t40 = load i64, ptr @ptr
+  %t41 = sub i64 %t40, 1
+  store i64 %t41, ptr @ptr
+  ret void
+}
@@ -0,0 +1,5 @@
+Reads three bytes from empty input into a cell holding one and prints the
+cell plus 65 like the conformance test for EOF
+
++,,,>++++++++[<++++++++>-]<+.
+>++++++++++.
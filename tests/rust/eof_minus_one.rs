@@ -0,0 +1,68 @@
+// Generated by rust_brain from brainfuck file tests/rust/eof.b, do not edit.
+#![allow(unused_variables, dead_code, clippy::all)]
+
+use std::io::{self, Read, Write};
+
+const TAPE_SIZE: usize = 640000;
+
+type Cell = u8;
+
+struct State {
+    tape: Vec<Cell>,
+    ptr: usize,
+}
+
+fn read_byte(input: &mut dyn Read) -> io::Result<Option<u8>> {
+    let mut buf = [0];
+    loop {
+        match input.read(&mut buf) {
+            Ok(0) => return Ok(None),
+            Ok(_) => return Ok(Some(buf[0])),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn program(s: &mut State, input: &mut dyn Read, output: &mut dyn Write) -> io::Result<()> {
+    s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(1);
+    for _ in 0..3 {
+        match read_byte(input)? {
+            Some(byte) => s.tape[s.ptr] = byte.into(),
+            None => s.tape[s.ptr] = Cell::MAX,
+        }
+    }
+    s.ptr += 1;
+    s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(8);
+    while s.tape[s.ptr] != 0 {
+        s.ptr -= 1;
+        s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(8);
+        s.ptr += 1;
+        s.tape[s.ptr] = s.tape[s.ptr].wrapping_sub(1);
+    }
+    s.ptr -= 1;
+    s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(1);
+    output.write_all(&[s.tape[s.ptr] as u8])?;
+    s.ptr += 1;
+    s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(10);
+    output.write_all(&[s.tape[s.ptr] as u8])?;
+    Ok(())
+}
+
+/// Run the program on a fresh tape.
+pub fn run(input: &mut impl Read, output: &mut impl Write) -> io::Result<()> {
+    let mut state = State {
+        tape: vec![0; TAPE_SIZE],
+        ptr: 0,
+    };
+    program(&mut state, input, output)?;
+    output.flush()
+}
+
+fn main() {
+    let mut output = io::stdout().lock();
+    if let Err(e) = run(&mut io::stdin().lock(), &mut output) {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    }
+}
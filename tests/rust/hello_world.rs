@@ -0,0 +1,93 @@
+// Generated by rust_brain from brainfuck file examples/hello_world.brainfuck, do not edit.
+#![allow(unused_variables, dead_code, clippy::all)]
+
+use std::io::{self, Read, Write};
+
+const TAPE_SIZE: usize = 640000;
+
+type Cell = u8;
+
+struct State {
+    tape: Vec<Cell>,
+    ptr: usize,
+}
+
+fn program(s: &mut State, input: &mut dyn Read, output: &mut dyn Write) -> io::Result<()> {
+    s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(8);
+    while s.tape[s.ptr] != 0 {
+        s.ptr += 1;
+        s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(4);
+        while s.tape[s.ptr] != 0 {
+            s.ptr += 1;
+            s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(2);
+            s.ptr += 1;
+            s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(3);
+            s.ptr += 1;
+            s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(3);
+            s.ptr += 1;
+            s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(1);
+            s.ptr -= 4;
+            s.tape[s.ptr] = s.tape[s.ptr].wrapping_sub(1);
+        }
+        s.ptr += 1;
+        s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(1);
+        s.ptr += 1;
+        s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(1);
+        s.ptr += 1;
+        s.tape[s.ptr] = s.tape[s.ptr].wrapping_sub(1);
+        s.ptr += 2;
+        s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(1);
+        while s.tape[s.ptr] != 0 {
+            s.ptr -= 1;
+        }
+        s.ptr -= 1;
+        s.tape[s.ptr] = s.tape[s.ptr].wrapping_sub(1);
+    }
+    s.ptr += 2;
+    output.write_all(&[s.tape[s.ptr] as u8])?;
+    s.ptr += 1;
+    s.tape[s.ptr] = s.tape[s.ptr].wrapping_sub(3);
+    output.write_all(&[s.tape[s.ptr] as u8])?;
+    s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(7);
+    output.write_all(&[s.tape[s.ptr] as u8; 2])?;
+    s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(3);
+    output.write_all(&[s.tape[s.ptr] as u8])?;
+    s.ptr += 2;
+    output.write_all(&[s.tape[s.ptr] as u8])?;
+    s.ptr -= 1;
+    s.tape[s.ptr] = s.tape[s.ptr].wrapping_sub(1);
+    output.write_all(&[s.tape[s.ptr] as u8])?;
+    s.ptr -= 1;
+    output.write_all(&[s.tape[s.ptr] as u8])?;
+    s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(3);
+    output.write_all(&[s.tape[s.ptr] as u8])?;
+    s.tape[s.ptr] = s.tape[s.ptr].wrapping_sub(6);
+    output.write_all(&[s.tape[s.ptr] as u8])?;
+    s.tape[s.ptr] = s.tape[s.ptr].wrapping_sub(8);
+    output.write_all(&[s.tape[s.ptr] as u8])?;
+    s.ptr += 2;
+    s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(1);
+    output.write_all(&[s.tape[s.ptr] as u8])?;
+    s.ptr += 1;
+    s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(2);
+    output.write_all(&[s.tape[s.ptr] as u8])?;
+    Ok(())
+}
+
+/// Run the program on a fresh tape.
+pub fn run(input: &mut impl Read, output: &mut impl Write) -> io::Result<()> {
+    let mut state = State {
+        tape: vec![0; TAPE_SIZE],
+        ptr: 0,
+    };
+    program(&mut state, input, output)?;
+    output.flush()
+}
+
+fn main() {
+    let mut output = io::stdout().lock();
+    if let Err(e) = run(&mut io::stdin().lock(), &mut output) {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    }
+}
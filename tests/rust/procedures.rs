@@ -0,0 +1,60 @@
+// Generated by rust_brain from brainfuck file tests/llvm_ir/procedures.pbrain, do not edit.
+#![allow(unused_variables, dead_code, clippy::all)]
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+const TAPE_SIZE: usize = 640000;
+
+type Cell = u8;
+type Procedure = fn(&mut State, &mut dyn Read, &mut dyn Write) -> io::Result<()>;
+
+struct State {
+    tape: Vec<Cell>,
+    ptr: usize,
+    procedures: HashMap<Cell, Procedure>,
+}
+
+fn program(s: &mut State, input: &mut dyn Read, output: &mut dyn Write) -> io::Result<()> {
+    s.procedures.insert(s.tape[s.ptr], procedure_0);
+    let procedure = *s.procedures.get(&s.tape[s.ptr]).ok_or_else(|| {
+        io::Error::other(format!("Call of undefined procedure {}", s.tape[s.ptr]))
+    })?;
+    procedure(s, input, output)?;
+    let procedure = *s.procedures.get(&s.tape[s.ptr]).ok_or_else(|| {
+        io::Error::other(format!("Call of undefined procedure {}", s.tape[s.ptr]))
+    })?;
+    procedure(s, input, output)?;
+    s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(10);
+    output.write_all(&[s.tape[s.ptr] as u8])?;
+    Ok(())
+}
+
+fn procedure_0(s: &mut State, input: &mut dyn Read, output: &mut dyn Write) -> io::Result<()> {
+    s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(8);
+    while s.tape[s.ptr] != 0 {
+        s.ptr += 1;
+        s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(8);
+        s.ptr -= 1;
+        s.tape[s.ptr] = s.tape[s.ptr].wrapping_sub(1);
+    }
+    s.ptr += 1;
+    s.tape[s.ptr] = s.tape[s.ptr].wrapping_add(1);
+    output.write_all(&[s.tape[s.ptr] as u8])?;
+    while s.tape[s.ptr] != 0 {
+        s.tape[s.ptr] = s.tape[s.ptr].wrapping_sub(1);
+    }
+    s.ptr -= 1;
+    Ok(())
+}
+
+/// Run the program on a fresh tape.
+pub fn run(input: &mut impl Read, output: &mut impl Write) -> io::Result<()> {
+    let mut state = State {
+        tape: vec![0; TAPE_SIZE],
+        ptr: 0,
+        procedures: HashMap::new(),
+    };
+    program(&mut state, input, output)?;
+    output.flush()
+}